@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use crate::queue::{OverflowPolicy, QueueConfig};
+
+use crate::webrtc::data_channel::{
+    data_channel_init::RTCDataChannelInit,
+    internal::message::message_channel_open::{
+        CHANNEL_PRIORITY_BELOW_NORMAL, CHANNEL_PRIORITY_EXTRA_HIGH, CHANNEL_PRIORITY_HIGH,
+        CHANNEL_PRIORITY_NORMAL,
+    },
+};
+
+const DEFAULT_MAX_BUFFERED_AMOUNT: usize = 1024 * 1024;
+
+/// A data channel to open when connecting, and how it delivers messages
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelConfig {
+    /// The channel's label, as announced to the server
+    pub label: String,
+    /// Whether messages are delivered in the order they were sent
+    pub ordered: bool,
+    /// How long a lost message keeps being retransmitted
+    pub reliability: Reliability,
+    /// The RFC 8832 priority announced to the server in the channel's
+    /// DATA_CHANNEL_OPEN message. The client does not schedule its own sends
+    /// by it.
+    pub priority: ChannelPriority,
+    /// The queue between the client and the data channel's write loop
+    pub send_queue: QueueConfig,
+    /// The queue between the data channel's read loop and the client. A full
+    /// `OverflowPolicy::Block` queue stops reading, so the server backs off.
+    pub receive_queue: QueueConfig,
+    /// How many bytes may wait in the SCTP send buffer before messages are
+    /// held back in `send_queue`
+    pub max_buffered_amount: usize,
+}
+
+impl ChannelConfig {
+    /// An unordered channel that never retransmits lost messages. Its queues
+    /// drop the oldest message when full, favouring fresh data.
+    pub fn unreliable(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            ordered: false,
+            reliability: Reliability::MaxRetransmits(0),
+            priority: ChannelPriority::Normal,
+            send_queue: QueueConfig::new(1024, OverflowPolicy::DropOldest),
+            receive_queue: QueueConfig::new(1024, OverflowPolicy::DropOldest),
+            max_buffered_amount: DEFAULT_MAX_BUFFERED_AMOUNT,
+        }
+    }
+
+    /// An ordered channel that retransmits lost messages until they are
+    /// delivered. Its queues block when full.
+    pub fn reliable(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            ordered: true,
+            reliability: Reliability::Reliable,
+            priority: ChannelPriority::Normal,
+            send_queue: QueueConfig::default(),
+            receive_queue: QueueConfig::default(),
+            max_buffered_amount: DEFAULT_MAX_BUFFERED_AMOUNT,
+        }
+    }
+}
+
+impl Default for ChannelConfig {
+    /// An unreliable channel labelled "data"
+    fn default() -> Self {
+        Self::unreliable("data")
+    }
+}
+
+/// The partial reliability policy of a data channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reliability {
+    /// Lost messages are retransmitted until they are delivered
+    Reliable,
+    /// A lost message is retransmitted at most this many times, then abandoned
+    MaxRetransmits(u16),
+    /// A lost message is retransmitted until it is this old, then abandoned.
+    /// Lifetimes are rounded down to milliseconds, up to `u16::MAX`.
+    MaxLifetime(Duration),
+}
+
+/// The priority levels of <https://www.rfc-editor.org/rfc/rfc8832#section-8.2.2>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChannelPriority {
+    BelowNormal,
+    #[default]
+    Normal,
+    High,
+    ExtraHigh,
+}
+
+impl From<&ChannelConfig> for RTCDataChannelInit {
+    fn from(config: &ChannelConfig) -> Self {
+        let (max_retransmits, max_packet_life_time) = match config.reliability {
+            Reliability::Reliable => (None, None),
+            Reliability::MaxRetransmits(max_retransmits) => (Some(max_retransmits), None),
+            Reliability::MaxLifetime(lifetime) => (
+                None,
+                Some(lifetime.as_millis().min(u16::MAX as u128) as u16),
+            ),
+        };
+
+        let priority = match config.priority {
+            ChannelPriority::BelowNormal => CHANNEL_PRIORITY_BELOW_NORMAL,
+            ChannelPriority::Normal => CHANNEL_PRIORITY_NORMAL,
+            ChannelPriority::High => CHANNEL_PRIORITY_HIGH,
+            ChannelPriority::ExtraHigh => CHANNEL_PRIORITY_EXTRA_HIGH,
+        };
+
+        Self {
+            ordered: Some(config.ordered),
+            max_packet_life_time,
+            max_retransmits,
+            priority: Some(priority),
+        }
+    }
+}
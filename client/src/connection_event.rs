@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+use crate::error::ConnectError;
+
+/// The lifecycle of a connection, as reported through `SocketIo::to_client_event_receiver`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// Signaling and the ICE, DTLS and SCTP handshakes are in progress
+    Connecting,
+    /// The data channel is open and messages can be sent and received
+    Open,
+    /// ICE has not heard from the server for its disconnected timeout, the
+    /// connection may still recover
+    Disconnected,
+    /// The connection failed and `Socket` is opening a new one, as configured
+    /// by `SocketConfig::reconnect`, or an ICE restart is opening one since
+    /// the server cannot restart sessions. Counts attempts from 1.
+    Reconnecting { attempt: u32 },
+    /// A new connection replaced the failed one, or the one an ICE restart
+    /// moved away from when the server cannot restart sessions, and the
+    /// channels are open again. The server knows the client by the new id
+    /// token.
+    Reconnected { id_token: String },
+    /// The server answered an ICE restart, and the connection is moving to
+    /// the new candidates with its channels open. The server knows the
+    /// client by the id token it answered the restart with.
+    IceRestarted { id_token: String },
+    /// The connection is closed and will not recover
+    Closed(CloseReason),
+}
+
+/// Why a connection was closed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CloseReason {
+    /// The client closed the connection through `SocketIo::to_server_disconnect_sender`
+    Local,
+    /// The connection could not be established
+    ConnectFailed(ConnectError),
+    /// ICE did not hear from the server for its failed timeout
+    IceFailed,
+    /// The server did not answer consent checks for `SocketConfig::consent_timeout`,
+    /// or refused them
+    ServerUnreachable,
+    /// The DTLS transport failed
+    DtlsFailed,
+    /// The DTLS transport was closed
+    DtlsClosed,
+    /// The SCTP association was closed
+    SctpClosed,
+    /// The data channel stopped delivering messages
+    ChannelClosed,
+    /// Every reconnect attempt failed, with the last attempt's error
+    ReconnectFailed(ConnectError),
+}
+
+impl CloseReason {
+    /// Whether a reconnect may replace a connection closed for this reason
+    pub(crate) fn is_recoverable(&self) -> bool {
+        !matches!(
+            self,
+            Self::Local | Self::ConnectFailed(_) | Self::ReconnectFailed(_)
+        )
+    }
+}
+
+// EventSender
+#[derive(Clone)]
+pub(crate) struct EventSender {
+    sender: Arc<watch::Sender<ConnectionEvent>>,
+}
+
+impl EventSender {
+    pub(crate) fn new() -> (Self, watch::Receiver<ConnectionEvent>) {
+        let (sender, receiver) = watch::channel(ConnectionEvent::Connecting);
+        (
+            Self {
+                sender: Arc::new(sender),
+            },
+            receiver,
+        )
+    }
+
+    /// Publishes an event, unless it repeats the current one or the
+    /// connection has already been closed
+    pub(crate) fn send(&self, event: ConnectionEvent) {
+        {
+            let current = self.sender.borrow();
+            if *current == event || matches!(*current, ConnectionEvent::Closed(_)) {
+                return;
+            }
+        }
+
+        log::info!("connection event: {:?}", event);
+        let _ = self.sender.send(event);
+    }
+
+    pub(crate) fn current(&self) -> ConnectionEvent {
+        self.sender.borrow().clone()
+    }
+
+    /// Reports that a peer connection started connecting, unless a reconnect
+    /// attempt already covers it
+    pub(crate) fn connecting(&self) {
+        if !matches!(*self.sender.borrow(), ConnectionEvent::Reconnecting { .. }) {
+            self.send(ConnectionEvent::Connecting);
+        }
+    }
+
+    /// Reports that a disconnected connection is hearing from the server again
+    pub(crate) fn recovered(&self) {
+        if *self.sender.borrow() == ConnectionEvent::Disconnected {
+            self.send(ConnectionEvent::Open);
+        }
+    }
+
+    pub(crate) fn close(&self, reason: CloseReason) {
+        self.send(ConnectionEvent::Closed(reason));
+    }
+}
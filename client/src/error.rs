@@ -0,0 +1,141 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::webrtc::{dtls, error::Error as RTCError, ice, sctp};
+
+/// The reason a call to `Socket::connect` failed
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConnectError {
+    /// The signaling server answered the session request with a non-success status
+    #[error("session request rejected with status {status}: {body}")]
+    Rejected { status: u16, body: String },
+    /// The session request could not be sent, or its response could not be read
+    #[error("session request failed: {0}")]
+    Request(String),
+    /// Every signaling attempt allowed by `SignalingConfig::max_attempts` failed
+    #[error("session request failed after {attempts} attempts: {error}")]
+    SignalingExhausted { attempts: u32, error: String },
+    /// The connection did not open within `SignalingConfig::handshake_timeout`
+    #[error("connection did not open within {0:?}")]
+    Timeout(Duration),
+    /// The signaling server's response was not a valid session JSON object
+    #[error("malformed session response: {0}")]
+    MalformedSession(String),
+    /// The session description could not be created or applied
+    #[error("sdp error: {0}")]
+    Sdp(String),
+    /// ICE could not establish a candidate pair with the server
+    #[error("ice failure: {0}")]
+    Ice(String),
+    /// The DTLS handshake with the server failed
+    #[error("dtls handshake failure: {0}")]
+    Dtls(String),
+    /// The SCTP association or the data channel could not be established
+    #[error("sctp failure: {0}")]
+    Sctp(String),
+    /// The server's address is already used by another connection on the
+    /// `UdpMux` passed to `SocketConfig::udp_mux`
+    #[error("server address {0} is used by another connection on the UDP mux")]
+    AddressInUse(SocketAddr),
+    /// The connection was closed before the data channel opened
+    #[error("connection closed before the data channel opened")]
+    Closed,
+    /// Any other failure while setting up the connection
+    #[error("{0}")]
+    Other(String),
+}
+
+/// The reason `MessageSender` did not queue a message, which is handed back
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SendError {
+    /// The queue is full and its policy is `OverflowPolicy::Block`. Only
+    /// `MessageSender::try_send` returns this.
+    #[error("queue is full")]
+    Full(Box<[u8]>),
+    /// The receiving half of the queue was dropped, the connection is closed
+    #[error("queue is closed")]
+    Closed(Box<[u8]>),
+    /// The message is larger than the max-message-size the server advertised
+    #[error("message of {} bytes exceeds the server's max-message-size of {max_message_size} bytes", .message.len())]
+    TooLarge {
+        message: Box<[u8]>,
+        max_message_size: usize,
+    },
+}
+
+/// The reason `MessageReceiver::try_recv` returned no message
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// No message is queued yet
+    #[error("queue is empty")]
+    Empty,
+    /// The queue is empty and every `MessageSender` was dropped, the
+    /// connection is closed
+    #[error("queue is closed")]
+    Disconnected,
+}
+
+impl From<ice::Error> for ConnectError {
+    fn from(err: ice::Error) -> Self {
+        match err {
+            ice::Error::ErrUdpMuxAddressInUse(addr) => ConnectError::AddressInUse(addr),
+            err => ConnectError::Ice(err.to_string()),
+        }
+    }
+}
+
+impl From<dtls::Error> for ConnectError {
+    fn from(err: dtls::Error) -> Self {
+        ConnectError::Dtls(err.to_string())
+    }
+}
+
+impl From<sctp::Error> for ConnectError {
+    fn from(err: sctp::Error) -> Self {
+        ConnectError::Sctp(err.to_string())
+    }
+}
+
+impl From<RTCError> for ConnectError {
+    fn from(err: RTCError) -> Self {
+        match err {
+            RTCError::Ice(err) => err.into(),
+            RTCError::Dtls(err) => err.into(),
+            RTCError::Sctp(err) => err.into(),
+            RTCError::Data(err) => ConnectError::Sctp(err.to_string()),
+            RTCError::Sdp(err) => ConnectError::Sdp(err.to_string()),
+            RTCError::ErrNoRemoteDescription
+            | RTCError::ErrSessionDescriptionNoFingerprint
+            | RTCError::ErrSessionDescriptionInvalidFingerprint
+            | RTCError::ErrSessionDescriptionConflictingFingerprints
+            | RTCError::ErrSessionDescriptionMissingIceUfrag
+            | RTCError::ErrSessionDescriptionMissingIcePwd
+            | RTCError::ErrSessionDescriptionConflictingIceUfrag
+            | RTCError::ErrSessionDescriptionConflictingIcePwd
+            | RTCError::ErrSDPDoesNotMatchOffer
+            | RTCError::ErrSDPDoesNotMatchAnswer
+            | RTCError::ErrPeerConnSDPTypeInvalidValue
+            | RTCError::ErrPeerConnSDPTypeInvalidValueSetLocalDescription
+            | RTCError::ErrSignalingStateCannotRollback
+            | RTCError::ErrSignalingStateProposedTransitionInvalid => {
+                ConnectError::Sdp(err.to_string())
+            }
+            RTCError::ErrICEConnectionNotStarted
+            | RTCError::ErrICECandidateTypeUnknown
+            | RTCError::ErrICEAgentNotExist
+            | RTCError::ErrICERoleUnknown
+            | RTCError::ErrICETransportNotInNew => ConnectError::Ice(err.to_string()),
+            RTCError::ErrInvalidDTLSStart
+            | RTCError::ErrNonCertificate
+            | RTCError::ErrSCTPTransportDTLS => ConnectError::Dtls(err.to_string()),
+            RTCError::ErrSCTPNotEstablished
+            | RTCError::ErrDetachBeforeOpened
+            | RTCError::ErrMaxDataChannelID => ConnectError::Sctp(err.to_string()),
+            RTCError::ErrConnectionClosed => ConnectError::Closed,
+            err => ConnectError::Other(err.to_string()),
+        }
+    }
+}
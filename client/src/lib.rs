@@ -7,9 +7,27 @@ extern crate lazy_static;
 extern crate serde_derive;
 
 mod addr_cell;
+mod channel;
+mod connection_event;
+mod error;
+mod queue;
+mod reconnect;
+mod signaling;
 mod socket;
+mod socket_config;
+mod stats;
+mod udp_mux;
 
 pub use addr_cell::{AddrCell, ServerAddr};
-pub use socket::Socket;
+pub use channel::{ChannelConfig, ChannelPriority, Reliability};
+pub use connection_event::{CloseReason, ConnectionEvent};
+pub use error::{ConnectError, SendError, TryRecvError};
+pub use queue::{MessageReceiver, MessageSender, OverflowPolicy, QueueConfig};
+pub use reconnect::ReconnectConfig;
+pub use signaling::{HttpSignaler, Signaler, SignalingAnswer, SignalingConfig};
+pub use socket::{ChannelIo, Socket, SocketIo};
+pub use socket_config::{DtlsCipherSuite, IpFamily, MulticastDns, SocketConfig};
+pub use stats::{IceStats, SctpStats, Stats};
+pub use udp_mux::UdpMux;
 
 mod webrtc;
@@ -0,0 +1,263 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use tokio::sync::Notify;
+
+use crate::error::{SendError, TryRecvError};
+
+/// What a full queue does with a message that does not fit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// The sender waits until the receiver makes room
+    #[default]
+    Block,
+    /// The oldest queued message is dropped to make room
+    DropOldest,
+    /// The new message is dropped
+    DropNewest,
+}
+
+/// How many messages a channel's queue holds, and what happens when it is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueConfig {
+    /// The most messages the queue holds at once, at least 1
+    pub capacity: usize,
+    pub overflow: OverflowPolicy,
+}
+
+impl QueueConfig {
+    pub fn new(capacity: usize, overflow: OverflowPolicy) -> Self {
+        Self { capacity, overflow }
+    }
+}
+
+impl Default for QueueConfig {
+    /// 1024 messages, blocking the sender when full
+    fn default() -> Self {
+        Self::new(1024, OverflowPolicy::Block)
+    }
+}
+
+// Shared
+struct Shared {
+    config: QueueConfig,
+    state: Mutex<State>,
+    // wakes the receiver when a message is queued or the last sender is dropped
+    receiver_notify: Notify,
+    // wakes blocked senders when room frees up or the receiver is dropped
+    sender_notify: Notify,
+    dropped: AtomicUsize,
+    saturated: AtomicBool,
+    // the server's max-message-size, 0 until the data channel opens
+    max_message_size: AtomicUsize,
+}
+
+impl Shared {
+    fn max_message_size(&self) -> Option<usize> {
+        match self.max_message_size.load(Ordering::SeqCst) {
+            0 => None,
+            max_message_size => Some(max_message_size),
+        }
+    }
+}
+
+// State
+struct State {
+    messages: VecDeque<Box<[u8]>>,
+    senders: usize,
+    receiver_dropped: bool,
+}
+
+pub(crate) fn queue(config: QueueConfig) -> (MessageSender, MessageReceiver) {
+    let shared = Arc::new(Shared {
+        config,
+        state: Mutex::new(State {
+            messages: VecDeque::new(),
+            senders: 1,
+            receiver_dropped: false,
+        }),
+        receiver_notify: Notify::new(),
+        sender_notify: Notify::new(),
+        dropped: AtomicUsize::new(0),
+        saturated: AtomicBool::new(false),
+        max_message_size: AtomicUsize::new(0),
+    });
+
+    (
+        MessageSender {
+            shared: Arc::clone(&shared),
+        },
+        MessageReceiver { shared },
+    )
+}
+
+/// The sending half of a channel's message queue
+pub struct MessageSender {
+    shared: Arc<Shared>,
+}
+
+impl MessageSender {
+    /// Queues a message, waiting for room if the queue is full and its
+    /// policy is `OverflowPolicy::Block`. Fails once the receiving half is
+    /// dropped, or if the message exceeds the server's max-message-size.
+    pub async fn send(&self, message: Box<[u8]>) -> Result<(), SendError> {
+        let mut message = message;
+        loop {
+            let room = self.shared.sender_notify.notified();
+            match self.try_send(message) {
+                Err(SendError::Full(returned)) => message = returned,
+                result => return result,
+            }
+            room.await;
+        }
+    }
+
+    /// Queues a message without waiting. A full queue whose policy is
+    /// `OverflowPolicy::Block` hands the message back as `SendError::Full`.
+    pub fn try_send(&self, message: Box<[u8]>) -> Result<(), SendError> {
+        if let Some(max_message_size) = self.max_message_size() {
+            if message.len() > max_message_size {
+                return Err(SendError::TooLarge {
+                    message,
+                    max_message_size,
+                });
+            }
+        }
+
+        let mut state = self.shared.state.lock().unwrap();
+        if state.receiver_dropped {
+            return Err(SendError::Closed(message));
+        }
+
+        if state.messages.len() >= self.shared.config.capacity.max(1) {
+            match self.shared.config.overflow {
+                OverflowPolicy::Block => return Err(SendError::Full(message)),
+                OverflowPolicy::DropOldest => {
+                    state.messages.pop_front();
+                    self.shared.dropped.fetch_add(1, Ordering::SeqCst);
+                }
+                OverflowPolicy::DropNewest => {
+                    self.shared.dropped.fetch_add(1, Ordering::SeqCst);
+                    return Ok(());
+                }
+            }
+        }
+
+        state.messages.push_back(message);
+        drop(state);
+
+        self.shared.receiver_notify.notify_one();
+        Ok(())
+    }
+
+    /// Whether the receiving half has been dropped
+    pub fn is_closed(&self) -> bool {
+        self.shared.state.lock().unwrap().receiver_dropped
+    }
+
+    /// How many messages the overflow policy has dropped so far
+    pub fn dropped(&self) -> usize {
+        self.shared.dropped.load(Ordering::SeqCst)
+    }
+
+    /// The largest message the server accepts, known once the data channel opens
+    pub fn max_message_size(&self) -> Option<usize> {
+        self.shared.max_message_size()
+    }
+
+    /// Whether the transport has more unacknowledged data buffered than the
+    /// channel's `max_buffered_amount`. Messages stay in this queue until it
+    /// drains, so a saturated transport eventually fills the queue.
+    pub fn is_saturated(&self) -> bool {
+        self.shared.saturated.load(Ordering::SeqCst)
+    }
+}
+
+impl Clone for MessageSender {
+    fn clone(&self) -> Self {
+        self.shared.state.lock().unwrap().senders += 1;
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl Drop for MessageSender {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.senders -= 1;
+        if state.senders == 0 {
+            drop(state);
+            self.shared.receiver_notify.notify_one();
+        }
+    }
+}
+
+/// The receiving half of a channel's message queue
+pub struct MessageReceiver {
+    shared: Arc<Shared>,
+}
+
+impl MessageReceiver {
+    /// Waits for the next message. Returns None once every sender is dropped
+    /// and the queue is empty.
+    pub async fn recv(&mut self) -> Option<Box<[u8]>> {
+        loop {
+            let queued = self.shared.receiver_notify.notified();
+            match self.take() {
+                Ok(message) => return Some(message),
+                Err(TryRecvError::Disconnected) => return None,
+                Err(TryRecvError::Empty) => {}
+            }
+            queued.await;
+        }
+    }
+
+    /// Takes the next message without waiting
+    pub fn try_recv(&mut self) -> Result<Box<[u8]>, TryRecvError> {
+        self.take()
+    }
+
+    fn take(&self) -> Result<Box<[u8]>, TryRecvError> {
+        let mut state = self.shared.state.lock().unwrap();
+        match state.messages.pop_front() {
+            Some(message) => {
+                drop(state);
+                self.shared.sender_notify.notify_waiters();
+                Ok(message)
+            }
+            None if state.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// How many messages the overflow policy has dropped so far
+    pub fn dropped(&self) -> usize {
+        self.shared.dropped.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn set_saturated(&self, saturated: bool) {
+        self.shared.saturated.store(saturated, Ordering::SeqCst);
+    }
+
+    pub(crate) fn set_max_message_size(&self, max_message_size: usize) {
+        self.shared
+            .max_message_size
+            .store(max_message_size, Ordering::SeqCst);
+    }
+}
+
+impl Drop for MessageReceiver {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.receiver_dropped = true;
+        state.messages.clear();
+        drop(state);
+        self.shared.sender_notify.notify_waiters();
+    }
+}
@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use crate::signaling::backoff;
+
+/// Controls how `Socket` replaces a failed connection. Set it through
+/// `SocketConfig::reconnect` to opt in; without it a failure closes the
+/// connection for good.
+///
+/// Each attempt signals again and opens a new peer connection, so the server
+/// sees a new client and hands out a new id token. The `SocketIo` senders and
+/// receivers stay the same, and messages queued while reconnecting are sent
+/// once the new data channels open.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectConfig {
+    /// How many reconnect attempts are made before closing the connection.
+    /// `None` retries forever.
+    pub max_attempts: Option<u32>,
+    /// The delay before the first attempt
+    pub initial_backoff: Duration,
+    /// The largest delay between two attempts
+    pub max_backoff: Duration,
+    /// The factor the delay grows by after each failed attempt
+    pub backoff_multiplier: f64,
+    /// The fraction of each delay that is randomized, between 0.0 and 1.0
+    pub jitter: f64,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: Some(5),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+            backoff_multiplier: 2.0,
+            jitter: 0.5,
+        }
+    }
+}
+
+impl ReconnectConfig {
+    /// Returns the delay to wait before the given attempt, counting from 1
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        backoff(
+            self.initial_backoff,
+            self.max_backoff,
+            self.backoff_multiplier,
+            self.jitter,
+            attempt,
+        )
+    }
+
+    pub(crate) fn attempts_exhausted(&self, attempt: u32) -> bool {
+        match self.max_attempts {
+            Some(max_attempts) => attempt >= max_attempts,
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,700 @@
+use std::{collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+use log::warn;
+use rand::Rng;
+use reqwest::{Client as HttpClient, Response};
+use tinyjson::JsonValue;
+use tokio::time::sleep;
+
+use crate::error::ConnectError;
+
+/// The server's reply to the client's offer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignalingAnswer {
+    /// The server's answer SDP
+    pub sdp: String,
+    /// The server's ICE candidates, in `candidate:...` attribute form, next
+    /// to any `a=candidate` lines in `sdp`. An empty string marks the end of
+    /// the candidates.
+    pub candidates: Vec<String>,
+    /// The token the server uses to identify this client
+    pub id_token: String,
+}
+
+/// Exchanges the client's offer for the server's answer. `Socket::connect`
+/// uses `HttpSignaler`; implement this to signal over another transport.
+#[async_trait]
+pub trait Signaler: Send + Sync {
+    async fn signal(&self, offer_sdp: String) -> Result<SignalingAnswer, ConnectError>;
+
+    /// Whether the server can restart ICE on a session it already has. If
+    /// so, an ICE restart offer is signaled with `restart` and the DTLS and
+    /// SCTP sessions carry on over the new candidates. Otherwise an ICE
+    /// restart opens a new connection in place of the old one, which is what
+    /// webrtc-unreliable servers need since they answer every offer with a
+    /// new session. Defaults to false.
+    fn restarts_sessions(&self) -> bool {
+        false
+    }
+
+    /// Exchanges an ICE restart offer for the server's answer, for the
+    /// session the server identified by `id_token` when it last answered.
+    /// The server must apply the offer to that session, answering with new
+    /// ICE credentials and keeping its DTLS and SCTP associations (RFC 8445
+    /// Section 9). Only called when `restarts_sessions` is true, and fails
+    /// by default.
+    async fn restart(
+        &self,
+        id_token: &str,
+        offer_sdp: String,
+    ) -> Result<SignalingAnswer, ConnectError> {
+        let _ = (id_token, offer_sdp);
+        Err(ConnectError::Other(
+            "the signaler cannot restart sessions".to_string(),
+        ))
+    }
+
+    /// Whether the client's candidates are trickled. If so, the offer is
+    /// signaled as soon as it is created, and each candidate is passed to
+    /// `trickle_candidate` as it is gathered. Otherwise the offer waits for
+    /// gathering to complete and carries every candidate. Defaults to false.
+    fn trickles(&self) -> bool {
+        false
+    }
+
+    /// Sends one of the client's candidates, in `candidate:...` attribute
+    /// form, or None once every candidate has been sent. `ufrag` is the
+    /// `a=ice-ufrag` of the offer the candidate belongs to, which changes
+    /// with each ICE restart. Gathering starts before the offer is signaled,
+    /// so this may be called before `signal` or `restart` is called with
+    /// that offer; a signaler that sends candidates for a session should
+    /// hold them until it knows the session. Errors are logged and the
+    /// candidate dropped.
+    async fn trickle_candidate(
+        &self,
+        _ufrag: &str,
+        _candidate: Option<String>,
+    ) -> Result<(), ConnectError> {
+        Ok(())
+    }
+}
+
+/// Controls how `HttpSignaler` retries the signaling request, and how long
+/// `Socket::connect` waits for the connection to open
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalingConfig {
+    /// How many times the signaling request is sent before giving up.
+    /// `None` retries until `handshake_timeout` runs out.
+    pub max_attempts: Option<u32>,
+    /// The delay before the first retry
+    pub initial_backoff: Duration,
+    /// The largest delay between two retries
+    pub max_backoff: Duration,
+    /// The factor the delay grows by after each failed attempt
+    pub backoff_multiplier: f64,
+    /// The fraction of each delay that is randomized, between 0.0 and 1.0
+    pub jitter: f64,
+    /// How long a single signaling request may take
+    pub request_timeout: Duration,
+    /// How long signaling, ICE, DTLS, SCTP and the data channel opening may
+    /// take together. `None` waits forever.
+    pub handshake_timeout: Option<Duration>,
+}
+
+impl Default for SignalingConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: Some(10),
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(8),
+            backoff_multiplier: 2.0,
+            jitter: 0.5,
+            request_timeout: Duration::from_secs(5),
+            handshake_timeout: Some(Duration::from_secs(30)),
+        }
+    }
+}
+
+impl SignalingConfig {
+    /// Returns the delay to wait after the given failed attempt, counting from 1
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        backoff(
+            self.initial_backoff,
+            self.max_backoff,
+            self.backoff_multiplier,
+            self.jitter,
+            attempt,
+        )
+    }
+
+    pub(crate) fn attempts_exhausted(&self, attempt: u32) -> bool {
+        match self.max_attempts {
+            Some(max_attempts) => attempt >= max_attempts,
+            None => false,
+        }
+    }
+}
+
+/// Grows `initial_backoff` by `multiplier` for each attempt after the first,
+/// capped at `max_backoff` and randomized by `jitter`
+pub(crate) fn backoff(
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: f64,
+    attempt: u32,
+) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+    let delay =
+        (initial_backoff.as_secs_f64() * multiplier.powi(exponent)).min(max_backoff.as_secs_f64());
+
+    let jitter = jitter.clamp(0.0, 1.0);
+    let factor = if jitter > 0.0 {
+        1.0 + rand::thread_rng().gen_range(-jitter..=jitter)
+    } else {
+        1.0
+    };
+
+    Duration::from_secs_f64((delay * factor).max(0.0))
+}
+
+/// Signals by POSTing the offer to a webrtc-unreliable server, retrying as
+/// configured by `SignalingConfig`
+pub struct HttpSignaler {
+    http_client: HttpClient,
+    server_url: String,
+    auth_bytes_opt: Option<Vec<u8>>,
+    auth_headers_opt: Option<Vec<(String, String)>>,
+    config: SignalingConfig,
+}
+
+impl HttpSignaler {
+    pub fn new(
+        server_url: &str,
+        auth_bytes_opt: Option<Vec<u8>>,
+        auth_headers_opt: Option<Vec<(String, String)>>,
+        config: SignalingConfig,
+    ) -> Self {
+        Self {
+            http_client: HttpClient::new(),
+            server_url: server_url.to_string(),
+            auth_bytes_opt,
+            auth_headers_opt,
+            config,
+        }
+    }
+
+    async fn send(&self, offer_sdp: &str) -> Result<Response, ConnectError> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let mut request = self
+                .http_client
+                .post(&self.server_url)
+                .timeout(self.config.request_timeout)
+                .header("Content-Length", offer_sdp.len())
+                .body(offer_sdp.to_string());
+            if let Some(auth_bytes) = &self.auth_bytes_opt {
+                let base64_encoded = base64::encode(auth_bytes);
+                request = request.header("Authorization", &base64_encoded);
+            }
+            if let Some(auth_headers) = &self.auth_headers_opt {
+                for (key, value) in auth_headers {
+                    request = request.header(key, value);
+                }
+            }
+
+            match request.send().await {
+                Ok(response) => return Ok(response),
+                Err(err) => {
+                    warn!(
+                        "Could not send request (attempt {}), original error: {:?}",
+                        attempt, err
+                    );
+                    if self.config.attempts_exhausted(attempt) {
+                        return Err(ConnectError::SignalingExhausted {
+                            attempts: attempt,
+                            error: err.to_string(),
+                        });
+                    }
+                    sleep(self.config.backoff(attempt)).await;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Signaler for HttpSignaler {
+    async fn signal(&self, offer_sdp: String) -> Result<SignalingAnswer, ConnectError> {
+        let response = self.send(&offer_sdp).await?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let body = response.text().await.unwrap_or_default();
+            return Err(ConnectError::Rejected { status, body });
+        }
+
+        // get the body of the response as a string
+        let response_string = response
+            .text()
+            .await
+            .map_err(|err| ConnectError::Request(err.to_string()))?;
+
+        get_session_response(response_string.as_str())
+    }
+}
+
+fn get_session_response(input: &str) -> Result<SignalingAnswer, ConnectError> {
+    let json_obj: JsonValue = input
+        .parse()
+        .map_err(|_| ConnectError::MalformedSession("could not parse response JSON".to_string()))?;
+
+    let sdp_json = field(&json_obj, "sdp");
+
+    let sdp_opt: Option<&String> = sdp_json
+        .and_then(|sdp_json| field(sdp_json, "answer"))
+        .and_then(|answer| field(answer, "sdp"))
+        .and_then(|sdp| sdp.get());
+    let Some(sdp) = sdp_opt.cloned() else {
+        return Err(ConnectError::MalformedSession(
+            "missing sdp.answer.sdp".to_string(),
+        ));
+    };
+
+    // one candidate or an array of them, or none when the answer SDP
+    // carries them as a=candidate lines
+    let candidates = match sdp_json.and_then(|sdp_json| field(sdp_json, "candidate")) {
+        None => Vec::new(),
+        Some(JsonValue::Array(entries)) => entries
+            .iter()
+            .map(candidate_from_json)
+            .collect::<Result<_, _>>()?,
+        Some(entry) => vec![candidate_from_json(entry)?],
+    };
+
+    let id_token_opt: Option<&String> = field(&json_obj, "id").and_then(|id| id.get());
+    let Some(id_token) = id_token_opt.cloned() else {
+        return Err(ConnectError::MalformedSession("missing id".to_string()));
+    };
+
+    Ok(SignalingAnswer {
+        sdp,
+        candidates,
+        id_token,
+    })
+}
+
+/// Reads a candidate given as an `RTCIceCandidateInit` object, or as its
+/// bare `candidate:...` string
+fn candidate_from_json(entry: &JsonValue) -> Result<String, ConnectError> {
+    let candidate_opt: Option<&String> = match entry {
+        JsonValue::String(candidate) => Some(candidate),
+        _ => field(entry, "candidate").and_then(|candidate| candidate.get()),
+    };
+    candidate_opt
+        .cloned()
+        .ok_or_else(|| ConnectError::MalformedSession("malformed sdp.candidate entry".to_string()))
+}
+
+/// Looks a key up in a JSON object, returning None instead of panicking like
+/// indexing does when the value is not an object or lacks the key
+fn field<'a>(value: &'a JsonValue, key: &str) -> Option<&'a JsonValue> {
+    let object: &HashMap<String, JsonValue> = value.get()?;
+    object.get(key)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{CloseReason, ConnectionEvent, Socket, SocketIo};
+    use futures_util::stream;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::{io, net::SocketAddr};
+    use tokio::sync::Mutex;
+    use webrtc_unreliable::{MessageType, Server, SessionEndpoint};
+
+    /// Hands the offer straight to a webrtc-unreliable server's session
+    /// endpoint, with no HTTP in between, and wraps its answer with an id
+    /// token the way naia-server-socket does. Each session gets a new id
+    /// token, `token-1` first.
+    struct InMemorySignaler {
+        session_endpoint: Mutex<SessionEndpoint>,
+        sessions: AtomicUsize,
+    }
+
+    impl InMemorySignaler {
+        fn new(session_endpoint: SessionEndpoint) -> Self {
+            Self {
+                session_endpoint: Mutex::new(session_endpoint),
+                sessions: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Signaler for InMemorySignaler {
+        async fn signal(&self, offer_sdp: String) -> Result<SignalingAnswer, ConnectError> {
+            let session = self.sessions.fetch_add(1, Ordering::SeqCst) + 1;
+            let mut session_endpoint = self.session_endpoint.lock().await;
+            let response = session_endpoint
+                .session_request(stream::iter([Ok::<_, io::Error>(offer_sdp)]))
+                .await
+                .map_err(|err| ConnectError::Request(err.to_string()))?;
+            get_session_response(&format!(
+                "{{\"sdp\":{response},\"id\":\"token-{session}\"}}"
+            ))
+        }
+    }
+
+    /// Starts a server on loopback that echoes every message back
+    async fn echo_server() -> SessionEndpoint {
+        let addr: SocketAddr = {
+            let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+            socket.local_addr().unwrap()
+        };
+        let mut server = Server::new(addr, addr).await.unwrap();
+        let session_endpoint = server.session_endpoint();
+
+        tokio::spawn(async move {
+            loop {
+                let (message, remote_addr) = match server.recv().await {
+                    Ok(received) => (received.message.as_ref().to_vec(), received.remote_addr),
+                    Err(_) => continue,
+                };
+                let _ = server
+                    .send(&message, MessageType::Binary, &remote_addr)
+                    .await;
+            }
+        });
+
+        session_endpoint
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_in_memory_signaler() {
+        let signaler = InMemorySignaler::new(echo_server().await);
+
+        let (socket, mut socket_io) = Socket::new();
+        socket.connect_with_signaler(signaler).await.unwrap();
+
+        let id_token = (&mut socket_io.to_client_id_receiver).await.unwrap();
+        assert_eq!(id_token, Ok("token-1".to_string()));
+
+        socket_io
+            .to_server_sender
+            .send(b"echo".to_vec().into_boxed_slice())
+            .await
+            .unwrap();
+        let echoed =
+            tokio::time::timeout(Duration::from_secs(5), socket_io.to_client_receiver.recv())
+                .await
+                .unwrap();
+        assert_eq!(echoed.as_deref(), Some(&b"echo"[..]));
+    }
+
+    async fn echo(socket_io: &mut SocketIo, message: &[u8]) {
+        socket_io
+            .to_server_sender
+            .send(message.to_vec().into_boxed_slice())
+            .await
+            .unwrap();
+        let echoed =
+            tokio::time::timeout(Duration::from_secs(5), socket_io.to_client_receiver.recv())
+                .await
+                .unwrap();
+        assert_eq!(echoed.as_deref(), Some(message));
+    }
+
+    #[tokio::test]
+    async fn test_restart_ice_reconnects_when_sessions_cannot_restart() {
+        let signaler = InMemorySignaler::new(echo_server().await);
+
+        let (socket, mut socket_io) = Socket::new();
+        socket.connect_with_signaler(signaler).await.unwrap();
+        echo(&mut socket_io, b"before").await;
+
+        // webrtc-unreliable answers every offer with a new session, so each
+        // restart replaces the connection
+        for session in 2..=3 {
+            let id_token = socket_io.restart_ice().await.unwrap();
+            assert_eq!(id_token, format!("token-{session}"));
+            assert_eq!(
+                *socket_io.to_client_event_receiver.borrow(),
+                ConnectionEvent::Reconnected { id_token }
+            );
+            echo(&mut socket_io, format!("after {session}").as_bytes()).await;
+        }
+    }
+
+    /// Claims to restart sessions, but never answers restart offers
+    struct StalledRestartSignaler(InMemorySignaler);
+
+    #[async_trait]
+    impl Signaler for StalledRestartSignaler {
+        async fn signal(&self, offer_sdp: String) -> Result<SignalingAnswer, ConnectError> {
+            self.0.signal(offer_sdp).await
+        }
+
+        fn restarts_sessions(&self) -> bool {
+            true
+        }
+
+        async fn restart(
+            &self,
+            _id_token: &str,
+            _offer_sdp: String,
+        ) -> Result<SignalingAnswer, ConnectError> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_wins_over_restart_in_flight() {
+        let signaler = StalledRestartSignaler(InMemorySignaler::new(echo_server().await));
+
+        let (socket, socket_io) = Socket::new();
+        socket.connect_with_signaler(signaler).await.unwrap();
+
+        let socket_io = Arc::new(socket_io);
+        let restarting_socket_io = Arc::clone(&socket_io);
+        let restart = tokio::spawn(async move { restarting_socket_io.restart_ice().await });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        socket_io
+            .to_server_disconnect_sender
+            .send(())
+            .await
+            .unwrap();
+        let restarted = tokio::time::timeout(Duration::from_secs(5), restart)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(restarted, Err(ConnectError::Closed));
+
+        let mut events = socket_io.to_client_event_receiver.clone();
+        let closed = tokio::time::timeout(
+            Duration::from_secs(5),
+            events.wait_for(|event| matches!(event, ConnectionEvent::Closed(_))),
+        )
+        .await
+        .unwrap()
+        .unwrap()
+        .clone();
+        assert_eq!(closed, ConnectionEvent::Closed(CloseReason::Local));
+    }
+
+    #[tokio::test]
+    async fn test_connect_fails_fast_when_server_address_is_in_use_on_mux() {
+        let udp_mux = crate::UdpMux::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let session_endpoint = echo_server().await;
+
+        let config = crate::SocketConfig::default().udp_mux(udp_mux.clone());
+        let (socket, _socket_io) = Socket::with_config(config);
+        socket
+            .connect_with_signaler(InMemorySignaler::new(session_endpoint.clone()))
+            .await
+            .unwrap();
+
+        // the second connection reaches the same server address through the mux
+        let config = crate::SocketConfig::default().udp_mux(udp_mux);
+        let (socket, _socket_io) = Socket::with_config(config);
+        let err = tokio::time::timeout(
+            Duration::from_secs(5),
+            socket.connect_with_signaler(InMemorySignaler::new(session_endpoint)),
+        )
+        .await
+        .unwrap()
+        .unwrap_err();
+        assert!(matches!(err, ConnectError::AddressInUse(_)), "{err:?}");
+    }
+
+    /// Trickles the client's candidates, recording each with its ufrag and
+    /// the ufrag of every offer signaled
+    struct TricklingSignaler {
+        signaler: InMemorySignaler,
+        offer_ufrags: Arc<Mutex<Vec<String>>>,
+        trickled: Arc<Mutex<Vec<(String, Option<String>)>>>,
+    }
+
+    #[async_trait]
+    impl Signaler for TricklingSignaler {
+        async fn signal(&self, offer_sdp: String) -> Result<SignalingAnswer, ConnectError> {
+            let ufrag = offer_sdp
+                .lines()
+                .find_map(|line| line.strip_prefix("a=ice-ufrag:"))
+                .unwrap()
+                .to_string();
+            self.offer_ufrags.lock().await.push(ufrag);
+            self.signaler.signal(offer_sdp).await
+        }
+
+        fn trickles(&self) -> bool {
+            true
+        }
+
+        async fn trickle_candidate(
+            &self,
+            ufrag: &str,
+            candidate: Option<String>,
+        ) -> Result<(), ConnectError> {
+            self.trickled
+                .lock()
+                .await
+                .push((ufrag.to_string(), candidate));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_trickling_signaler() {
+        let signaler = TricklingSignaler {
+            signaler: InMemorySignaler::new(echo_server().await),
+            offer_ufrags: Arc::default(),
+            trickled: Arc::default(),
+        };
+        let offer_ufrags = Arc::clone(&signaler.offer_ufrags);
+        let trickled = Arc::clone(&signaler.trickled);
+
+        // gathers a single host candidate
+        let config = crate::SocketConfig::default()
+            .ip_family(crate::IpFamily::Ipv4)
+            .ip_filter(|ip| ip.is_loopback())
+            .multicast_dns(crate::MulticastDns::Disabled);
+        let (socket, mut socket_io) = Socket::with_config(config);
+        socket.connect_with_signaler(signaler).await.unwrap();
+
+        socket_io
+            .to_server_sender
+            .send(b"echo".to_vec().into_boxed_slice())
+            .await
+            .unwrap();
+        let echoed =
+            tokio::time::timeout(Duration::from_secs(5), socket_io.to_client_receiver.recv())
+                .await
+                .unwrap();
+        assert_eq!(echoed.as_deref(), Some(&b"echo"[..]));
+
+        // gathering may complete after the connection opens
+        let trickled = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let trickled = trickled.lock().await.clone();
+                if trickled.last().is_some_and(|(_, c)| c.is_none()) {
+                    return trickled;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        let offer_ufrags = offer_ufrags.lock().await.clone();
+        assert_eq!(offer_ufrags.len(), 1);
+        assert!(
+            trickled.iter().all(|(ufrag, _)| *ufrag == offer_ufrags[0]),
+            "{trickled:?}"
+        );
+        let candidates: Vec<_> = trickled.into_iter().map(|(_, c)| c).collect();
+        assert_eq!(candidates.len(), 2, "{candidates:?}");
+        assert!(candidates[0]
+            .as_deref()
+            .is_some_and(|c| c.starts_with("candidate:") && c.contains(" 127.0.0.1 ")));
+        assert_eq!(candidates[1], None);
+    }
+
+    struct FailingSignaler;
+
+    #[async_trait]
+    impl Signaler for FailingSignaler {
+        async fn signal(&self, _offer_sdp: String) -> Result<SignalingAnswer, ConnectError> {
+            Err(ConnectError::Rejected {
+                status: 403,
+                body: "forbidden".to_string(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect_fails_with_signaler_error() {
+        let (socket, socket_io) = Socket::new();
+        let err = socket
+            .connect_with_signaler(FailingSignaler)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ConnectError::Rejected { status: 403, .. }));
+
+        let id_token = socket_io.to_client_id_receiver.await.unwrap();
+        assert_eq!(id_token, Err(err));
+    }
+
+    #[test]
+    fn test_session_response_with_one_candidate() {
+        let answer = get_session_response(
+            r#"{
+                "sdp": {
+                    "answer": {"type": "answer", "sdp": "v=0"},
+                    "candidate": {"candidate": "candidate:1 1 UDP 1 127.0.0.1 5000 typ host", "sdpMLineIndex": 0}
+                },
+                "id": "token"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            answer,
+            SignalingAnswer {
+                sdp: "v=0".to_string(),
+                candidates: vec!["candidate:1 1 UDP 1 127.0.0.1 5000 typ host".to_string()],
+                id_token: "token".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_session_response_with_candidate_array() {
+        let answer = get_session_response(
+            r#"{
+                "sdp": {
+                    "answer": {"type": "answer", "sdp": "v=0"},
+                    "candidate": [
+                        {"candidate": "candidate:1 1 UDP 1 127.0.0.1 5000 typ host"},
+                        "candidate:2 1 UDP 1 ::1 5000 typ host",
+                        {"candidate": ""}
+                    ]
+                },
+                "id": "token"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            answer.candidates,
+            vec![
+                "candidate:1 1 UDP 1 127.0.0.1 5000 typ host".to_string(),
+                "candidate:2 1 UDP 1 ::1 5000 typ host".to_string(),
+                String::new(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_malformed_session_response() {
+        for body in [
+            "not json",
+            r#"{"id": "token"}"#,
+            r#"{"sdp": {"answer": {"sdp": "v=0"}}}"#,
+            r#"{"sdp": {"answer": {"sdp": "v=0"}, "candidate": [1]}, "id": "token"}"#,
+        ] {
+            assert!(
+                matches!(
+                    get_session_response(body),
+                    Err(ConnectError::MalformedSession(_))
+                ),
+                "{body}"
+            );
+        }
+    }
+}
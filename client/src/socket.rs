@@ -1,62 +1,192 @@
-use std::{sync::Arc, time::Duration};
+use std::{future::Future, pin::Pin, sync::Arc, time::Duration};
 
 use anyhow::{Error, Result};
 use bytes::Bytes;
 use log::warn;
-use reqwest::{Client as HttpClient, Response};
-use tinyjson::JsonValue;
 use tokio::{
-    sync::{mpsc, oneshot},
-    time::sleep,
+    sync::{mpsc, oneshot, watch, Mutex, Notify},
+    time::{sleep, timeout},
 };
 
 use crate::webrtc::{
     data_channel::internal::data_channel::DataChannel,
-    peer_connection::{sdp::session_description::RTCSessionDescription, RTCPeerConnection},
+    dtls_transport::dtls_transport_state::RTCDtlsTransportState,
+    ice_transport::ice_connection_state::RTCIceConnectionState,
+    peer_connection::{
+        offer_answer_options::RTCOfferOptions, peer_connection_state::RTCPeerConnectionState,
+        sdp::session_description::RTCSessionDescription, RTCPeerConnection,
+    },
+    sctp_transport::sctp_transport_state::RTCSctpTransportState,
+    setting_engine::SettingEngine,
 };
 
-use super::addr_cell::AddrCell;
+use super::{
+    addr_cell::{candidate_to_addr, AddrCell, ServerAddr},
+    channel::ChannelConfig,
+    connection_event::{CloseReason, ConnectionEvent, EventSender},
+    error::ConnectError,
+    queue::{queue, MessageReceiver, MessageSender},
+    reconnect::ReconnectConfig,
+    signaling::{HttpSignaler, Signaler, SignalingConfig},
+    socket_config::SocketConfig,
+    stats::{Stats, StatsSource},
+};
+
+// the reply to an ICE restart requested through SocketIo::restart_ice
+type IceRestartRequest = oneshot::Sender<Result<String, ConnectError>>;
+
+// an ICE restart in flight
+type IceRestartFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<IceRestart, ConnectError>> + Send + 'a>>;
+
+// how an ICE restart moved the client to new candidates
+enum IceRestart {
+    // the server restarted ICE on its session, answering with this id token
+    Restarted(String),
+    // the server cannot restart sessions, so a new connection replaces the old one
+    Reconnected(Connection, String),
+}
 
-const MESSAGE_SIZE: usize = 1500;
+// why watch returned
+enum Watched {
+    Ended(CloseReason),
+    // an ICE restart opened this connection and closed the watched one
+    Replaced(Connection),
+}
+
+// what started an ICE restart, which decides where its result goes
+enum IceRestartCause {
+    Requested(IceRestartRequest),
+    Disconnected,
+    NetworkChanged,
+}
 
 pub struct Socket {
     addr_cell: AddrCell,
-    to_server_receiver: mpsc::UnboundedReceiver<Box<[u8]>>,
+    channels: Vec<SocketChannel>,
     to_server_disconnect_receiver: mpsc::Receiver<()>,
-    to_client_sender: mpsc::UnboundedSender<Box<[u8]>>,
-    to_client_id_sender: oneshot::Sender<Result<String, u16>>,
+    to_server_ice_restart_receiver: mpsc::Receiver<IceRestartRequest>,
+    to_client_id_sender: oneshot::Sender<Result<String, ConnectError>>,
+    to_client_event_sender: EventSender,
+    signaling_config: SignalingConfig,
+    reconnect_config: Option<ReconnectConfig>,
+    restart_ice_on_disconnect: bool,
+    setting_engine: SettingEngine,
+    stats_source: StatsSource,
 }
 
 pub struct SocketIo {
     pub addr_cell: AddrCell,
-    pub to_server_sender: mpsc::UnboundedSender<Box<[u8]>>,
+    pub to_server_sender: MessageSender,
     pub to_server_disconnect_sender: mpsc::Sender<()>,
-    pub to_client_receiver: mpsc::UnboundedReceiver<Box<[u8]>>,
-    pub to_client_id_receiver: oneshot::Receiver<Result<String, u16>>,
+    pub to_client_receiver: MessageReceiver,
+    pub to_client_id_receiver: oneshot::Receiver<Result<String, ConnectError>>,
+    pub to_client_event_receiver: watch::Receiver<ConnectionEvent>,
+    /// The channels declared after the first one, in the order they were
+    /// passed to `SocketConfig::channels`
+    pub channels: Vec<ChannelIo>,
+    to_server_ice_restart_sender: mpsc::Sender<IceRestartRequest>,
+    stats_source: StatsSource,
+}
+
+/// The client's end of a data channel declared through `SocketConfig::channels`.
+/// Dropping either half closes the whole connection, as with `SocketIo`.
+pub struct ChannelIo {
+    pub label: String,
+    pub to_server_sender: MessageSender,
+    pub to_client_receiver: MessageReceiver,
+}
+
+// SocketChannel
+// the socket's halves of a channel's queues, handed to every peer connection
+// so a reconnect keeps the client's halves
+#[derive(Clone)]
+struct SocketChannel {
+    config: ChannelConfig,
+    to_server_receiver: Arc<Mutex<MessageReceiver>>,
+    to_client_sender: MessageSender,
+}
+
+impl SocketChannel {
+    fn new(config: ChannelConfig) -> (Self, ChannelIo) {
+        let (to_server_sender, to_server_receiver) = queue(config.send_queue);
+        let (to_client_sender, to_client_receiver) = queue(config.receive_queue);
+
+        (
+            Self {
+                config: config.clone(),
+                to_server_receiver: Arc::new(Mutex::new(to_server_receiver)),
+                to_client_sender,
+            },
+            ChannelIo {
+                label: config.label,
+                to_server_sender,
+                to_client_receiver,
+            },
+        )
+    }
 }
 
 impl Socket {
     pub fn new() -> (Self, SocketIo) {
+        Self::with_config(SocketConfig::default())
+    }
+
+    /// Creates a socket that opens the config's channels and connects with
+    /// its signaling and transport settings
+    pub fn with_config(config: SocketConfig) -> (Self, SocketIo) {
+        let SocketConfig {
+            channels,
+            signaling: signaling_config,
+            reconnect: reconnect_config,
+            restart_ice_on_disconnect,
+            setting_engine,
+        } = config;
+
+        // an empty list opens a single ChannelConfig::default() channel
+        let mut channels = channels.into_iter();
+        let (first_channel, first_channel_io) =
+            SocketChannel::new(channels.next().unwrap_or_default());
+
+        let mut socket_channels = vec![first_channel];
+        let mut channel_ios = Vec::new();
+        for config in channels {
+            let (channel, channel_io) = SocketChannel::new(config);
+            socket_channels.push(channel);
+            channel_ios.push(channel_io);
+        }
+
         let addr_cell = AddrCell::default();
-        let (to_server_sender, to_server_receiver) = mpsc::unbounded_channel();
         let (to_server_disconnect_sender, to_server_disconnect_receiver) = mpsc::channel(1);
-        let (to_client_sender, to_client_receiver) = mpsc::unbounded_channel();
+        let (to_server_ice_restart_sender, to_server_ice_restart_receiver) = mpsc::channel(1);
         let (to_client_id_sender, to_client_id_receiver) = oneshot::channel();
+        let (to_client_event_sender, to_client_event_receiver) = EventSender::new();
+        let stats_source = StatsSource::default();
 
         (
             Self {
                 addr_cell: addr_cell.clone(),
-                to_server_receiver,
+                channels: socket_channels,
                 to_server_disconnect_receiver,
-                to_client_sender,
+                to_server_ice_restart_receiver,
                 to_client_id_sender,
+                to_client_event_sender,
+                signaling_config,
+                reconnect_config,
+                restart_ice_on_disconnect,
+                setting_engine,
+                stats_source: stats_source.clone(),
             },
             SocketIo {
                 addr_cell,
-                to_server_sender,
+                to_server_sender: first_channel_io.to_server_sender,
                 to_server_disconnect_sender,
-                to_client_receiver,
+                to_client_receiver: first_channel_io.to_client_receiver,
                 to_client_id_receiver,
+                to_client_event_receiver,
+                channels: channel_ios,
+                to_server_ice_restart_sender,
+                stats_source,
             },
         )
     }
@@ -66,190 +196,796 @@ impl Socket {
         server_url: &str,
         auth_bytes_opt: Option<Vec<u8>>,
         auth_headers_opt: Option<Vec<(String, String)>>,
-    ) {
+    ) -> Result<(), ConnectError> {
+        let signaler = HttpSignaler::new(
+            server_url,
+            auth_bytes_opt,
+            auth_headers_opt,
+            self.signaling_config.clone(),
+        );
+        self.connect_with_signaler(signaler).await
+    }
+
+    /// Connects using the given `Signaler` to exchange the offer and answer.
+    /// The signaler is kept to signal again on reconnect.
+    pub async fn connect_with_signaler<S: Signaler + 'static>(
+        self,
+        signaler: S,
+    ) -> Result<(), ConnectError> {
         let Self {
             addr_cell,
-            to_server_receiver,
+            channels,
             to_server_disconnect_receiver,
-            to_client_sender,
+            to_server_ice_restart_receiver,
             to_client_id_sender,
+            to_client_event_sender,
+            signaling_config,
+            reconnect_config,
+            restart_ice_on_disconnect,
+            setting_engine,
+            stats_source,
         } = self;
 
-        // create a new RTCPeerConnection
-        let peer_connection = RTCPeerConnection::new().await;
+        let connector = Connector {
+            addr_cell,
+            channels,
+            event_sender: to_client_event_sender.clone(),
+            signaler: Arc::new(signaler),
+            handshake_timeout: signaling_config.handshake_timeout,
+            restart_ice_on_disconnect,
+            setting_engine: Arc::new(setting_engine),
+            stats_source,
+        };
 
-        let label = "data";
-        let protocol = "";
+        // send the id token (or the reason we failed to connect) to the client
+        let (result, response) = match connector.connect().await {
+            Ok((connection, id_token)) => {
+                to_client_event_sender.send(ConnectionEvent::Open);
+                tokio::spawn(connector.supervise(
+                    connection,
+                    id_token.clone(),
+                    to_server_disconnect_receiver,
+                    to_server_ice_restart_receiver,
+                    reconnect_config,
+                ));
+                (Ok(()), Ok(id_token))
+            }
+            Err(err) => {
+                to_client_event_sender.close(CloseReason::ConnectFailed(err.clone()));
+                (Err(err.clone()), Err(err))
+            }
+        };
+        if let Err(err) = to_client_id_sender.send(response) {
+            warn!("Could not send id token to client: {:?}. Did the IdentityReceiver returned from Socket::connect() de-allocate?", err);
+        }
 
-        // create a datachannel with label 'data'
-        let data_channel = peer_connection
-            .create_data_channel(label, protocol)
-            .await
-            .expect("cannot create data channel");
+        result
+    }
+}
 
-        // datachannel on_error callback
-        data_channel
-            .on_error(Box::new(move |error| {
-                println!("data channel error: {:?}", error);
-                Box::pin(async {})
-            }))
-            .await;
+// Connection
+// one peer connection, which ends at its first failure or a local close
+struct Connection {
+    peer_connection: Arc<RTCPeerConnection>,
+    end: ConnectionEnd,
+    // notified when the peer connection reports Disconnected
+    disconnected: Arc<Notify>,
+    // notified when the local network changes under the ICE agent
+    network_changed: Arc<Notify>,
+}
 
-        // datachannel on_open callback
-        let peer_connection_ref = Arc::clone(&peer_connection);
-        let data_channel_ref = Arc::clone(&data_channel);
-        data_channel
-            .on_open(Box::new(move || {
-                let peer_connection_ref_2 = Arc::clone(&peer_connection_ref);
-                let data_channel_ref_2 = Arc::clone(&data_channel_ref);
-                Box::pin(async move {
-                    let detached_data_channel = data_channel_ref_2
-                        .detach()
-                        .await
-                        .expect("data channel detach got error");
+impl Connection {
+    async fn close(&self, reason: CloseReason) {
+        // ending first keeps the callbacks fired while closing from reporting a new reason
+        self.end.end(reason);
+        self.peer_connection.internal.close().await;
+    }
+}
 
-                    // Handle reading from the data channel
-                    let peer_connection_ref_3 = Arc::clone(&peer_connection_ref_2);
-                    let peer_connection_ref_4 = Arc::clone(&peer_connection_ref_2);
+// ConnectionEnd
+#[derive(Clone)]
+struct ConnectionEnd {
+    sender: Arc<watch::Sender<Option<CloseReason>>>,
+}
 
-                    let detached_data_channel_1 = Arc::clone(&detached_data_channel);
-                    let detached_data_channel_2 = Arc::clone(&detached_data_channel);
-                    tokio::spawn(async move {
-                        let _loop_result =
-                            read_loop(detached_data_channel_1, to_client_sender).await;
+impl ConnectionEnd {
+    fn new() -> Self {
+        let (sender, _) = watch::channel(None);
+        Self {
+            sender: Arc::new(sender),
+        }
+    }
 
-                        // do nothing with result, just close thread
-                        peer_connection_ref_3.internal.close().await;
-                    });
+    /// Records why the connection ended, unless it already has
+    fn end(&self, reason: CloseReason) {
+        self.sender.send_if_modified(|current| {
+            if current.is_some() {
+                return false;
+            }
+            *current = Some(reason);
+            true
+        });
+    }
 
-                    // Handle writing to the data channel
-                    tokio::spawn(async move {
-                        let detached_data_channel_3 = Arc::clone(&detached_data_channel_2);
-                        let _loop_result = write_loop(
-                            detached_data_channel_3,
-                            to_server_receiver,
-                            to_server_disconnect_receiver,
-                        )
-                        .await;
+    fn is_ended(&self) -> bool {
+        self.sender.borrow().is_some()
+    }
 
-                        // do nothing with result, just close thread
-                        detached_data_channel_2.close().await;
+    /// Resolves with the reason once the connection has ended
+    async fn ended(&self) -> CloseReason {
+        let mut receiver = self.sender.subscribe();
+        loop {
+            if let Some(reason) = receiver.borrow_and_update().clone() {
+                return reason;
+            }
+            // self holds the sender, so this never fails
+            let _ = receiver.changed().await;
+        }
+    }
+}
 
-                        peer_connection_ref_4.internal.close().await;
-                    });
-                })
-            }))
-            .await;
+// Connector
+// everything needed to open a peer connection, kept to open another on reconnect
+struct Connector {
+    addr_cell: AddrCell,
+    channels: Vec<SocketChannel>,
+    event_sender: EventSender,
+    signaler: Arc<dyn Signaler>,
+    handshake_timeout: Option<Duration>,
+    restart_ice_on_disconnect: bool,
+    setting_engine: Arc<SettingEngine>,
+    stats_source: StatsSource,
+}
 
-        // create an offer to send to the server
-        let offer = peer_connection
-            .create_offer()
-            .await
-            .expect("cannot create offer");
+impl Connector {
+    /// Opens a peer connection and waits for its data channels, returning
+    /// the server's id token
+    async fn connect(&self) -> Result<(Connection, String), ConnectError> {
+        let peer_connection = RTCPeerConnection::new(Arc::clone(&self.setting_engine)).await?;
+        self.stats_source.set(&peer_connection);
 
-        // sets the LocalDescription, and starts our UDP listeners
-        peer_connection
-            .set_local_description(offer)
+        let connection = Connection {
+            peer_connection,
+            end: ConnectionEnd::new(),
+            disconnected: Arc::new(Notify::new()),
+            network_changed: Arc::new(Notify::new()),
+        };
+
+        // the handshake deadline covers signaling through the data channels opening
+        match self
+            .within_handshake_timeout(self.handshake(&connection))
             .await
-            .expect("cannot set local description");
+        {
+            Ok(id_token) => Ok((connection, id_token)),
+            Err(err) => {
+                connection
+                    .close(CloseReason::ConnectFailed(err.clone()))
+                    .await;
+                Err(err)
+            }
+        }
+    }
 
-        // send a request to server to initiate connection (signaling, essentially)
-        let http_client = HttpClient::new();
+    /// Restarts ICE on the connection with new credentials and signals the
+    /// restart offer for the server's session, keeping the DTLS and SCTP
+    /// sessions running. A server that cannot restart sessions gets a new
+    /// connection instead, which the caller swaps in for the old one.
+    async fn restart_ice(
+        &self,
+        connection: &Connection,
+        id_token: &str,
+    ) -> Result<IceRestart, ConnectError> {
+        if !self.signaler.restarts_sessions() {
+            let previous_event = self.event_sender.current();
+            self.event_sender
+                .send(ConnectionEvent::Reconnecting { attempt: 1 });
+            return match self.connect().await {
+                Ok((new_connection, new_id_token)) => {
+                    Ok(IceRestart::Reconnected(new_connection, new_id_token))
+                }
+                Err(err) => {
+                    // the old connection carries on
+                    self.stats_source.set(&connection.peer_connection);
+                    self.event_sender.send(previous_event);
+                    Err(err)
+                }
+            };
+        }
 
-        let sdp = peer_connection.local_description().await.unwrap().sdp;
+        let peer_connection = &connection.peer_connection;
+        self.within_handshake_timeout(async {
+            let offer = peer_connection
+                .create_offer(Some(RTCOfferOptions { ice_restart: true }))
+                .await?;
+            self.negotiate(peer_connection, offer, Some(id_token)).await
+        })
+        .await
+        .map(IceRestart::Restarted)
+    }
 
-        let sdp_len = sdp.len();
+    async fn within_handshake_timeout<T>(
+        &self,
+        future: impl Future<Output = Result<T, ConnectError>>,
+    ) -> Result<T, ConnectError> {
+        match self.handshake_timeout {
+            Some(handshake_timeout) => timeout(handshake_timeout, future)
+                .await
+                .unwrap_or(Err(ConnectError::Timeout(handshake_timeout))),
+            None => future.await,
+        }
+    }
 
-        // wait to receive a response from server
-        let response: Response = loop {
-            let mut request = http_client
-                .post(server_url)
-                .header("Content-Length", sdp_len)
-                .body(sdp.clone());
-            if let Some(auth_bytes) = auth_bytes_opt.clone() {
-                let base64_encoded = base64::encode(auth_bytes);
-                request = request.header("Authorization", &base64_encoded);
-            }
-            if let Some(auth_headers) = auth_headers_opt.clone() {
-                for (key, value) in auth_headers {
-                    request = request.header(key, value);
+    /// Waits for the connection to end, then replaces it if reconnecting is
+    /// enabled and the failure is recoverable, or closes the socket. Restarts
+    /// ICE in the meantime when asked to, or when the local network changes.
+    async fn supervise(
+        self,
+        mut connection: Connection,
+        mut id_token: String,
+        mut to_server_disconnect_receiver: mpsc::Receiver<()>,
+        mut to_server_ice_restart_receiver: mpsc::Receiver<IceRestartRequest>,
+        reconnect_config: Option<ReconnectConfig>,
+    ) {
+        loop {
+            let watched = self
+                .watch(
+                    &connection,
+                    &mut id_token,
+                    &mut to_server_disconnect_receiver,
+                    &mut to_server_ice_restart_receiver,
+                )
+                .await;
+            let reason = match watched {
+                Watched::Ended(reason) => reason,
+                Watched::Replaced(new_connection) => {
+                    connection = new_connection;
+                    continue;
+                }
+            };
+            connection.close(reason.clone()).await;
+
+            let reconnect_config = match &reconnect_config {
+                Some(reconnect_config) if reason.is_recoverable() => reconnect_config,
+                _ => {
+                    self.event_sender.close(reason);
+                    return;
+                }
+            };
+            warn!("connection closed: {:?}, reconnecting", reason);
+
+            match self
+                .reconnect(reconnect_config, &mut to_server_disconnect_receiver)
+                .await
+            {
+                Ok((new_connection, new_id_token)) => {
+                    connection = new_connection;
+                    id_token.clone_from(&new_id_token);
+                    self.event_sender.send(ConnectionEvent::Reconnected {
+                        id_token: new_id_token,
+                    });
+                }
+                Err(reason) => {
+                    self.event_sender.close(reason);
+                    return;
                 }
             }
+        }
+    }
 
-            match request.send().await {
-                Ok(resp) => {
-                    break resp;
+    /// Waits for the connection to end or the client to close it, returning
+    /// why, or for an ICE restart to replace it. ICE restarts run alongside,
+    /// so closing or the connection ending still wins while one is in flight.
+    async fn watch(
+        &self,
+        connection: &Connection,
+        id_token: &mut String,
+        to_server_disconnect_receiver: &mut mpsc::Receiver<()>,
+        to_server_ice_restart_receiver: &mut mpsc::Receiver<IceRestartRequest>,
+    ) -> Watched {
+        let mut restart: Option<(IceRestartFuture<'_>, IceRestartCause)> = None;
+        loop {
+            let cause = tokio::select! {
+                // close the connection when the client asks to, or drops its disconnect sender
+                _ = to_server_disconnect_receiver.recv() => return Watched::Ended(CloseReason::Local),
+                reason = connection.end.ended() => return Watched::Ended(reason),
+                result = async {
+                    match &mut restart {
+                        Some((future, _)) => future.await,
+                        None => std::future::pending().await,
+                    }
+                }, if restart.is_some() => {
+                    if let Some((_, cause)) = restart.take() {
+                        let (result, new_connection) = match result {
+                            Ok(IceRestart::Restarted(restarted_id_token)) => {
+                                id_token.clone_from(&restarted_id_token);
+                                self.event_sender.send(ConnectionEvent::IceRestarted {
+                                    id_token: restarted_id_token.clone(),
+                                });
+                                (Ok(restarted_id_token), None)
+                            }
+                            Ok(IceRestart::Reconnected(new_connection, new_id_token)) => {
+                                // the new connection's channels are open, so
+                                // the old one can go before it is reported
+                                connection.close(CloseReason::Local).await;
+                                id_token.clone_from(&new_id_token);
+                                self.event_sender.send(ConnectionEvent::Reconnected {
+                                    id_token: new_id_token.clone(),
+                                });
+                                (Ok(new_id_token), Some(new_connection))
+                            }
+                            Err(err) => (Err(err), None),
+                        };
+                        match (cause, result) {
+                            (IceRestartCause::Requested(reply), result) => {
+                                let _ = reply.send(result);
+                            }
+                            (IceRestartCause::Disconnected, Err(err)) => {
+                                warn!("ICE restart failed: {:?}", err);
+                            }
+                            (IceRestartCause::NetworkChanged, Err(err)) => {
+                                warn!("ICE restart after a network change failed: {:?}", err);
+                            }
+                            (_, Ok(_)) => {}
+                        }
+                        if let Some(new_connection) = new_connection {
+                            return Watched::Replaced(new_connection);
+                        }
+                    }
+                    continue;
                 }
-                Err(err) => {
-                    warn!("Could not send request, original error: {:?}", err);
-                    sleep(Duration::from_secs(1)).await;
+                Some(reply) = to_server_ice_restart_receiver.recv(), if restart.is_none() => {
+                    IceRestartCause::Requested(reply)
+                }
+                _ = connection.disconnected.notified(),
+                    if self.restart_ice_on_disconnect && restart.is_none() => {
+                    IceRestartCause::Disconnected
+                }
+                // gather on the new network and move the session over to it
+                _ = connection.network_changed.notified(), if restart.is_none() => {
+                    IceRestartCause::NetworkChanged
                 }
             };
-        };
 
-        if !response.status().is_success() {
-            let status_code = response.status().as_u16();
-            to_client_id_sender.send(Err(status_code)).unwrap();
-            return;
+            let restarted_id_token = id_token.clone();
+            restart = Some((
+                Box::pin(async move { self.restart_ice(connection, &restarted_id_token).await }),
+                cause,
+            ));
         }
+    }
 
-        // get the body of the response as a string
-        let response_string = match response.text().await {
-            Ok(response_string) => response_string,
-            Err(_err) => {
-                // error reading response?
-                to_client_id_sender.send(Err(500)).unwrap();
-                return;
+    async fn reconnect(
+        &self,
+        reconnect_config: &ReconnectConfig,
+        to_server_disconnect_receiver: &mut mpsc::Receiver<()>,
+    ) -> Result<(Connection, String), CloseReason> {
+        let mut attempt = 1;
+        loop {
+            self.event_sender
+                .send(ConnectionEvent::Reconnecting { attempt });
+
+            tokio::select! {
+                _ = sleep(reconnect_config.backoff(attempt)) => {}
+                _ = to_server_disconnect_receiver.recv() => return Err(CloseReason::Local),
             }
-        };
 
-        // parse session from server response
-        let session_response_result = get_session_response(response_string.as_str());
-        let session_response = match session_response_result {
-            Ok(session_response) => session_response,
-            Err(_err) => {
-                // parsing error?
-                to_client_id_sender.send(Err(500)).unwrap();
-                return;
+            match self.connect().await {
+                Ok(connected) => return Ok(connected),
+                Err(err) => {
+                    warn!("reconnect attempt {} failed: {:?}", attempt, err);
+                    if reconnect_config.attempts_exhausted(attempt) {
+                        return Err(CloseReason::ReconnectFailed(err));
+                    }
+                }
             }
+            attempt += 1;
+        }
+    }
+
+    /// Applies the offer locally, signals it and applies the server's answer
+    /// and candidates, returning the server's id token. An ICE restart offer
+    /// is signaled for the session identified by `id_token`.
+    async fn negotiate(
+        &self,
+        peer_connection: &Arc<RTCPeerConnection>,
+        offer: RTCSessionDescription,
+        id_token: Option<&str>,
+    ) -> Result<String, ConnectError> {
+        // a trickling signaler gets the offer right away and the candidates
+        // as they are gathered, otherwise the offer carries every candidate
+        let mut gathering_complete = if self.signaler.trickles() {
+            let ufrag = offer
+                .sdp
+                .lines()
+                .find_map(|line| line.strip_prefix("a=ice-ufrag:"))
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            self.trickle_candidates(peer_connection, ufrag).await;
+            None
+        } else {
+            Some(peer_connection.gathering_complete_promise().await)
         };
 
-        // send the id token to the client
-        // info!("Sending id token to client: {:?}", auth_header);
-        if let Err(err) = to_client_id_sender.send(Ok(session_response.id_token)) {
-            warn!("Could not send id token to client: {:?}. Did the IdentityReceiver returned from Socket::connect() de-allocate?", err);
-            return;
+        // sets the LocalDescription, and starts our UDP listeners
+        peer_connection.set_local_description(offer).await?;
+
+        // wait until the STUN and TURN servers have answered or timed out
+        if let Some(gathering_complete) = &mut gathering_complete {
+            let _ = gathering_complete.recv().await;
         }
 
-        // apply the server's response as the remote description
-        let session_description =
-            RTCSessionDescription::answer(session_response.answer.sdp).unwrap();
+        let Some(local_description) = peer_connection.local_description().await else {
+            return Err(ConnectError::Sdp(
+                "local description is not set".to_string(),
+            ));
+        };
+
+        // send the offer to the server to initiate connection (signaling, essentially)
+        let answer = match id_token {
+            Some(id_token) => {
+                self.signaler
+                    .restart(id_token, local_description.sdp)
+                    .await?
+            }
+            None => self.signaler.signal(local_description.sdp).await?,
+        };
+
+        // apply the server's answer as the remote description
+        let session_description = RTCSessionDescription::answer(answer.sdp.clone())?;
 
         peer_connection
             .set_remote_description(session_description)
-            .await
-            .expect("cannot set remote description");
+            .await?;
 
-        addr_cell
-            .receive_candidate(session_response.candidate.candidate.as_str())
+        // until ICE selects a pair, the server's address is its first
+        // candidate that has one, which active TCP candidates do not
+        let sdp_candidates = answer
+            .sdp
+            .lines()
+            .filter_map(|line| line.strip_prefix("a="))
+            .filter(|attribute| attribute.starts_with("candidate:"));
+        if let Some(candidate) = answer
+            .candidates
+            .iter()
+            .map(String::as_str)
+            .chain(sdp_candidates)
+            .filter(|candidate| !candidate.contains("tcptype active"))
+            .find(|candidate| matches!(candidate_to_addr(candidate), ServerAddr::Found(_)))
+        {
+            self.addr_cell.receive_candidate(candidate).await;
+        }
+
+        // add the candidates the answer SDP does not carry, skipping any
+        // this client cannot use
+        for candidate in answer.candidates {
+            if let Err(err) = peer_connection.add_ice_candidate(candidate.clone()).await {
+                match ConnectError::from(err) {
+                    err @ ConnectError::AddressInUse(_) => return Err(err),
+                    err => warn!("skipping server candidate {:?}: {:?}", candidate, err),
+                }
+            }
+        }
+
+        Ok(answer.id_token)
+    }
+
+    /// Passes local candidates to the signaler as they are gathered, then
+    /// None once gathering is complete, each with the ufrag of the offer
+    /// they belong to
+    async fn trickle_candidates(&self, peer_connection: &Arc<RTCPeerConnection>, ufrag: String) {
+        let (candidate_sender, mut candidate_receiver) = mpsc::unbounded_channel();
+        peer_connection
+            .on_ice_candidate(Box::new(move |candidate| {
+                let _ = candidate_sender.send(candidate);
+                Box::pin(async {})
+            }))
             .await;
 
-        // add ice candidate to connection
-        if let Err(error) = peer_connection
-            .add_ice_candidate(session_response.candidate.candidate)
-            .await
-        {
-            panic!("Error during add_ice_candidate: {:?}", error);
+        // ends once gathering completes, or when an ICE restart replaces the handler
+        let signaler = Arc::clone(&self.signaler);
+        tokio::spawn(async move {
+            while let Some(candidate) = candidate_receiver.recv().await {
+                let candidate = match candidate {
+                    Some(candidate) => match candidate.to_json().await {
+                        Ok(candidate_init) => Some(candidate_init.candidate),
+                        Err(err) => {
+                            warn!("could not marshal candidate {}: {:?}", candidate, err);
+                            continue;
+                        }
+                    },
+                    None => None,
+                };
+                let complete = candidate.is_none();
+                if let Err(err) = signaler.trickle_candidate(&ufrag, candidate).await {
+                    warn!("could not trickle candidate: {:?}", err);
+                }
+                if complete {
+                    return;
+                }
+            }
+        });
+    }
+
+    async fn handshake(&self, connection: &Connection) -> Result<String, ConnectError> {
+        let peer_connection = &connection.peer_connection;
+
+        // one message per data channel opening, or the first transport failure
+        let channel_count = self.channels.len();
+        let (open_sender, mut open_receiver) =
+            mpsc::channel::<Result<(), ConnectError>>(channel_count + 1);
+
+        // peer connection on_error callback, fired when a transport fails to start
+        let open_sender_ref = open_sender.clone();
+        peer_connection
+            .on_error(Box::new(move |error| {
+                let _ = open_sender_ref.try_send(Err(error.into()));
+                Box::pin(async {})
+            }))
+            .await;
+
+        // ice connection state callback, ICE gives up after its failed timeout
+        // or once the server stops granting consent, and the server's address
+        // is the selected pair's once it connects
+        let open_sender_ref = open_sender.clone();
+        let end_ref = connection.end.clone();
+        let addr_cell_ref = self.addr_cell.clone();
+        let ice_transport_ref = Arc::clone(&peer_connection.internal.ice_transport);
+        peer_connection
+            .on_ice_connection_state_change(Box::new(move |state| {
+                if state == RTCIceConnectionState::Failed {
+                    let _ = open_sender_ref
+                        .try_send(Err(ConnectError::Ice("ICE connection failed".to_string())));
+                }
+                let end_ref = end_ref.clone();
+                let addr_cell = addr_cell_ref.clone();
+                let ice_transport = Arc::clone(&ice_transport_ref);
+                Box::pin(async move {
+                    match state {
+                        RTCIceConnectionState::Failed => {
+                            if ice_transport.consent_expired().await {
+                                end_ref.end(CloseReason::ServerUnreachable);
+                            } else {
+                                end_ref.end(CloseReason::IceFailed);
+                            }
+                        }
+                        RTCIceConnectionState::Connected => {
+                            if let Some(pair) = ice_transport.selected_candidate_pair().await {
+                                addr_cell.set_addr(pair.remote.addr().await).await;
+                            }
+                        }
+                        _ => {}
+                    }
+                })
+            }))
+            .await;
+
+        // network change callback, the ICE agent saw a local IP change that calls for a restart
+        let network_changed_ref = Arc::clone(&connection.network_changed);
+        peer_connection
+            .internal
+            .ice_transport
+            .on_network_change(Box::new(move || {
+                network_changed_ref.notify_one();
+                Box::pin(async {})
+            }))
+            .await;
+
+        // peer connection state callback, ignored once the connection has
+        // ended so it cannot overwrite the reconnect events
+        let event_sender_ref = self.event_sender.clone();
+        let end_ref = connection.end.clone();
+        let disconnected_ref = Arc::clone(&connection.disconnected);
+        peer_connection
+            .on_peer_connection_state_change(Box::new(move |state| {
+                if !end_ref.is_ended() {
+                    match state {
+                        RTCPeerConnectionState::Connecting => event_sender_ref.connecting(),
+                        RTCPeerConnectionState::Connected => event_sender_ref.recovered(),
+                        RTCPeerConnectionState::Disconnected => {
+                            event_sender_ref.send(ConnectionEvent::Disconnected);
+                            disconnected_ref.notify_one();
+                        }
+                        _ => {}
+                    }
+                }
+                Box::pin(async {})
+            }))
+            .await;
+
+        // dtls transport state callback
+        let end_ref = connection.end.clone();
+        peer_connection
+            .internal
+            .dtls_transport
+            .on_state_change(Box::new(move |state| {
+                match state {
+                    RTCDtlsTransportState::Failed => end_ref.end(CloseReason::DtlsFailed),
+                    RTCDtlsTransportState::Closed => end_ref.end(CloseReason::DtlsClosed),
+                    _ => {}
+                }
+                Box::pin(async {})
+            }))
+            .await;
+
+        // sctp transport state callback
+        let end_ref = connection.end.clone();
+        peer_connection
+            .internal
+            .sctp_transport
+            .on_state_change(Box::new(move |state| {
+                if state == RTCSctpTransportState::Closed {
+                    end_ref.end(CloseReason::SctpClosed);
+                }
+                Box::pin(async {})
+            }))
+            .await;
+
+        // create the datachannels, they open once SCTP is established
+        for channel in &self.channels {
+            Self::create_channel(
+                peer_connection,
+                channel.clone(),
+                &connection.end,
+                open_sender.clone(),
+            )
+            .await?;
         }
+        drop(open_sender);
+
+        // create an offer to send to the server
+        let offer = peer_connection.create_offer(None).await?;
+        let id_token = self.negotiate(peer_connection, offer, None).await?;
+
+        // wait for every data channel to open, or the connection to end first
+        for _ in 0..channel_count {
+            let opened = tokio::select! {
+                biased;
+                opened = open_receiver.recv() => opened,
+                _ = connection.end.ended() => None,
+            };
+            match opened {
+                Some(Ok(())) => {}
+                Some(Err(err)) => return Err(err),
+                None => return Err(ConnectError::Closed),
+            }
+        }
+
+        Ok(id_token)
+    }
+
+    async fn create_channel(
+        peer_connection: &Arc<RTCPeerConnection>,
+        channel: SocketChannel,
+        end: &ConnectionEnd,
+        open_sender: mpsc::Sender<Result<(), ConnectError>>,
+    ) -> Result<(), ConnectError> {
+        let SocketChannel {
+            config,
+            to_server_receiver,
+            to_client_sender,
+        } = channel;
+
+        let max_buffered_amount = config.max_buffered_amount;
+        let protocol = "";
+        let data_channel = peer_connection
+            .create_data_channel(&config.label, protocol, (&config).into())
+            .await?;
+
+        // datachannel on_error callback
+        let label = config.label.clone();
+        data_channel
+            .on_error(Box::new(move |error| {
+                warn!("data channel '{}' error: {:?}", label, error);
+                Box::pin(async {})
+            }))
+            .await;
+
+        // datachannel on_open callback
+        let peer_connection_ref = Arc::clone(peer_connection);
+        let data_channel_ref = Arc::clone(&data_channel);
+        let end = end.clone();
+        data_channel
+            .on_open(Box::new(move || {
+                Box::pin(async move {
+                    let detached_data_channel = match data_channel_ref.detach().await {
+                        Ok(detached_data_channel) => detached_data_channel,
+                        Err(err) => {
+                            let _ = open_sender.try_send(Err(err.into()));
+                            return;
+                        }
+                    };
+
+                    // the negotiated max-message-size limits sends, the advertised one sizes reads
+                    let sctp_transport = &peer_connection_ref.internal.sctp_transport;
+                    let max_message_size = sctp_transport.max_message_size() as usize;
+                    let max_receive_message_size =
+                        sctp_transport.max_receive_message_size() as usize;
+
+                    // Handle reading from the data channel
+                    let detached_data_channel_1 = Arc::clone(&detached_data_channel);
+                    let end_1 = end.clone();
+                    tokio::spawn(async move {
+                        let loop_result = read_loop(
+                            detached_data_channel_1,
+                            to_client_sender,
+                            max_receive_message_size,
+                        )
+                        .await;
+
+                        // the client dropped its receiver, or the channel stopped delivering
+                        end_1.end(match loop_result {
+                            Ok(()) => CloseReason::ChannelClosed,
+                            Err(_) => CloseReason::Local,
+                        });
+                    });
+
+                    // Handle writing to the data channel
+                    let detached_data_channel_2 = Arc::clone(&detached_data_channel);
+                    tokio::spawn(async move {
+                        let loop_result = write_loop(
+                            Arc::clone(&detached_data_channel_2),
+                            to_server_receiver,
+                            max_message_size,
+                            max_buffered_amount,
+                            end.clone(),
+                        )
+                        .await;
+
+                        // the client dropped its sender, or the channel could not be written to
+                        end.end(match loop_result {
+                            Ok(()) => CloseReason::Local,
+                            Err(_) => CloseReason::ChannelClosed,
+                        });
+                        detached_data_channel_2.close().await;
+                    });
+
+                    let _ = open_sender.try_send(Ok(()));
+                })
+            }))
+            .await;
+
+        Ok(())
+    }
+}
+
+impl SocketIo {
+    /// Returns a snapshot of the connection's counters, or None before
+    /// `Socket::connect` has started or after the connection is gone
+    pub async fn stats(&self) -> Option<Stats> {
+        self.stats_source.stats().await
+    }
+
+    /// Moves the connection to new candidates, for example after a network
+    /// change, returning the server's id token afterwards.
+    ///
+    /// If the signaler's `Signaler::restarts_sessions` is true, ICE restarts
+    /// with new credentials, the restart offer is signaled for the current
+    /// session and the DTLS and SCTP sessions carry on, reported as
+    /// `ConnectionEvent::IceRestarted`. Otherwise, as with `HttpSignaler`
+    /// and webrtc-unreliable servers, the restart is really a reconnect: a
+    /// new connection, with new DTLS and SCTP sessions and a new id token,
+    /// replaces the old one once its channels are open, reported as
+    /// `ConnectionEvent::Reconnecting` then `ConnectionEvent::Reconnected`.
+    /// Messages in flight on the old connection may be lost. If the
+    /// restart fails, the old connection is kept.
+    pub async fn restart_ice(&self) -> Result<String, ConnectError> {
+        let (reply_sender, reply_receiver) = oneshot::channel();
+        self.to_server_ice_restart_sender
+            .send(reply_sender)
+            .await
+            .map_err(|_| ConnectError::Closed)?;
+        reply_receiver.await.map_err(|_| ConnectError::Closed)?
     }
 }
 
 // read_loop shows how to read from the datachannel directly
 async fn read_loop(
     data_channel: Arc<DataChannel>,
-    to_client_sender: mpsc::UnboundedSender<Box<[u8]>>,
+    to_client_sender: MessageSender,
+    max_message_size: usize,
 ) -> Result<()> {
-    let mut buffer = vec![0u8; MESSAGE_SIZE];
+    let mut buffer = vec![0u8; max_message_size];
     loop {
         let message_length = match data_channel.read(&mut buffer).await {
             Ok(length) => length,
@@ -259,7 +995,8 @@ async fn read_loop(
             }
         };
 
-        match to_client_sender.send(buffer[..message_length].into()) {
+        // waits while a blocking receive queue is full, which stops reading
+        match to_client_sender.send(buffer[..message_length].into()).await {
             Ok(_) => {}
             Err(e) => {
                 return Err(Error::new(e));
@@ -271,62 +1008,59 @@ async fn read_loop(
 // write_loop shows how to write to the datachannel directly
 async fn write_loop(
     data_channel: Arc<DataChannel>,
-    mut to_server_receiver: mpsc::UnboundedReceiver<Box<[u8]>>,
-    mut to_server_disconnect_receiver: mpsc::Receiver<()>,
+    to_server_receiver: Arc<Mutex<MessageReceiver>>,
+    max_message_size: usize,
+    max_buffered_amount: usize,
+    end: ConnectionEnd,
 ) -> Result<()> {
-    loop {
-        tokio::select! {
-            _ = to_server_disconnect_receiver.recv() => {
-                return Ok(());
-            }
-            result = to_server_receiver.recv() => {
-                if let Some(mut write_message) = result {
-                    let taken_message = std::mem::take(&mut write_message);
-                    let message_bytes = Bytes::from(taken_message);
-                    if let Err(e) = data_channel.write(&message_bytes).await {
-                        return Err(Error::new(e));
-                    }
-                } else {
-                    return Ok(());
-                }
-            }
-        }
-    }
-}
-
-#[derive(Clone)]
-pub(crate) struct SessionAnswer {
-    pub(crate) sdp: String,
-}
-
-pub(crate) struct SessionCandidate {
-    pub(crate) candidate: String,
-}
-
-pub(crate) struct JsSessionResponse {
-    pub(crate) id_token: String,
-    pub(crate) answer: SessionAnswer,
-    pub(crate) candidate: SessionCandidate,
-}
+    // an ended connection's write_loop returns and hands the queue to the next one
+    let mut to_server_receiver = to_server_receiver.lock().await;
+    to_server_receiver.set_max_message_size(max_message_size);
 
-fn get_session_response(input: &str) -> Result<JsSessionResponse, String> {
-    // info!("{}", input);
-    let Ok(json_obj): Result<JsonValue, _> = input.parse() else {
-        return Err("Could not parse response JSON".to_string());
-    };
+    // woken when the SCTP send buffer drains to half of max_buffered_amount
+    let buffered_amount_low = Arc::new(Notify::new());
+    let buffered_amount_low_ref = Arc::clone(&buffered_amount_low);
+    data_channel.set_buffered_amount_low_threshold(max_buffered_amount / 2);
+    data_channel
+        .on_buffered_amount_low(Box::new(move || {
+            buffered_amount_low_ref.notify_one();
+            Box::pin(async {})
+        }))
+        .await;
 
-    let sdp_opt: Option<&String> = json_obj["sdp"]["answer"]["sdp"].get();
-    let sdp: String = sdp_opt.unwrap().clone();
+    loop {
+        let mut write_message = tokio::select! {
+            write_message = to_server_receiver.recv() => match write_message {
+                Some(write_message) => write_message,
+                None => return Ok(()),
+            },
+            _ = end.ended() => return Ok(()),
+        };
 
-    let candidate_opt: Option<&String> = json_obj["sdp"]["candidate"]["candidate"].get();
-    let candidate: String = candidate_opt.unwrap().clone();
+        // hold messages back in the send queue while the transport is saturated
+        while data_channel.buffered_amount() > max_buffered_amount {
+            to_server_receiver.set_saturated(true);
+            tokio::select! {
+                _ = buffered_amount_low.notified() => {}
+                _ = end.ended() => return Ok(()),
+            }
+        }
+        to_server_receiver.set_saturated(false);
 
-    let id_token_opt: Option<&String> = json_obj["id"].get();
-    let id_token: String = id_token_opt.unwrap().clone();
+        // messages queued before the channel opened were not checked against max-message-size
+        if write_message.len() > max_message_size {
+            warn!(
+                "dropping a {} byte message, the server's max-message-size is {} bytes",
+                write_message.len(),
+                max_message_size
+            );
+            continue;
+        }
 
-    Ok(JsSessionResponse {
-        id_token,
-        answer: SessionAnswer { sdp },
-        candidate: SessionCandidate { candidate },
-    })
+        let taken_message = std::mem::take(&mut write_message);
+        let message_bytes = Bytes::from(taken_message);
+        if let Err(e) = data_channel.write(&message_bytes).await {
+            return Err(Error::new(e));
+        }
+    }
 }
@@ -0,0 +1,327 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{
+    channel::ChannelConfig,
+    reconnect::ReconnectConfig,
+    signaling::SignalingConfig,
+    udp_mux::UdpMux,
+    webrtc::{
+        dtls::cipher_suite::CipherSuiteId,
+        ice::{mdns::MulticastDnsMode, network_type::NetworkType},
+        ice_transport::ice_server::RTCIceServer,
+        setting_engine::{GatherFilter, SettingEngine},
+    },
+};
+
+/// Everything `Socket::with_config` can be tuned with. Unset values keep the
+/// defaults listed on each setter.
+#[derive(Debug, Clone, Default)]
+pub struct SocketConfig {
+    pub(crate) channels: Vec<ChannelConfig>,
+    pub(crate) signaling: SignalingConfig,
+    pub(crate) reconnect: Option<ReconnectConfig>,
+    pub(crate) restart_ice_on_disconnect: bool,
+    pub(crate) setting_engine: SettingEngine,
+}
+
+impl SocketConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens one data channel per config. The first channel is exposed through
+    /// `SocketIo::to_server_sender` and `SocketIo::to_client_receiver`, the
+    /// others through `SocketIo::channels`. Defaults to a single
+    /// `ChannelConfig::default()` channel.
+    pub fn channels(mut self, channels: Vec<ChannelConfig>) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    /// Sets how signaling is retried, and how long connecting may take
+    pub fn signaling(mut self, signaling: SignalingConfig) -> Self {
+        self.signaling = signaling;
+        self
+    }
+
+    /// Replaces the connection when it fails, keeping the same `SocketIo`.
+    /// Disabled by default, so a failure closes the connection.
+    pub fn reconnect(mut self, reconnect: ReconnectConfig) -> Self {
+        self.reconnect = Some(reconnect);
+        self
+    }
+
+    /// Restarts ICE whenever the connection reports
+    /// `ConnectionEvent::Disconnected`, as `SocketIo::restart_ice` does.
+    /// Disabled by default.
+    pub fn restart_ice_on_disconnect(mut self) -> Self {
+        self.restart_ice_on_disconnect = true;
+        self
+    }
+
+    /// STUN servers to learn the client's public address from, as
+    /// `stun:host[:port]` URLs, the port defaulting to 3478. The address is
+    /// offered to the server as a server reflexive candidate next to the
+    /// host candidates, which lets clients behind a NAT connect. None by
+    /// default. An invalid URL fails the connection with
+    /// `ConnectError::Ice`.
+    pub fn stun_servers(mut self, urls: Vec<String>) -> Self {
+        self.setting_engine.ice_servers.push(RTCIceServer {
+            urls,
+            ..Default::default()
+        });
+        self
+    }
+
+    /// A TURN server to relay traffic through when the network blocks a
+    /// direct path to the server, as URLs sharing one long-term credential:
+    /// `turn:host[:port]` over UDP, `turn:host[:port]?transport=tcp` over TCP
+    /// and `turns:host[:port]` over TLS. The port defaults to 3478, or 5349
+    /// for `turns:`. Each URL allocates a relayed UDP address that is offered
+    /// as a relay candidate. Can be called once per TURN server. An invalid
+    /// URL, or an empty username or credential, fails the connection with
+    /// `ConnectError::Ice`.
+    pub fn turn_server(
+        mut self,
+        urls: Vec<String>,
+        username: impl Into<String>,
+        credential: impl Into<String>,
+    ) -> Self {
+        self.setting_engine.ice_servers.push(RTCIceServer {
+            urls,
+            username: username.into(),
+            credential: credential.into(),
+        });
+        self
+    }
+
+    /// How long ICE waits without hearing from the server before reporting
+    /// `ConnectionEvent::Disconnected`. Defaults to 5 seconds, zero never
+    /// disconnects.
+    pub fn ice_disconnected_timeout(mut self, timeout: Duration) -> Self {
+        self.setting_engine.ice_disconnected_timeout = Some(timeout);
+        self
+    }
+
+    /// How long ICE stays disconnected before the connection fails. Defaults
+    /// to 25 seconds, zero never fails.
+    pub fn ice_failed_timeout(mut self, timeout: Duration) -> Self {
+        self.setting_engine.ice_failed_timeout = Some(timeout);
+        self
+    }
+
+    /// How often ICE sends keepalives on an idle connection. Defaults to 2
+    /// seconds, and should stay below `ice_disconnected_timeout`.
+    pub fn ice_keepalive_interval(mut self, interval: Duration) -> Self {
+        self.setting_engine.ice_keepalive_interval = Some(interval);
+        self
+    }
+
+    /// How long the server may go without answering a consent check before
+    /// the connection closes with `CloseReason::ServerUnreachable`. Consent
+    /// is checked every fifth of the timeout, and at least every 5 seconds.
+    /// Defaults to 15 seconds as RFC 7675 recommends, checked every 3
+    /// seconds. Zero never checks consent.
+    pub fn consent_timeout(mut self, timeout: Duration) -> Self {
+        self.setting_engine.ice_consent_timeout = Some(timeout);
+        self
+    }
+
+    /// Checks the local interfaces at this interval for a change of network,
+    /// such as moving from Wi-Fi to Ethernet or a VPN coming up or down.
+    /// ICE is restarted as `SocketIo::restart_ice` does when the IP the
+    /// connection sends from goes away, or when an IP appears while the
+    /// connection is disconnected or failed. Other changes are ignored.
+    /// Disabled by default, as is zero.
+    pub fn network_monitor_interval(mut self, interval: Duration) -> Self {
+        self.setting_engine.ice_network_monitor_interval = Some(interval);
+        self
+    }
+
+    /// Gathers host candidates only on the interfaces, by name, that the
+    /// filter returns true for. Every interface is used by default.
+    pub fn interface_filter(
+        mut self,
+        filter: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.setting_engine.ice_interface_filter = Some(GatherFilter(Arc::new(filter)));
+        self
+    }
+
+    /// Gathers host candidates only on the local IPs the filter returns true
+    /// for. Every IP is used by default.
+    pub fn ip_filter(mut self, filter: impl Fn(IpAddr) -> bool + Send + Sync + 'static) -> Self {
+        self.setting_engine.ice_ip_filter = Some(GatherFilter(Arc::new(filter)));
+        self
+    }
+
+    /// The IP families candidates are gathered and STUN and TURN servers
+    /// are reached on. Defaults to `IpFamily::DualStack`, where both families
+    /// are checked at once and the preferred one, usually IPv6, gets a short
+    /// head start before the other is settled for. `IceStats::ip_family`
+    /// reports which one the connection ended up on.
+    pub fn ip_family(mut self, family: IpFamily) -> Self {
+        self.setting_engine.ice_network_types = family.network_types();
+        self
+    }
+
+    /// Also gathers active ICE-TCP host candidates (RFC 6544) on the IP
+    /// families `ip_family` selects, for networks that block UDP. Each one
+    /// opens a TCP connection to the server, which is only used if no UDP
+    /// pair works. Disabled by default.
+    pub fn ice_tcp(mut self) -> Self {
+        self.setting_engine.ice_tcp = true;
+        self
+    }
+
+    /// Binds every local UDP socket to a port between `min` and `max`,
+    /// inclusive, zero leaving that end open. Ports are ephemeral by default.
+    /// A range with `max` below `min` fails the connection with
+    /// `ConnectError::Ice`, and a socket finding no free port in the range
+    /// gathers no candidate.
+    pub fn port_range(mut self, min: u16, max: u16) -> Self {
+        self.setting_engine.ice_port_min = min;
+        self.setting_engine.ice_port_max = max;
+        self
+    }
+
+    /// Binds every local socket to `ip`, gathering a single host candidate
+    /// on it. STUN and TURN servers only reachable over the other IP family
+    /// are skipped. By default every interface is used.
+    pub fn local_address(mut self, ip: IpAddr) -> Self {
+        self.setting_engine.ice_local_ip = Some(ip);
+        self
+    }
+
+    /// Gathers the UDP host candidate on a socket shared with other
+    /// connections, instead of binding a socket per local interface, which
+    /// saves ports and file descriptors when opening many connections.
+    /// `port_range` and `local_address` do not apply to the shared socket.
+    /// Server reflexive and relay candidates still bind their own sockets,
+    /// and so does multicast DNS unless disabled with
+    /// `MulticastDns::Disabled`. None by default.
+    pub fn udp_mux(mut self, udp_mux: UdpMux) -> Self {
+        self.setting_engine.ice_udp_mux = Some(udp_mux.inner);
+        self
+    }
+
+    /// Whether the server's `.local` host candidates are resolved over
+    /// multicast DNS, and whether the client publishes its own host
+    /// candidates under a random `.local` name instead of its IP. Defaults
+    /// to `MulticastDns::QueryOnly`.
+    pub fn multicast_dns(mut self, mode: MulticastDns) -> Self {
+        self.setting_engine.ice_multicast_dns_mode = mode.into();
+        self
+    }
+
+    /// How long the DTLS handshake waits for a reply before retransmitting.
+    /// Defaults to 1 second.
+    pub fn dtls_flight_interval(mut self, interval: Duration) -> Self {
+        self.setting_engine.dtls_flight_interval = Some(interval);
+        self
+    }
+
+    /// The DTLS cipher suites offered to the server, in order of preference.
+    /// Defaults to AES-128-GCM, then AES-256-CBC.
+    pub fn dtls_cipher_suites(mut self, cipher_suites: Vec<DtlsCipherSuite>) -> Self {
+        self.setting_engine.dtls_cipher_suites =
+            cipher_suites.into_iter().map(CipherSuiteId::from).collect();
+        self
+    }
+
+    /// The SCTP receive window, in bytes. Defaults to 1 MiB, and is raised to
+    /// `max_message_size` if smaller.
+    pub fn sctp_receive_buffer_size(mut self, size: u32) -> Self {
+        self.setting_engine.sctp_max_receive_buffer_size = size;
+        self
+    }
+
+    /// The largest message the client accepts, advertised to the server as
+    /// `a=max-message-size` in the offer. Defaults to 65536 bytes, and is at
+    /// least 1.
+    pub fn max_message_size(mut self, max_message_size: u32) -> Self {
+        self.setting_engine.sctp_max_message_size = max_message_size.max(1);
+        self
+    }
+
+    /// Accepts any DTLS certificate from the server instead of checking it
+    /// against the fingerprint in the server's answer. This leaves the
+    /// connection open to man-in-the-middle attacks, use it only for testing.
+    pub fn insecure_skip_fingerprint_verification(mut self) -> Self {
+        self.setting_engine
+            .disable_certificate_fingerprint_verification = true;
+        self
+    }
+}
+
+/// The IP families ICE gathers candidates on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IpFamily {
+    #[default]
+    DualStack,
+    Ipv4,
+    Ipv6,
+}
+
+impl IpFamily {
+    pub(crate) fn network_types(self) -> Vec<NetworkType> {
+        match self {
+            IpFamily::DualStack => vec![NetworkType::Udp4, NetworkType::Udp6],
+            IpFamily::Ipv4 => vec![NetworkType::Udp4],
+            IpFamily::Ipv6 => vec![NetworkType::Udp6],
+        }
+    }
+}
+
+/// How ICE uses multicast DNS (mDNS) for host candidates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MulticastDns {
+    /// `.local` candidates are dropped and host candidates carry IPs.
+    Disabled,
+    /// `.local` candidates are resolved and host candidates carry IPs.
+    #[default]
+    QueryOnly,
+    /// `.local` candidates are resolved and host candidates are published
+    /// under a `.local` name, hiding the client's local IPs from signaling.
+    QueryAndGather,
+}
+
+impl From<MulticastDns> for MulticastDnsMode {
+    fn from(mode: MulticastDns) -> Self {
+        match mode {
+            MulticastDns::Disabled => MulticastDnsMode::Disabled,
+            MulticastDns::QueryOnly => MulticastDnsMode::QueryOnly,
+            MulticastDns::QueryAndGather => MulticastDnsMode::QueryAndGather,
+        }
+    }
+}
+
+/// The DTLS cipher suites the client supports with its ECDSA certificate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DtlsCipherSuite {
+    EcdheEcdsaWithAes128GcmSha256,
+    EcdheEcdsaWithAes256CbcSha,
+    EcdheEcdsaWithAes128Ccm,
+    EcdheEcdsaWithAes128Ccm8,
+}
+
+impl From<DtlsCipherSuite> for CipherSuiteId {
+    fn from(cipher_suite: DtlsCipherSuite) -> Self {
+        match cipher_suite {
+            DtlsCipherSuite::EcdheEcdsaWithAes128GcmSha256 => {
+                CipherSuiteId::Tls_Ecdhe_Ecdsa_With_Aes_128_Gcm_Sha256
+            }
+            DtlsCipherSuite::EcdheEcdsaWithAes256CbcSha => {
+                CipherSuiteId::Tls_Ecdhe_Ecdsa_With_Aes_256_Cbc_Sha
+            }
+            DtlsCipherSuite::EcdheEcdsaWithAes128Ccm => {
+                CipherSuiteId::Tls_Ecdhe_Ecdsa_With_Aes_128_Ccm
+            }
+            DtlsCipherSuite::EcdheEcdsaWithAes128Ccm8 => {
+                CipherSuiteId::Tls_Ecdhe_Ecdsa_With_Aes_128_Ccm_8
+            }
+        }
+    }
+}
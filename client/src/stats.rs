@@ -0,0 +1,152 @@
+use std::{
+    sync::{Arc, Mutex, Weak},
+    time::{Duration, SystemTime},
+};
+
+use crate::{socket_config::IpFamily, webrtc::peer_connection::RTCPeerConnection};
+
+/// A snapshot of a connection's counters, returned by `SocketIo::stats`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    /// Messages written to the data channels
+    pub messages_sent: usize,
+    /// Messages read from the data channels
+    pub messages_received: usize,
+    /// Payload bytes written to the data channels
+    pub bytes_sent: usize,
+    /// Payload bytes read from the data channels
+    pub bytes_received: usize,
+    /// How long ago the last packet arrived from the server
+    pub since_last_packet: Option<Duration>,
+    pub ice: IceStats,
+    /// The name of the negotiated DTLS cipher suite
+    pub dtls_cipher_suite: Option<String>,
+    pub sctp: SctpStats,
+}
+
+/// The ICE transport's counters and selected candidate pair
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IceStats {
+    /// The client's side of the selected candidate pair
+    pub local_candidate: Option<String>,
+    /// The server's side of the selected candidate pair
+    pub remote_candidate: Option<String>,
+    /// The IP family of the selected candidate pair, `IpFamily::Ipv4` or
+    /// `IpFamily::Ipv6`, the one that won the race when both were gathered
+    pub ip_family: Option<IpFamily>,
+    /// The round trip time of the latest STUN connectivity check on the
+    /// selected candidate pair
+    pub round_trip_time: Option<Duration>,
+    /// Bytes sent over the selected candidate pair, including DTLS and SCTP
+    /// overhead
+    pub bytes_sent: usize,
+    /// Bytes received over the selected candidate pair, including DTLS and
+    /// SCTP overhead
+    pub bytes_received: usize,
+}
+
+/// The SCTP association's congestion control state and counters
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SctpStats {
+    /// The smoothed round trip time, `None` until the first measurement
+    pub srtt: Option<Duration>,
+    /// The retransmission timeout
+    pub rto: Duration,
+    /// The congestion window, in bytes
+    pub cwnd: u32,
+    /// The server's receiver window, in bytes
+    pub rwnd: u32,
+    /// DATA chunks received
+    pub datas_received: u64,
+    /// SACK chunks received
+    pub sacks_received: u64,
+    /// Retransmission timer expirations
+    pub t3_timeouts: u64,
+    /// Delayed acknowledgement timer expirations
+    pub ack_timeouts: u64,
+    /// Chunks retransmitted after being reported missing
+    pub fast_retransmits: u64,
+    /// SCTP packet bytes sent, before DTLS
+    pub bytes_sent: usize,
+    /// SCTP packet bytes received, after DTLS
+    pub bytes_received: usize,
+}
+
+// StatsSource
+#[derive(Clone, Default)]
+pub(crate) struct StatsSource {
+    peer_connection: Arc<Mutex<Option<Weak<RTCPeerConnection>>>>,
+}
+
+impl StatsSource {
+    pub(crate) fn set(&self, peer_connection: &Arc<RTCPeerConnection>) {
+        *self.peer_connection.lock().unwrap() = Some(Arc::downgrade(peer_connection));
+    }
+
+    /// Collects a snapshot, or returns None if the connection is gone or was
+    /// never started
+    pub(crate) async fn stats(&self) -> Option<Stats> {
+        let peer_connection = self
+            .peer_connection
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(Weak::upgrade)?;
+        let internal = &peer_connection.internal;
+
+        let mut stats = Stats::default();
+
+        let data_channels = internal.sctp_transport.data_channels.lock().await.clone();
+        for data_channel in data_channels {
+            if let Some(data_channel) = data_channel.data_channel().await {
+                stats.messages_sent += data_channel.messages_sent();
+                stats.messages_received += data_channel.messages_received();
+                stats.bytes_sent += data_channel.bytes_sent();
+                stats.bytes_received += data_channel.bytes_received();
+            }
+        }
+
+        if let Some(pair) = internal.ice_transport.selected_candidate_pair().await {
+            stats.ice.local_candidate = Some(pair.local.to_string());
+            stats.ice.remote_candidate = Some(pair.remote.to_string());
+            stats.ice.ip_family = Some(if pair.local.network_type().is_ipv6() {
+                IpFamily::Ipv6
+            } else {
+                IpFamily::Ipv4
+            });
+            stats.ice.round_trip_time = pair.current_round_trip_time();
+            stats.since_last_packet = SystemTime::now()
+                .duration_since(pair.remote.last_received())
+                .ok();
+        }
+        (stats.ice.bytes_sent, stats.ice.bytes_received) =
+            internal.ice_transport.bytes_sent_received().await;
+
+        if let Some(dtls_conn) = internal.dtls_transport.conn().await {
+            stats.dtls_cipher_suite = dtls_conn
+                .cipher_suite_id()
+                .await
+                .map(|cipher_suite_id| cipher_suite_id.to_string());
+        }
+
+        if let Some(association) = internal.sctp_transport.association().await {
+            let srtt = association.srtt().await;
+            stats.sctp.srtt = (srtt != 0).then(|| Duration::from_millis(srtt));
+            stats.sctp.rto = Duration::from_millis(association.rto().await);
+            stats.sctp.cwnd = association.cwnd().await;
+            stats.sctp.rwnd = association.rwnd().await;
+
+            let counters = association.stats().await;
+            stats.sctp.datas_received = counters.get_num_datas();
+            stats.sctp.sacks_received = counters.get_num_sacks();
+            stats.sctp.t3_timeouts = counters.get_num_t3timeouts();
+            stats.sctp.ack_timeouts = counters.get_num_ack_timeouts();
+            stats.sctp.fast_retransmits = counters.get_num_fast_retrans();
+
+            stats.sctp.bytes_sent = association.bytes_sent();
+            stats.sctp.bytes_received = association.bytes_received();
+        }
+
+        Some(stats)
+    }
+}
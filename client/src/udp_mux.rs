@@ -0,0 +1,38 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tokio::net::UdpSocket;
+
+use crate::webrtc::ice::udp_mux::UdpMux as IceUdpMux;
+
+/// One UDP socket shared by the connections of every `SocketConfig` it is
+/// passed to, instead of each connection binding its own. Packets are routed
+/// to a connection by the server address it sends to, so connections on one
+/// mux must reach distinct server addresses: connecting to an address
+/// another connection on the mux already uses fails with
+/// `ConnectError::AddressInUse`.
+///
+/// The socket closes once the mux and every connection using it are dropped.
+#[derive(Debug, Clone)]
+pub struct UdpMux {
+    pub(crate) inner: Arc<IceUdpMux>,
+}
+
+impl UdpMux {
+    /// Binds the shared socket to `addr`. A specific IP is offered to
+    /// servers as the host candidate's address, with the unspecified address
+    /// the first local IP of its family is offered instead.
+    pub async fn bind(addr: SocketAddr) -> io::Result<UdpMux> {
+        let socket = UdpSocket::bind(addr).await?;
+        let local_addr = socket.local_addr()?;
+        Ok(UdpMux {
+            inner: IceUdpMux::new(Arc::new(socket), local_addr),
+        })
+    }
+
+    /// The address the shared socket is bound to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.inner.local_addr()
+    }
+}
@@ -5,6 +5,7 @@ use crate::webrtc::peer_connection::certificate::RTCCertificate;
 
 use crate::webrtc::error::Result;
 use crate::webrtc::sctp_transport::RTCSctpTransport;
+use crate::webrtc::setting_engine::SettingEngine;
 
 use rcgen::KeyPair;
 use std::sync::Arc;
@@ -19,8 +20,8 @@ impl API {
     /// new_ice_gatherer creates a new ice gatherer.
     /// This constructor is part of the ORTC API. It is not
     /// meant to be used together with the basic WebRTC API.
-    pub(crate) fn new_ice_gatherer() -> Result<RTCIceGatherer> {
-        Ok(RTCIceGatherer::new())
+    pub(crate) fn new_ice_gatherer(setting_engine: Arc<SettingEngine>) -> Result<RTCIceGatherer> {
+        Ok(RTCIceGatherer::new(setting_engine))
     }
 
     /// new_ice_transport creates a new ice transport.
@@ -35,12 +36,17 @@ impl API {
     /// meant to be used together with the basic WebRTC API.
     pub(crate) fn new_dtls_transport(
         ice_transport: Arc<RTCIceTransport>,
+        setting_engine: Arc<SettingEngine>,
     ) -> Result<RTCDtlsTransport> {
         let kp = KeyPair::generate(&rcgen::PKCS_ECDSA_P256_SHA256)?;
         let cert = RTCCertificate::from_key_pair(kp)?;
         let certificates = vec![cert];
 
-        Ok(RTCDtlsTransport::new(ice_transport, certificates))
+        Ok(RTCDtlsTransport::new(
+            ice_transport,
+            certificates,
+            setting_engine,
+        ))
     }
 
     /// new_sctp_transport creates a new SCTPTransport.
@@ -48,7 +54,8 @@ impl API {
     /// meant to be used together with the basic WebRTC API.
     pub(crate) fn new_sctp_transport(
         dtls_transport: Arc<RTCDtlsTransport>,
+        setting_engine: Arc<SettingEngine>,
     ) -> Result<RTCSctpTransport> {
-        Ok(RTCSctpTransport::new(dtls_transport))
+        Ok(RTCSctpTransport::new(dtls_transport, setting_engine))
     }
 }
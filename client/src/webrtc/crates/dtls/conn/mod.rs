@@ -737,6 +737,12 @@ impl DTLSConn {
         self.handshake_completed_successfully.load(Ordering::SeqCst)
     }
 
+    /// Returns the negotiated cipher suite, if one has been chosen.
+    pub(crate) async fn cipher_suite_id(&self) -> Option<CipherSuiteId> {
+        let cipher_suite = self.state.cipher_suite.lock().await;
+        cipher_suite.as_ref().map(|cipher_suite| cipher_suite.id())
+    }
+
     async fn read_and_buffer(
         ctx: &mut ConnReaderContext,
         next_conn: &Arc<dyn crate::webrtc::util::Conn + Send + Sync>,
@@ -65,6 +65,8 @@ pub(crate) enum Error {
     ErrNoAvailableSignatureSchemes,
     #[error("no certificates configured")]
     ErrNoCertificates,
+    #[error("remote certificate does not match any fingerprint in the remote description")]
+    ErrNoMatchingCertificateFingerprint,
     #[error("client requested zero or more elliptic curves that are not supported by the server")]
     ErrNoSupportedEllipticCurves,
     #[error("unsupported protocol version")]
@@ -0,0 +1,297 @@
+use crate::webrtc::util::{self, Conn};
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpSocket;
+use tokio::sync::{broadcast, mpsc, Mutex};
+
+type ConnResult<T> = std::result::Result<T, util::Error>;
+
+/// The port active candidates advertise, since they never accept
+/// connections.
+///
+/// RFC 6544 Section 4.5
+pub(crate) const ACTIVE_TCP_PORT: u16 = 9;
+/// How long connecting to a passive candidate may take.
+const DIAL_TIMEOUT: Duration = Duration::from_secs(5);
+/// How many packets are held for a connection while it is being made, and
+/// from all connections until the candidate reads them.
+const QUEUE_SIZE: usize = 256;
+
+/// A packet read from a connection, with the remote candidate's address.
+type InboundPacket = (Vec<u8>, SocketAddr);
+
+/// ActiveTcpConn is the conn of an active ICE-TCP candidate. Sending to a
+/// passive remote candidate connects to it from the candidate's IP, and
+/// later packets to it reuse that connection. Packets are framed with their
+/// length, so the connections read and write like a UDP socket would.
+///
+/// RFC 6544, RFC 4571
+pub(crate) struct ActiveTcpConn {
+    local_ip: IpAddr,
+    streams: Mutex<HashMap<SocketAddr, mpsc::Sender<Vec<u8>>>>,
+    inbound_tx: mpsc::Sender<InboundPacket>,
+    inbound_rx: Mutex<mpsc::Receiver<InboundPacket>>,
+    closed_ch: Mutex<Option<broadcast::Sender<()>>>,
+}
+
+impl ActiveTcpConn {
+    pub(crate) fn new(local_ip: IpAddr) -> Self {
+        let (inbound_tx, inbound_rx) = mpsc::channel(QUEUE_SIZE);
+        let (closed_tx, _) = broadcast::channel(1);
+        ActiveTcpConn {
+            local_ip,
+            streams: Mutex::new(HashMap::new()),
+            inbound_tx,
+            inbound_rx: Mutex::new(inbound_rx),
+            closed_ch: Mutex::new(Some(closed_tx)),
+        }
+    }
+
+    async fn closed_rx(&self) -> ConnResult<broadcast::Receiver<()>> {
+        let closed_ch = self.closed_ch.lock().await;
+        match &*closed_ch {
+            Some(tx) => Ok(tx.subscribe()),
+            None => Err(util::Error::ErrUseClosedNetworkConn),
+        }
+    }
+
+    /// Connects to the remote candidate, then writes the packets queued for
+    /// it until the connection fails or ends, or the conn closes. A failed
+    /// connection is made again by the next send to the remote.
+    async fn run_stream(
+        local_ip: IpAddr,
+        remote: SocketAddr,
+        mut outbound_rx: mpsc::Receiver<Vec<u8>>,
+        inbound_tx: mpsc::Sender<InboundPacket>,
+        mut closed_rx: broadcast::Receiver<()>,
+    ) {
+        let stream = tokio::select! {
+            result = tokio::time::timeout(DIAL_TIMEOUT, dial(local_ip, remote)) => match result {
+                Ok(Ok(stream)) => stream,
+                Ok(Err(err)) => {
+                    log::debug!("failed to connect to ICE-TCP candidate {}: {}", remote, err);
+                    return;
+                }
+                Err(_) => {
+                    log::debug!("timed out connecting to ICE-TCP candidate {}", remote);
+                    return;
+                }
+            },
+            _ = closed_rx.recv() => return,
+        };
+        let (mut reader, mut writer) = stream.into_split();
+
+        let mut reader_closed_rx = closed_rx.resubscribe();
+        let mut reader_task = tokio::spawn(async move {
+            loop {
+                let packet = tokio::select! {
+                    result = read_framed(&mut reader) => match result {
+                        Ok(packet) => packet,
+                        Err(err) => {
+                            log::debug!("ICE-TCP connection to {} ended: {}", remote, err);
+                            return;
+                        }
+                    },
+                    _ = reader_closed_rx.recv() => return,
+                };
+                if inbound_tx.send((packet, remote)).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        loop {
+            let packet = tokio::select! {
+                packet = outbound_rx.recv() => match packet {
+                    Some(packet) => packet,
+                    None => return,
+                },
+                _ = closed_rx.recv() => return,
+                // the remote closed the connection, the next send makes it again
+                _ = &mut reader_task => return,
+            };
+            if let Err(err) = write_framed(&mut writer, &packet).await {
+                log::debug!("failed to write to ICE-TCP candidate {}: {}", remote, err);
+                return;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Conn for ActiveTcpConn {
+    async fn recv(&self, buf: &mut [u8]) -> ConnResult<usize> {
+        let (n, _) = self.recv_from(buf).await?;
+        Ok(n)
+    }
+
+    /// Reads the next packet from any connection. Like a datagram socket,
+    /// a packet longer than buf is truncated.
+    async fn recv_from(&self, buf: &mut [u8]) -> ConnResult<(usize, SocketAddr)> {
+        let mut closed_rx = self.closed_rx().await?;
+        let mut inbound_rx = self.inbound_rx.lock().await;
+        tokio::select! {
+            packet = inbound_rx.recv() => match packet {
+                Some((data, from)) => {
+                    let n = data.len().min(buf.len());
+                    buf[..n].copy_from_slice(&data[..n]);
+                    Ok((n, from))
+                }
+                None => Err(util::Error::ErrUseClosedNetworkConn),
+            },
+            _ = closed_rx.recv() => Err(util::Error::ErrUseClosedNetworkConn),
+        }
+    }
+
+    async fn send(&self, _buf: &[u8]) -> ConnResult<usize> {
+        Err(util::Error::ErrNoRemAddr)
+    }
+
+    /// Queues the packet on the connection to target, making the connection
+    /// first if there is none. Like a full socket buffer, a full queue drops
+    /// the packet.
+    async fn send_to(&self, buf: &[u8], target: SocketAddr) -> ConnResult<usize> {
+        if buf.len() > u16::MAX as usize {
+            return Err(util::Error::ErrPacketTooBig);
+        }
+        let closed_rx = self.closed_rx().await?;
+
+        let mut streams = self.streams.lock().await;
+        if streams
+            .get(&target)
+            .is_none_or(|outbound_tx| outbound_tx.is_closed())
+        {
+            let (outbound_tx, outbound_rx) = mpsc::channel(QUEUE_SIZE);
+            tokio::spawn(Self::run_stream(
+                self.local_ip,
+                target,
+                outbound_rx,
+                self.inbound_tx.clone(),
+                closed_rx,
+            ));
+            streams.insert(target, outbound_tx);
+        }
+        if let Some(outbound_tx) = streams.get(&target) {
+            let _ = outbound_tx.try_send(buf.to_vec());
+        }
+
+        Ok(buf.len())
+    }
+
+    async fn local_addr(&self) -> ConnResult<SocketAddr> {
+        Ok(SocketAddr::new(self.local_ip, ACTIVE_TCP_PORT))
+    }
+
+    async fn remote_addr(&self) -> Option<SocketAddr> {
+        None
+    }
+
+    /// Closes every connection.
+    async fn close(&self) -> ConnResult<()> {
+        let mut closed_ch = self.closed_ch.lock().await;
+        if closed_ch.take().is_none() {
+            return Err(util::Error::ErrAlreadyClosed);
+        }
+
+        let mut streams = self.streams.lock().await;
+        streams.clear();
+
+        Ok(())
+    }
+}
+
+/// Connects to remote from local_ip, so the connection leaves through the
+/// candidate's interface.
+async fn dial(local_ip: IpAddr, remote: SocketAddr) -> io::Result<tokio::net::TcpStream> {
+    let socket = if local_ip.is_ipv4() {
+        TcpSocket::new_v4()?
+    } else {
+        TcpSocket::new_v6()?
+    };
+    socket.bind(SocketAddr::new(local_ip, 0))?;
+    socket.connect(remote).await
+}
+
+/// Reads a packet framed by a 16 bit length.
+///
+/// RFC 4571 Section 2
+async fn read_framed<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut length = [0u8; 2];
+    reader.read_exact(&mut length).await?;
+    let mut packet = vec![0u8; u16::from_be_bytes(length) as usize];
+    reader.read_exact(&mut packet).await?;
+    Ok(packet)
+}
+
+/// Writes a packet framed by a 16 bit length.
+///
+/// RFC 4571 Section 2
+async fn write_framed<W: AsyncWrite + Unpin>(writer: &mut W, packet: &[u8]) -> io::Result<()> {
+    let mut framed = Vec::with_capacity(2 + packet.len());
+    framed.extend_from_slice(&(packet.len() as u16).to_be_bytes());
+    framed.extend_from_slice(packet);
+    writer.write_all(&framed).await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::Ipv4Addr;
+    use tokio::net::TcpListener;
+
+    async fn accept(listener: &TcpListener) -> ConnResult<tokio::net::TcpStream> {
+        let (stream, _) = tokio::time::timeout(DIAL_TIMEOUT, listener.accept())
+            .await
+            .map_err(|_| util::Error::Other("no connection accepted".to_owned()))??;
+        Ok(stream)
+    }
+
+    #[tokio::test]
+    async fn test_framed_round_trip_and_redial() -> ConnResult<()> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+        let passive = listener.local_addr()?;
+        let conn = ActiveTcpConn::new(Ipv4Addr::LOCALHOST.into());
+
+        conn.send_to(b"hello", passive).await?;
+        let mut stream = accept(&listener).await?;
+        assert_eq!(read_framed(&mut stream).await?, b"hello");
+
+        write_framed(&mut stream, b"world").await?;
+        let mut buf = [0u8; 1500];
+        let (n, from) = conn.recv_from(&mut buf).await?;
+        assert_eq!(&buf[..n], b"world");
+        assert_eq!(from, passive);
+
+        assert_eq!(
+            conn.send_to(&vec![0u8; u16::MAX as usize + 1], passive)
+                .await
+                .unwrap_err(),
+            util::Error::ErrPacketTooBig
+        );
+        conn.send_to(&vec![1u8; u16::MAX as usize], passive).await?;
+        assert_eq!(
+            read_framed(&mut stream).await?,
+            vec![1u8; u16::MAX as usize]
+        );
+
+        // the passive side drops the connection, a later send connects again
+        drop(stream);
+        let mut stream = loop {
+            conn.send_to(b"again", passive).await?;
+            if let Ok(accepted) =
+                tokio::time::timeout(Duration::from_millis(50), listener.accept()).await
+            {
+                break accepted?.0;
+            }
+        };
+        assert_eq!(read_framed(&mut stream).await?, b"again");
+
+        conn.close().await?;
+        Ok(())
+    }
+}
@@ -2,10 +2,12 @@ use super::*;
 use crate::webrtc::ice::error::*;
 use crate::webrtc::ice::mdns::*;
 use crate::webrtc::ice::network_type::*;
+use crate::webrtc::ice::udp_mux::UdpMux;
 use crate::webrtc::ice::url::*;
 
 use crate::webrtc::util::vnet::net::*;
 
+use std::net::IpAddr;
 use std::time::Duration;
 
 /// The interval at which the agent performs candidate checks in the connecting phase.
@@ -20,6 +22,17 @@ pub(crate) const DEFAULT_DISCONNECTED_TIMEOUT: Duration = Duration::from_secs(5)
 /// The default time till an Agent transitions to failed after disconnected.
 pub(crate) const DEFAULT_FAILED_TIMEOUT: Duration = Duration::from_secs(25);
 
+/// How often consent to send on the selected pair is refreshed, randomized by up to 20% either way.
+///
+/// RFC 7675 Section 5.1
+pub(crate) const DEFAULT_CONSENT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The default time without a consent response till consent expires and the Agent transitions to
+/// failed.
+///
+/// RFC 7675 Section 5.1
+pub(crate) const DEFAULT_CONSENT_TIMEOUT: Duration = Duration::from_secs(15);
+
 /// Wait time before nominating a host candidate.
 pub(crate) const DEFAULT_HOST_ACCEPTANCE_MIN_WAIT: Duration = Duration::from_secs(0);
 
@@ -41,11 +54,22 @@ pub(crate) const MAX_BUFFER_SIZE: usize = 1000 * 1000; // 1MB
 /// Wait time before binding requests can be deleted.
 pub(crate) const MAX_BINDING_REQUEST_TIMEOUT: Duration = Duration::from_millis(4000);
 
+/// The timeout for a STUN server to answer while gathering srflx candidates.
+pub(crate) const STUN_GATHER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The timeout for a TURN server to grant an allocation while gathering relay candidates.
+pub(crate) const TURN_GATHER_TIMEOUT: Duration = Duration::from_secs(5);
+
 pub(crate) fn default_candidate_types() -> Vec<CandidateType> {
-    vec![CandidateType::Host]
+    vec![
+        CandidateType::Host,
+        CandidateType::ServerReflexive,
+        CandidateType::Relay,
+    ]
 }
 
 pub(crate) type InterfaceFilterFn = Box<dyn (Fn(&str) -> bool) + Send + Sync>;
+pub(crate) type IpFilterFn = Box<dyn (Fn(IpAddr) -> bool) + Send + Sync>;
 
 /// Collects the arguments to `ice::Agent` construction into a single structure, for
 /// future-proofness of the interface.
@@ -76,6 +100,14 @@ pub(crate) struct AgentConfig {
     /// If the duration is 0, we will never go to failed.
     pub(crate) failed_timeout: Option<Duration>,
 
+    /// Defaults to 15 seconds when this property is nil.
+    /// If the duration is 0, consent never expires and is not checked.
+    pub(crate) consent_timeout: Option<Duration>,
+
+    /// Determines how often the local interfaces are checked for changes. They are never checked
+    /// when this property is nil or the duration is 0.
+    pub(crate) network_monitor_interval: Option<Duration>,
+
     /// Determines how often should we send ICE keepalives (should be less then connectiontimeout
     /// above) when this is nil, it defaults to 10 seconds.
     /// A keepalive interval of 0 means we never send keepalive packets
@@ -130,6 +162,23 @@ pub(crate) struct AgentConfig {
     /// A function that you can use in order to whitelist or blacklist the interfaces which are
     /// used to gather ICE candidates.
     pub(crate) interface_filter: Arc<Option<InterfaceFilterFn>>,
+
+    /// A function that you can use in order to whitelist or blacklist the local IPs which are
+    /// used to gather host candidates.
+    pub(crate) ip_filter: Arc<Option<IpFilterFn>>,
+
+    /// The lowest port local UDP sockets are bound to, 0 leaves it unbounded.
+    pub(crate) port_min: u16,
+    /// The highest port local UDP sockets are bound to, 0 leaves it unbounded.
+    pub(crate) port_max: u16,
+
+    /// Binds every local socket to this IP instead of gathering host candidates on each
+    /// interface. Servers of the other IP family are skipped.
+    pub(crate) local_ip: Option<IpAddr>,
+
+    /// Gathers the UDP host candidate on this shared socket instead of binding one per
+    /// interface.
+    pub(crate) udp_mux: Option<Arc<UdpMux>>,
 }
 
 impl AgentConfig {
@@ -183,6 +232,12 @@ impl AgentConfig {
             a.keepalive_interval = DEFAULT_KEEPALIVE_INTERVAL;
         }
 
+        if let Some(consent_timeout) = self.consent_timeout {
+            a.consent_timeout = consent_timeout;
+        } else {
+            a.consent_timeout = DEFAULT_CONSENT_TIMEOUT;
+        }
+
         if self.check_interval == Duration::from_secs(0) {
             a.check_interval = DEFAULT_CHECK_INTERVAL;
         } else {
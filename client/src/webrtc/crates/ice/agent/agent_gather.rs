@@ -1,22 +1,38 @@
 use super::*;
+use crate::webrtc::ice::active_tcp::{ActiveTcpConn, ACTIVE_TCP_PORT};
 use crate::webrtc::ice::network_type::*;
+use crate::webrtc::ice::tcp_type::TcpType;
+use crate::webrtc::ice::udp_mux::UdpMux;
 use crate::webrtc::ice::util::*;
 
 use crate::webrtc::util::{vnet::net::*, Conn};
 
 use crate::webrtc::ice::candidate::candidate_base::CandidateBaseConfig;
 use crate::webrtc::ice::candidate::candidate_host::CandidateHostConfig;
+use crate::webrtc::ice::candidate::candidate_relay::CandidateRelayConfig;
+use crate::webrtc::ice::candidate::candidate_server_reflexive::CandidateServerReflexiveConfig;
 use crate::webrtc::ice::candidate::*;
+use crate::webrtc::ice::url::{ProtoType, SchemeType, Url};
+use crate::webrtc::turn::client::stun_conn::StunConn;
+use crate::webrtc::turn::client::{Client as TurnClient, ClientConfig as TurnClientConfig};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::sync::Arc;
+use tokio::net::{TcpSocket, TcpStream};
 use waitgroup::WaitGroup;
 
 pub(crate) struct GatherCandidatesInternalParams {
     pub(crate) candidate_types: Vec<CandidateType>,
     pub(crate) network_types: Vec<NetworkType>,
+    pub(crate) urls: Vec<Url>,
     pub(crate) mdns_mode: MulticastDnsMode,
     pub(crate) mdns_name: String,
     pub(crate) net: Arc<Net>,
     pub(crate) interface_filter: Arc<Option<InterfaceFilterFn>>,
+    pub(crate) ip_filter: Arc<Option<IpFilterFn>>,
+    pub(crate) port_min: u16,
+    pub(crate) port_max: u16,
+    pub(crate) local_ip: Option<IpAddr>,
+    pub(crate) udp_mux: Option<Arc<UdpMux>>,
     pub(crate) ext_ip_mapper: Arc<Option<ExternalIpMapper>>,
     pub(crate) agent_internal: Arc<AgentInternal>,
     pub(crate) gathering_state: Arc<AtomicU8>,
@@ -28,6 +44,11 @@ struct GatherCandidatesLocalParams {
     mdns_mode: MulticastDnsMode,
     mdns_name: String,
     interface_filter: Arc<Option<InterfaceFilterFn>>,
+    ip_filter: Arc<Option<IpFilterFn>>,
+    port_min: u16,
+    port_max: u16,
+    local_ip: Option<IpAddr>,
+    udp_mux: Option<Arc<UdpMux>>,
     ext_ip_mapper: Arc<Option<ExternalIpMapper>>,
     net: Arc<Net>,
     agent_internal: Arc<AgentInternal>,
@@ -52,6 +73,11 @@ impl Agent {
                         mdns_mode: params.mdns_mode,
                         mdns_name: params.mdns_name.clone(),
                         interface_filter: Arc::clone(&params.interface_filter),
+                        ip_filter: Arc::clone(&params.ip_filter),
+                        port_min: params.port_min,
+                        port_max: params.port_max,
+                        local_ip: params.local_ip,
+                        udp_mux: params.udp_mux.clone(),
                         ext_ip_mapper: Arc::clone(&params.ext_ip_mapper),
                         net: Arc::clone(&params.net),
                         agent_internal: Arc::clone(&params.agent_internal),
@@ -64,6 +90,52 @@ impl Agent {
                         Self::gather_candidates_local(local_params).await;
                     });
                 }
+                CandidateType::ServerReflexive => {
+                    let urls = params.urls.clone();
+                    let network_types = params.network_types.clone();
+                    let (port_min, port_max, local_ip) =
+                        (params.port_min, params.port_max, params.local_ip);
+                    let net = Arc::clone(&params.net);
+                    let agent_internal = Arc::clone(&params.agent_internal);
+
+                    let w = wg.worker();
+                    tokio::spawn(async move {
+                        let _d = w;
+
+                        Self::gather_candidates_srflx(
+                            urls,
+                            network_types,
+                            port_min,
+                            port_max,
+                            local_ip,
+                            net,
+                            agent_internal,
+                        )
+                        .await;
+                    });
+                }
+                CandidateType::Relay => {
+                    let urls = params.urls.clone();
+                    let (port_min, port_max, local_ip) =
+                        (params.port_min, params.port_max, params.local_ip);
+                    let net = Arc::clone(&params.net);
+                    let agent_internal = Arc::clone(&params.agent_internal);
+
+                    let w = wg.worker();
+                    tokio::spawn(async move {
+                        let _d = w;
+
+                        Self::gather_candidates_relay(
+                            urls,
+                            port_min,
+                            port_max,
+                            local_ip,
+                            net,
+                            agent_internal,
+                        )
+                        .await;
+                    });
+                }
                 _ => {}
             }
         }
@@ -102,6 +174,11 @@ impl Agent {
             mdns_mode,
             mdns_name,
             interface_filter,
+            ip_filter,
+            port_min,
+            port_max,
+            local_ip,
+            udp_mux,
             ext_ip_mapper,
             net,
             agent_internal,
@@ -110,13 +187,52 @@ impl Agent {
             params.mdns_mode,
             params.mdns_name,
             params.interface_filter,
+            params.ip_filter,
+            params.port_min,
+            params.port_max,
+            params.local_ip,
+            params.udp_mux,
             params.ext_ip_mapper,
             params.net,
             params.agent_internal,
         );
 
-        let ips = local_interfaces(&net, &*interface_filter, &network_types).await;
-        for ip in ips {
+        let mut ips = local_interfaces(&net, &*interface_filter, &ip_filter, &network_types).await;
+        if let Some(local_ip) = local_ip {
+            if !ips.contains(&local_ip) {
+                log::warn!(
+                    "[{}]: local ip {} is not on an allowed interface",
+                    agent_internal.get_name(),
+                    local_ip
+                );
+            }
+            ips.retain(|ip| *ip == local_ip);
+        }
+
+        // agents on a mux share its one socket, so each gathers a single UDP
+        // host candidate, on the mux's IP or else the first local IP of its
+        // family
+        let mut mux_conn = None;
+        if let Some(udp_mux) = &udp_mux {
+            let laddr = udp_mux.local_addr();
+            let mux_ip = if laddr.ip().is_unspecified() {
+                ips.iter()
+                    .filter(|ip| ip.is_ipv4() == laddr.is_ipv4())
+                    .min_by_key(|ip| (ip.is_loopback(), **ip))
+                    .copied()
+            } else {
+                ips.insert(laddr.ip());
+                Some(laddr.ip())
+            };
+
+            if let Some(mux_ip) = mux_ip {
+                let ufrag = agent_internal.ufrag_pwd.lock().await.local_ufrag.clone();
+                mux_conn = Some((mux_ip, udp_mux.get_conn(&ufrag).await, laddr.port()));
+            }
+        }
+
+        // host candidates are ranked in RFC 8421's interleaved order of IPs
+        for (address_rank, ip) in interleave_ip_families(ips).into_iter().enumerate() {
             let mut mapped_ip = ip;
 
             if mdns_mode != MulticastDnsMode::QueryAndGather && ext_ip_mapper.is_some() {
@@ -141,95 +257,568 @@ impl Agent {
                 mapped_ip.to_string()
             };
 
-            //TODO: for network in networks
-            let network = UDP.to_owned();
+            for network in networks(&network_types, &net) {
+                let (conn, port, tcp_type): (Arc<dyn Conn + Send + Sync>, u16, TcpType) =
+                    if network == TCP {
+                        // active candidates connect out, so there is nothing
+                        // to listen on
+                        (
+                            Arc::new(ActiveTcpConn::new(ip)),
+                            ACTIVE_TCP_PORT,
+                            TcpType::Active,
+                        )
+                    } else if udp_mux.is_some() {
+                        match &mux_conn {
+                            Some((mux_ip, conn, port)) if *mux_ip == ip => {
+                                (Arc::clone(conn), *port, TcpType::Unspecified)
+                            }
+                            _ => continue,
+                        }
+                    } else {
+                        let conn = match listen_udp_in_port_range(
+                            &net,
+                            port_max,
+                            port_min,
+                            SocketAddr::new(ip, 0),
+                        )
+                        .await
+                        {
+                            Ok(conn) => conn,
+                            Err(err) => {
+                                log::warn!(
+                                    "[{}]: could not listen {} {}: {}",
+                                    agent_internal.get_name(),
+                                    network,
+                                    ip,
+                                    err
+                                );
+                                continue;
+                            }
+                        };
+
+                        let port = match conn.local_addr().await {
+                            Ok(addr) => addr.port(),
+                            Err(err) => {
+                                log::warn!(
+                                    "[{}]: could not get local addr: {}",
+                                    agent_internal.get_name(),
+                                    err
+                                );
+                                continue;
+                            }
+                        };
+
+                        (conn, port, TcpType::Unspecified)
+                    };
+
+                let host_config = CandidateHostConfig {
+                    base_config: CandidateBaseConfig {
+                        network: network.to_owned(),
+                        address: address.clone(),
+                        port,
+                        component: COMPONENT_RTP,
+                        conn: Some(conn),
+                        tcp_type,
+                        address_rank: address_rank.try_into().unwrap_or(u16::MAX),
+                        ..CandidateBaseConfig::default()
+                    },
+                    ..CandidateHostConfig::default()
+                };
+
+                let candidate: Arc<dyn Candidate + Send + Sync> =
+                    match host_config.new_candidate_host().await {
+                        Ok(candidate) => {
+                            if mdns_mode == MulticastDnsMode::QueryAndGather {
+                                if let Err(err) = candidate.set_ip(&ip).await {
+                                    log::warn!(
+                                        "[{}]: Failed to create host candidate: {} {} {}: {:?}",
+                                        agent_internal.get_name(),
+                                        network,
+                                        mapped_ip,
+                                        port,
+                                        err
+                                    );
+                                    continue;
+                                }
+                            }
+                            Arc::new(candidate)
+                        }
+                        Err(err) => {
+                            log::warn!(
+                                "[{}]: Failed to create host candidate: {} {} {}: {}",
+                                agent_internal.get_name(),
+                                network,
+                                mapped_ip,
+                                port,
+                                err
+                            );
+                            continue;
+                        }
+                    };
 
-            let conn: Arc<dyn Conn + Send + Sync> =
-                match listen_udp_in_port_range(&net, SocketAddr::new(ip, 0)).await {
-                    Ok(conn) => conn,
-                    Err(err) => {
+                {
+                    if let Err(err) = agent_internal.add_candidate(&candidate).await {
+                        if let Err(close_err) = candidate.close().await {
+                            log::warn!(
+                                "[{}]: Failed to close candidate: {}",
+                                agent_internal.get_name(),
+                                close_err
+                            );
+                        }
                         log::warn!(
-                            "[{}]: could not listen {} {}: {}",
+                            "[{}]: Failed to append to localCandidates and run onCandidateHdlr: {}",
                             agent_internal.get_name(),
-                            network,
-                            ip,
                             err
                         );
-                        continue;
                     }
-                };
+                }
+            }
+        }
+    }
 
-            let port = match conn.local_addr().await {
-                Ok(addr) => addr.port(),
-                Err(err) => {
-                    log::warn!(
-                        "[{}]: could not get local addr: {}",
-                        agent_internal.get_name(),
-                        err
-                    );
+    async fn gather_candidates_srflx(
+        urls: Vec<Url>,
+        network_types: Vec<NetworkType>,
+        port_min: u16,
+        port_max: u16,
+        local_ip: Option<IpAddr>,
+        net: Arc<Net>,
+        agent_internal: Arc<AgentInternal>,
+    ) {
+        let wg = WaitGroup::new();
+
+        for network_type in network_types {
+            if !network_type.is_udp() {
+                continue;
+            }
+            let bind_ip = match bind_ip(local_ip, network_type.is_ipv4()) {
+                Some(ip) => ip,
+                None => continue,
+            };
+
+            for url in &urls {
+                // stuns: needs a TLS connection, which srflx gathering does not make
+                if url.scheme != SchemeType::Stun {
                     continue;
                 }
-            };
 
-            let host_config = CandidateHostConfig {
-                base_config: CandidateBaseConfig {
-                    network: network.clone(),
-                    address,
-                    port,
-                    component: COMPONENT_RTP,
-                    conn: Some(conn),
-                    ..CandidateBaseConfig::default()
-                },
-                ..CandidateHostConfig::default()
-            };
+                let url = url.clone();
+                let net = Arc::clone(&net);
+                let agent_internal = Arc::clone(&agent_internal);
+
+                let w = wg.worker();
+                tokio::spawn(async move {
+                    let _d = w;
+
+                    let host_port = format!("{}:{}", url.host, url.port);
+                    let server_addr = match resolve_addr(network_type.is_ipv4(), &host_port).await {
+                        Ok(addr) => addr,
+                        Err(err) => {
+                            log::warn!(
+                                "[{}]: failed to resolve stun host: {}: {}",
+                                agent_internal.get_name(),
+                                host_port,
+                                err
+                            );
+                            return;
+                        }
+                    };
 
-            let candidate: Arc<dyn Candidate + Send + Sync> =
-                match host_config.new_candidate_host().await {
-                    Ok(candidate) => {
-                        if mdns_mode == MulticastDnsMode::QueryAndGather {
-                            if let Err(err) = candidate.set_ip(&ip).await {
+                    let conn: Arc<dyn Conn + Send + Sync> = match listen_udp_in_port_range(
+                        &net,
+                        port_max,
+                        port_min,
+                        SocketAddr::new(bind_ip, 0),
+                    )
+                    .await
+                    {
+                        Ok(conn) => conn,
+                        Err(err) => {
+                            log::warn!(
+                                "[{}]: failed to listen for {}: {}",
+                                agent_internal.get_name(),
+                                server_addr,
+                                err
+                            );
+                            return;
+                        }
+                    };
+
+                    let xoraddr =
+                        match get_xormapped_addr(&conn, server_addr, STUN_GATHER_TIMEOUT).await {
+                            Ok(xoraddr) => xoraddr,
+                            Err(err) => {
                                 log::warn!(
-                                    "[{}]: Failed to create host candidate: {} {} {}: {:?}",
+                                    "[{}]: could not get server reflexive address {} {}: {}",
                                     agent_internal.get_name(),
-                                    network,
-                                    mapped_ip,
-                                    port,
+                                    network_type,
+                                    url,
                                     err
                                 );
-                                continue;
+                                let _ = conn.close().await;
+                                return;
+                            }
+                        };
+
+                    let laddr = match conn.local_addr().await {
+                        Ok(laddr) => laddr,
+                        Err(err) => {
+                            log::warn!(
+                                "[{}]: could not get local addr: {}",
+                                agent_internal.get_name(),
+                                err
+                            );
+                            let _ = conn.close().await;
+                            return;
+                        }
+                    };
+
+                    let srflx_config = CandidateServerReflexiveConfig {
+                        base_config: CandidateBaseConfig {
+                            network: UDP.to_owned(),
+                            address: xoraddr.ip.to_string(),
+                            port: xoraddr.port,
+                            component: COMPONENT_RTP,
+                            conn: Some(conn),
+                            ..CandidateBaseConfig::default()
+                        },
+                        rel_addr: laddr.ip().to_string(),
+                        rel_port: laddr.port(),
+                    };
+
+                    let candidate: Arc<dyn Candidate + Send + Sync> =
+                        match srflx_config.new_candidate_server_reflexive().await {
+                            Ok(candidate) => Arc::new(candidate),
+                            Err(err) => {
+                                log::warn!(
+                                "[{}]: Failed to create server reflexive candidate: {} {} {}: {:?}",
+                                agent_internal.get_name(),
+                                network_type,
+                                xoraddr.ip,
+                                xoraddr.port,
+                                err
+                            );
+                                return;
                             }
+                        };
+
+                    if let Err(err) = agent_internal.add_candidate(&candidate).await {
+                        if let Err(close_err) = candidate.close().await {
+                            log::warn!(
+                                "[{}]: Failed to close candidate: {}",
+                                agent_internal.get_name(),
+                                close_err
+                            );
                         }
-                        Arc::new(candidate)
-                    }
-                    Err(err) => {
                         log::warn!(
-                            "[{}]: Failed to create host candidate: {} {} {}: {}",
+                            "[{}]: Failed to append to localCandidates and run onCandidateHdlr: {}",
                             agent_internal.get_name(),
-                            network,
-                            mapped_ip,
-                            port,
                             err
                         );
-                        continue;
                     }
-                };
+                });
+            }
+        }
 
-            {
-                if let Err(err) = agent_internal.add_candidate(&candidate).await {
-                    if let Err(close_err) = candidate.close().await {
-                        log::warn!(
-                            "[{}]: Failed to close candidate: {}",
-                            agent_internal.get_name(),
-                            close_err
-                        );
-                    }
+        wg.wait().await;
+    }
+
+    async fn gather_candidates_relay(
+        urls: Vec<Url>,
+        port_min: u16,
+        port_max: u16,
+        local_ip: Option<IpAddr>,
+        net: Arc<Net>,
+        agent_internal: Arc<AgentInternal>,
+    ) {
+        let wg = WaitGroup::new();
+
+        for url in urls {
+            // turns: over UDP would need DTLS to the server, which relay
+            // gathering does not do
+            match (url.scheme, url.proto) {
+                (SchemeType::Turn, ProtoType::Udp)
+                | (SchemeType::Turn, ProtoType::Tcp)
+                | (SchemeType::Turns, ProtoType::Tcp) => {}
+                _ => continue,
+            }
+
+            let net = Arc::clone(&net);
+            let agent_internal = Arc::clone(&agent_internal);
+
+            let w = wg.worker();
+            tokio::spawn(async move {
+                let _d = w;
+
+                if let Err(err) = Self::gather_candidate_relay(
+                    &url,
+                    port_min,
+                    port_max,
+                    local_ip,
+                    &net,
+                    &agent_internal,
+                )
+                .await
+                {
                     log::warn!(
-                        "[{}]: Failed to append to localCandidates and run onCandidateHdlr: {}",
+                        "[{}]: failed to gather relay candidate from {}: {}",
                         agent_internal.get_name(),
+                        url,
                         err
                     );
                 }
+            });
+        }
+
+        wg.wait().await;
+    }
+
+    async fn gather_candidate_relay(
+        url: &Url,
+        port_min: u16,
+        port_max: u16,
+        local_ip: Option<IpAddr>,
+        net: &Arc<Net>,
+        agent_internal: &Arc<AgentInternal>,
+    ) -> Result<()> {
+        let host_port = format!("{}:{}", url.host, url.port);
+        let turn_server_addr = match local_ip {
+            Some(ip) => resolve_addr(ip.is_ipv4(), &host_port).await?,
+            None => match resolve_addr(true, &host_port).await {
+                Ok(addr) => addr,
+                Err(_) => resolve_addr(false, &host_port).await?,
+            },
+        };
+        let bind_ip =
+            bind_ip(local_ip, turn_server_addr.is_ipv4()).ok_or(Error::ErrNoStunServerAddress)?;
+
+        // only the conn to the server is a stream over TCP and TLS, the
+        // relayed transport address is UDP either way
+        let (conn, reliable): (Arc<dyn Conn + Send + Sync>, bool) = if url.proto == ProtoType::Udp {
+            let conn =
+                listen_udp_in_port_range(net, port_max, port_min, SocketAddr::new(bind_ip, 0))
+                    .await?;
+            (conn, false)
+        } else {
+            if net.is_virtual() {
+                return Err(Error::ErrProtoType);
+            }
+            let connect = async {
+                if local_ip.is_none() {
+                    return TcpStream::connect(turn_server_addr).await;
+                }
+                let socket = if bind_ip.is_ipv4() {
+                    TcpSocket::new_v4()?
+                } else {
+                    TcpSocket::new_v6()?
+                };
+                socket.bind(SocketAddr::new(bind_ip, 0))?;
+                socket.connect(turn_server_addr).await
+            };
+            let stream = match tokio::time::timeout(TURN_GATHER_TIMEOUT, connect).await {
+                Ok(stream) => stream?,
+                Err(_) => return Err(Error::ErrTurnTimeout),
+            };
+            let conn = if url.scheme == SchemeType::Turns {
+                match tokio::time::timeout(
+                    TURN_GATHER_TIMEOUT,
+                    StunConn::from_tls(stream, &url.host),
+                )
+                .await
+                {
+                    Ok(conn) => conn?,
+                    Err(_) => return Err(Error::ErrTurnTimeout),
+                }
+            } else {
+                StunConn::from_tcp(stream)?
+            };
+            (Arc::new(conn), true)
+        };
+        let laddr = conn.local_addr().await?;
+
+        let client = TurnClient::new(TurnClientConfig {
+            turn_server_addr,
+            username: url.username.clone(),
+            password: url.password.clone(),
+            conn,
+            reliable,
+        });
+
+        let relay_conn = match tokio::time::timeout(TURN_GATHER_TIMEOUT, client.allocate()).await {
+            Ok(Ok(relay_conn)) => relay_conn,
+            Ok(Err(err)) => {
+                client.close().await;
+                return Err(err.into());
+            }
+            Err(_) => {
+                client.close().await;
+                return Err(Error::ErrTurnTimeout);
             }
+        };
+
+        let raddr = relay_conn.local_addr().await?;
+        let relay_conn: Arc<dyn Conn + Send + Sync> = Arc::new(relay_conn);
+
+        let relay_config = CandidateRelayConfig {
+            base_config: CandidateBaseConfig {
+                network: UDP.to_owned(),
+                address: raddr.ip().to_string(),
+                port: raddr.port(),
+                component: COMPONENT_RTP,
+                conn: Some(Arc::clone(&relay_conn)),
+                ..CandidateBaseConfig::default()
+            },
+            rel_addr: laddr.ip().to_string(),
+            rel_port: laddr.port(),
+        };
+
+        let candidate: Arc<dyn Candidate + Send + Sync> =
+            match relay_config.new_candidate_relay().await {
+                Ok(candidate) => Arc::new(candidate),
+                Err(err) => {
+                    let _ = relay_conn.close().await;
+                    return Err(err);
+                }
+            };
+
+        if let Err(err) = agent_internal.add_candidate(&candidate).await {
+            if let Err(close_err) = candidate.close().await {
+                log::warn!(
+                    "[{}]: Failed to close candidate: {}",
+                    agent_internal.get_name(),
+                    close_err
+                );
+            }
+            return Err(err);
         }
+
+        Ok(())
+    }
+}
+
+/// The networks host candidates are gathered on for the network types. TCP
+/// candidates need real interfaces to connect from.
+fn networks(network_types: &[NetworkType], net: &Net) -> Vec<&'static str> {
+    let mut networks = vec![];
+    if network_types.iter().any(|typ| typ.is_udp()) {
+        networks.push(UDP);
+    }
+    if network_types.iter().any(|typ| typ.is_tcp()) && !net.is_virtual() {
+        networks.push(TCP);
+    }
+    networks
+}
+
+/// Returns the IP a socket of the given family binds to: the agent's local
+/// IP, or the unspecified address without one. None when the local IP is of
+/// the other family.
+fn bind_ip(local_ip: Option<IpAddr>, ipv4: bool) -> Option<IpAddr> {
+    match local_ip {
+        Some(ip) if ip.is_ipv4() == ipv4 => Some(ip),
+        Some(_) => None,
+        None if ipv4 => Some(Ipv4Addr::UNSPECIFIED.into()),
+        None => Some(Ipv6Addr::UNSPECIFIED.into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::webrtc::stun::message::{Message, Setter, BINDING_SUCCESS};
+    use crate::webrtc::stun::xoraddr::XorMappedAddress;
+    use tokio::net::UdpSocket;
+    use tokio::sync::mpsc;
+
+    async fn gather_srflx(url: &str) -> Result<Vec<Arc<dyn Candidate + Send + Sync>>> {
+        let agent = Agent::new(AgentConfig {
+            urls: vec![Url::parse_url(url)?],
+            network_types: vec![NetworkType::Udp4],
+            candidate_types: vec![CandidateType::ServerReflexive],
+            ..Default::default()
+        })
+        .await?;
+
+        let (done_tx, mut done_rx) = mpsc::channel::<()>(1);
+        let done_tx = Arc::new(Mutex::new(Some(done_tx)));
+        agent
+            .on_candidate(Box::new(move |c| {
+                let done_tx = Arc::clone(&done_tx);
+                Box::pin(async move {
+                    if c.is_none() {
+                        done_tx.lock().await.take();
+                    }
+                })
+            }))
+            .await;
+        agent.gather_candidates().await?;
+        let _ = done_rx.recv().await;
+
+        let candidates = agent.get_local_candidates().await?;
+        agent.close().await?;
+        Ok(candidates)
+    }
+
+    #[tokio::test]
+    async fn test_srflx_candidate_from_xor_mapped_address() -> Result<()> {
+        // a mapping no NAT on loopback would produce, so the candidate can
+        // only have come from the response
+        let mapped = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)), 40000);
+
+        let server = UdpSocket::bind("127.0.0.1:0").await?;
+        let url = format!("stun:{}", server.local_addr()?);
+        let responder = tokio::spawn(async move {
+            let mut buf = vec![0u8; 1500];
+            let (n, from) = server.recv_from(&mut buf).await?;
+
+            let mut req = Message::new();
+            req.raw = buf[..n].to_vec();
+            req.decode()?;
+
+            let mut res = Message::new();
+            res.build(&[Box::new(BINDING_SUCCESS), Box::new(req.transaction_id)])?;
+            XorMappedAddress {
+                ip: mapped.ip(),
+                port: mapped.port(),
+            }
+            .add_to(&mut res)?;
+            server.send_to(&res.raw, from).await?;
+
+            Ok::<SocketAddr, Error>(from)
+        });
+
+        let candidates = gather_srflx(&url).await?;
+        let from = responder.await.unwrap()?;
+
+        assert_eq!(candidates.len(), 1);
+        let candidate = &candidates[0];
+        assert_eq!(candidate.candidate_type(), CandidateType::ServerReflexive);
+        assert_eq!(candidate.address(), mapped.ip().to_string());
+        assert_eq!(candidate.port(), mapped.port());
+        assert_eq!(
+            candidate.related_address().map(|r| r.port),
+            Some(from.port())
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_srflx_gathering_times_out_without_response() -> Result<()> {
+        let server = UdpSocket::bind("127.0.0.1:0").await?;
+        let url = format!("stun:{}", server.local_addr()?);
+
+        let started = Instant::now();
+        let candidates = tokio::time::timeout(
+            STUN_GATHER_TIMEOUT + Duration::from_secs(2),
+            gather_srflx(&url),
+        )
+        .await
+        .expect("gathering did not complete after the STUN timeout")?;
+
+        assert!(candidates.is_empty());
+        assert!(started.elapsed() >= STUN_GATHER_TIMEOUT);
+
+        drop(server);
+        Ok(())
     }
 }
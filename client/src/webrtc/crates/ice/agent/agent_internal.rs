@@ -1,5 +1,6 @@
 use super::agent_transport::*;
 use super::*;
+use crate::webrtc::ice::tcp_type::TcpType;
 use crate::webrtc::ice::util::*;
 use std::sync::atomic::{AtomicBool, AtomicU64};
 
@@ -34,6 +35,7 @@ pub(crate) struct AgentInternal {
     pub(crate) on_selected_candidate_pair_change_hdlr:
         Mutex<Option<OnSelectedCandidatePairChangeHdlrFn>>,
     pub(crate) on_candidate_hdlr: Mutex<Option<OnCandidateHdlrFn>>,
+    pub(crate) on_network_change_hdlr: Mutex<Option<OnNetworkChangeHdlrFn>>,
 
     pub(crate) tie_breaker: AtomicU64,
     pub(crate) is_controlling: AtomicBool,
@@ -41,6 +43,13 @@ pub(crate) struct AgentInternal {
 
     pub(crate) start_time: Mutex<Instant>,
     pub(crate) nominated_pair: Mutex<Option<Arc<CandidatePair>>>,
+    // Until when a valid pair waits for a better pair of the other IP family
+    pub(crate) happy_eyeballs_deadline: Mutex<Option<Instant>>,
+
+    // When the selected pair last answered a binding request, and when consent to send on it is
+    // next checked
+    pub(crate) consent_granted_at: Mutex<Instant>,
+    pub(crate) consent_check_at: Mutex<Instant>,
 
     pub(crate) connection_state: AtomicU8, //ConnectionState,
 
@@ -72,6 +81,9 @@ pub(crate) struct AgentInternal {
     // How often should we send keepalive packets?
     // 0 means never
     pub(crate) keepalive_interval: Duration,
+    // How long the selected pair can go without answering a consent check
+    // before the ICE Agent goes to failed, 0 means never
+    pub(crate) consent_timeout: Duration,
     // How often should we run our internal taskLoop to check for state changes when connecting
     pub(crate) check_interval: Duration,
 }
@@ -104,6 +116,7 @@ impl AgentInternal {
             on_connection_state_change_hdlr: Mutex::new(None),
             on_selected_candidate_pair_change_hdlr: Mutex::new(None),
             on_candidate_hdlr: Mutex::new(None),
+            on_network_change_hdlr: Mutex::new(None),
 
             tie_breaker: AtomicU64::new(rand::random::<u64>()),
             is_controlling: AtomicBool::new(config.is_controlling),
@@ -111,6 +124,10 @@ impl AgentInternal {
 
             start_time: Mutex::new(Instant::now()),
             nominated_pair: Mutex::new(None),
+            happy_eyeballs_deadline: Mutex::new(None),
+
+            consent_granted_at: Mutex::new(Instant::now()),
+            consent_check_at: Mutex::new(Instant::now()),
 
             connection_state: AtomicU8::new(ConnectionState::New as u8),
 
@@ -135,6 +152,10 @@ impl AgentInternal {
             // 0 means never
             keepalive_interval: Duration::from_secs(0),
 
+            // How long the selected pair can go without answering a consent check
+            // before the ICE Agent goes to failed, 0 means never
+            consent_timeout: Duration::from_secs(0),
+
             // How often should we run our internal taskLoop to check for state changes when connecting
             check_interval: Duration::from_secs(0),
 
@@ -239,6 +260,7 @@ impl AgentInternal {
             self.disconnected_timeout,
             self.failed_timeout,
         );
+        let consent_check_interval = self.consent_check_interval();
 
         let done_and_force_candidate_contact_rx = {
             let mut done_and_force_candidate_contact_rx =
@@ -267,6 +289,7 @@ impl AgentInternal {
                         }
                         ConnectionState::Connected | ConnectionState::Disconnected => {
                             update_interval(keepalive_interval);
+                            update_interval(consent_check_interval);
                         }
                         _ => {}
                     };
@@ -333,6 +356,16 @@ impl AgentInternal {
                 *selected_pair = Some(p);
             }
 
+            // The pair was just checked, so consent starts out fresh
+            {
+                let mut consent_granted_at = self.consent_granted_at.lock().await;
+                *consent_granted_at = Instant::now();
+            }
+            self.schedule_consent_check().await;
+            self.agent_conn
+                .consent_expired
+                .store(false, Ordering::SeqCst);
+
             self.update_connection_state(ConnectionState::Connected)
                 .await;
 
@@ -447,6 +480,26 @@ impl AgentInternal {
             )
         };
 
+        // Lite agents send no checks, so there are no responses to refresh consent with
+        if valid
+            && self.consent_timeout != Duration::from_secs(0)
+            && !self.lite.load(Ordering::SeqCst)
+        {
+            let consent_age = {
+                let consent_granted_at = self.consent_granted_at.lock().await;
+                Instant::now().duration_since(*consent_granted_at)
+            };
+            if consent_age > self.consent_timeout {
+                log::warn!(
+                    "[{}]: no consent response for {:?}, consent expired",
+                    self.get_name(),
+                    consent_age
+                );
+                self.expire_consent().await;
+                return valid;
+            }
+        }
+
         if valid {
             // Only allow transitions to failed if a.failedTimeout is non-zero
             let mut total_time_to_failure = self.failed_timeout;
@@ -497,7 +550,17 @@ impl AgentInternal {
                 .duration_since(remote.last_received())
                 .unwrap_or_else(|_| Duration::from_secs(0));
 
-            if (self.keepalive_interval != Duration::from_secs(0))
+            let consent_check_due = self.consent_timeout != Duration::from_secs(0) && {
+                let consent_check_at = self.consent_check_at.lock().await;
+                Instant::now() >= *consent_check_at
+            };
+
+            if consent_check_due {
+                // Consent is refreshed on a schedule even while data flows, as only a binding
+                // success response shows the remote still wants our traffic
+                self.schedule_consent_check().await;
+                self.ping_candidate(&local, &remote).await;
+            } else if (self.keepalive_interval != Duration::from_secs(0))
                 && ((last_sent > self.keepalive_interval)
                     || (last_received > self.keepalive_interval))
             {
@@ -508,6 +571,47 @@ impl AgentInternal {
         }
     }
 
+    /// Returns how often consent is checked, often enough that several checks go unanswered
+    /// before a short consent timeout expires.
+    fn consent_check_interval(&self) -> Duration {
+        DEFAULT_CONSENT_CHECK_INTERVAL.min(self.consent_timeout / 5)
+    }
+
+    /// Sets when consent is next checked, randomized to 0.8 to 1.2 times the check interval.
+    ///
+    /// RFC 7675 Section 5.1
+    async fn schedule_consent_check(&self) {
+        let interval = self
+            .consent_check_interval()
+            .mul_f64(0.8 + 0.4 * rand::random::<f64>());
+        let mut consent_check_at = self.consent_check_at.lock().await;
+        *consent_check_at = Instant::now() + interval;
+    }
+
+    /// Refreshes consent if p is the selected pair, after it answered a binding request.
+    pub(crate) async fn refresh_consent(&self, p: &Arc<CandidatePair>) {
+        let is_selected = self
+            .agent_conn
+            .get_selected_pair()
+            .await
+            .is_some_and(|selected_pair| Arc::ptr_eq(&selected_pair, p));
+        if is_selected {
+            let mut consent_granted_at = self.consent_granted_at.lock().await;
+            *consent_granted_at = Instant::now();
+        }
+    }
+
+    /// Stops sending on the selected pair and fails the connection, after consent expired or the
+    /// remote revoked it.
+    ///
+    /// RFC 7675 Section 5.1
+    pub(crate) async fn expire_consent(&self) {
+        self.agent_conn
+            .consent_expired
+            .store(true, Ordering::SeqCst);
+        self.update_connection_state(ConnectionState::Failed).await;
+    }
+
     fn request_connectivity_check(&self) {
         let _ = self.force_candidate_contact_tx.try_send(true);
     }
@@ -543,7 +647,9 @@ impl AgentInternal {
         }
 
         for cand in local_cands {
-            self.add_pair(cand, c.clone()).await;
+            if can_pair(&*cand, &**c) {
+                self.add_pair(cand, c.clone()).await;
+            }
         }
 
         self.request_connectivity_check();
@@ -596,8 +702,10 @@ impl AgentInternal {
         }
 
         for cand in remote_cands {
-            log::debug!("adding remote {}", c);
-            self.add_pair(c.clone(), cand).await;
+            if can_pair(&**c, &*cand) {
+                log::debug!("adding remote {}", c);
+                self.add_pair(c.clone(), cand).await;
+            }
         }
 
         self.request_connectivity_check();
@@ -820,6 +928,68 @@ impl AgentInternal {
         None
     }
 
+    /// Revokes consent when the selected pair's remote refuses a binding request. A remote that
+    /// forgot the session answers with 401 Unauthorized, one that rejects it with 403 Forbidden.
+    /// Error responses carry no integrity, so only answers to a pending request are trusted.
+    ///
+    /// RFC 7675 Section 5.2
+    async fn handle_error_response(
+        &self,
+        m: &Message,
+        local: &Arc<dyn Candidate + Send + Sync>,
+        remote: SocketAddr,
+    ) {
+        let mut error_code = ErrorCodeAttribute::default();
+        if let Err(err) = error_code.get_from(m) {
+            log::debug!(
+                "[{}]: discard error response from ({}), {}",
+                self.get_name(),
+                remote,
+                err
+            );
+            return;
+        }
+
+        match self.handle_inbound_binding_success(m.transaction_id).await {
+            Some(pending_request) if pending_request.destination == remote => {}
+            _ => {
+                log::debug!(
+                    "[{}]: discard error response from ({}), unknown TransactionID 0x{:?}",
+                    self.get_name(),
+                    remote,
+                    m.transaction_id
+                );
+                return;
+            }
+        }
+
+        let on_selected_pair = if let Some(p) = self.agent_conn.get_selected_pair().await {
+            p.local.equal(&**local) && p.remote.addr().await == remote
+        } else {
+            false
+        };
+
+        if on_selected_pair
+            && (error_code.code == CODE_UNAUTHORIZED || error_code.code == CODE_FORBIDDEN)
+        {
+            log::warn!(
+                "[{}]: consent revoked by ({}), error code {}",
+                self.get_name(),
+                remote,
+                error_code.code.0
+            );
+            self.expire_consent().await;
+        } else {
+            log::debug!(
+                "[{}]: binding error response from ({}) to {}, error code {}",
+                self.get_name(),
+                remote,
+                local,
+                error_code.code.0
+            );
+        }
+    }
+
     /// Processes STUN traffic from a remote candidate.
     pub(crate) async fn handle_inbound(
         &self,
@@ -827,6 +997,11 @@ impl AgentInternal {
         local: &Arc<dyn Candidate + Send + Sync>,
         remote: SocketAddr,
     ) {
+        if m.typ.method == METHOD_BINDING && m.typ.class == CLASS_ERROR_RESPONSE {
+            self.handle_error_response(m, local, remote).await;
+            return;
+        }
+
         if m.typ.method != METHOD_BINDING
             || !(m.typ.class == CLASS_SUCCESS_RESPONSE
                 || m.typ.class == CLASS_REQUEST
@@ -1170,3 +1345,13 @@ impl AgentInternal {
         }
     }
 }
+
+/// Whether a local and a remote candidate of the same network type form a
+/// pair. Over TCP only active local candidates are gathered, which pair
+/// with passive remote candidates.
+///
+/// RFC 6544 Section 6.2
+fn can_pair(local: &(dyn Candidate + Send + Sync), remote: &(dyn Candidate + Send + Sync)) -> bool {
+    !local.network_type().is_tcp()
+        || (local.tcp_type() == TcpType::Active && remote.tcp_type() == TcpType::Passive)
+}
@@ -0,0 +1,253 @@
+use super::*;
+use crate::webrtc::ice::util::*;
+use std::collections::HashSet;
+use std::sync::Weak;
+
+pub(crate) struct NetworkMonitorParams {
+    pub(crate) interval: Duration,
+    pub(crate) net: Arc<Net>,
+    pub(crate) interface_filter: Arc<Option<InterfaceFilterFn>>,
+    pub(crate) ip_filter: Arc<Option<IpFilterFn>>,
+    pub(crate) network_types: Vec<NetworkType>,
+    pub(crate) local_ip: Option<IpAddr>,
+    pub(crate) agent_internal: Weak<AgentInternal>,
+    pub(crate) done_rx: mpsc::Receiver<()>,
+}
+
+impl Agent {
+    /// Polls the local IPs host candidates are gathered on, and fires the network change
+    /// handler when a change calls for moving to the new network with an ICE restart: the IP
+    /// the selected pair sends from went away, or an IP appeared while the agent is
+    /// disconnected or failed. Stops once the agent is closed or dropped.
+    pub(crate) fn start_network_monitor(params: NetworkMonitorParams) {
+        let NetworkMonitorParams {
+            interval,
+            net,
+            interface_filter,
+            ip_filter,
+            network_types,
+            local_ip,
+            agent_internal,
+            mut done_rx,
+        } = params;
+
+        let watched_ips = move || {
+            let net = Arc::clone(&net);
+            let interface_filter = Arc::clone(&interface_filter);
+            let ip_filter = Arc::clone(&ip_filter);
+            let network_types = network_types.clone();
+            async move {
+                let mut ips: HashSet<IpAddr> =
+                    local_interfaces(&net, &interface_filter, &ip_filter, &network_types).await;
+                if let Some(local_ip) = local_ip {
+                    ips.retain(|ip| *ip == local_ip);
+                }
+                ips
+            }
+        };
+
+        tokio::spawn(async move {
+            let mut ips = watched_ips().await;
+            let mut ticker = tokio::time::interval_at(Instant::now() + interval, interval);
+            loop {
+                tokio::select! {
+                    _ = done_rx.recv() => break,
+                    _ = ticker.tick() => {}
+                }
+
+                let ai = match agent_internal.upgrade() {
+                    Some(ai) if ai.done_tx.lock().await.is_some() => ai,
+                    _ => break,
+                };
+
+                let current_ips = watched_ips().await;
+                if current_ips == ips {
+                    continue;
+                }
+                log::info!(
+                    "[{}]: local network changed from {:?} to {:?}",
+                    ai.get_name(),
+                    ips,
+                    current_ips
+                );
+                let lost_selected_ip = match ai.selected_local_ip().await {
+                    Some(ip) => ips.contains(&ip) && !current_ips.contains(&ip),
+                    None => false,
+                };
+                let gained_ip = current_ips.difference(&ips).next().is_some();
+                ips = current_ips;
+
+                let state = ConnectionState::from(ai.connection_state.load(Ordering::SeqCst));
+                let down = matches!(
+                    state,
+                    ConnectionState::Disconnected | ConnectionState::Failed
+                );
+                let restart = lost_selected_ip || (gained_ip && down);
+                if !restart {
+                    continue;
+                }
+
+                let mut on_network_change_hdlr = ai.on_network_change_hdlr.lock().await;
+                if let Some(f) = &mut *on_network_change_hdlr {
+                    f().await;
+                }
+            }
+        });
+    }
+}
+
+impl AgentInternal {
+    /// The local IP the selected pair sends from: a host candidate's address, or the base a
+    /// server reflexive candidate was gathered on. None without a selected pair, or for
+    /// candidates whose base is not known here.
+    async fn selected_local_ip(&self) -> Option<IpAddr> {
+        let pair = self.agent_conn.get_selected_pair().await?;
+        let address = match pair.local.candidate_type() {
+            CandidateType::Host => pair.local.address(),
+            CandidateType::ServerReflexive => pair.local.related_address()?.address,
+            _ => return None,
+        };
+        address.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::webrtc::ice::candidate::candidate_base::CandidateBaseConfig;
+    use crate::webrtc::ice::candidate::candidate_host::CandidateHostConfig;
+    use crate::webrtc::util::vnet::interface::Interface;
+    use std::net::SocketAddr;
+    use std::sync::atomic::AtomicUsize;
+
+    const INTERVAL: Duration = Duration::from_millis(20);
+
+    // an agent monitoring a virtual network, counting the network change handler's calls
+    async fn monitored_agent() -> Result<(Agent, Arc<Net>, Arc<AtomicUsize>)> {
+        let net = Arc::new(Net::new(Some(NetConfig::default())));
+        let agent = Agent::new(AgentConfig {
+            network_types: vec![NetworkType::Udp4],
+            network_monitor_interval: Some(INTERVAL),
+            net: Some(Arc::clone(&net)),
+            ..Default::default()
+        })
+        .await?;
+
+        let changes = Arc::new(AtomicUsize::new(0));
+        let changes_ref = Arc::clone(&changes);
+        agent
+            .on_network_change(Box::new(move || {
+                changes_ref.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async {})
+            }))
+            .await;
+
+        Ok((agent, net, changes))
+    }
+
+    async fn set_eth0_ips(net: &Net, ips: &[&str]) {
+        let vnet = match net {
+            Net::VNet(vnet) => vnet,
+            Net::Ifs => unreachable!("the test network is virtual"),
+        };
+        let mut eth0 = Interface::new("eth0".to_owned(), vec![]);
+        for ip in ips {
+            let mask = SocketAddr::from(([255, 255, 255, 0], 0));
+            let addr = SocketAddr::new(ip.parse().unwrap(), 0);
+            eth0.add_addr(Interface::convert(addr, Some(mask)).unwrap());
+        }
+
+        let mut vnet = vnet.lock().await;
+        for interface in vnet.interfaces.iter_mut() {
+            if interface.name() == "eth0" {
+                *interface = eth0.clone();
+            }
+        }
+    }
+
+    // long enough for several polls
+    async fn settle() {
+        tokio::time::sleep(INTERVAL * 5).await;
+    }
+
+    fn set_state(agent: &Agent, state: ConnectionState) {
+        agent
+            .internal
+            .connection_state
+            .store(state as u8, Ordering::SeqCst);
+    }
+
+    async fn host(address: &str) -> Result<Arc<dyn Candidate + Send + Sync>> {
+        Ok(Arc::new(
+            CandidateHostConfig {
+                base_config: CandidateBaseConfig {
+                    network: "udp4".to_owned(),
+                    address: address.to_owned(),
+                    port: 5000,
+                    component: 1,
+                    ..Default::default()
+                },
+            }
+            .new_candidate_host()
+            .await?,
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_new_ip_fires_only_while_down() -> Result<()> {
+        let (agent, net, changes) = monitored_agent().await?;
+        settle().await;
+
+        set_eth0_ips(&net, &["10.0.0.1"]).await;
+        settle().await;
+        assert_eq!(changes.load(Ordering::SeqCst), 0, "fired while new");
+
+        set_state(&agent, ConnectionState::Disconnected);
+        settle().await;
+        assert_eq!(changes.load(Ordering::SeqCst), 0, "fired without a change");
+
+        set_eth0_ips(&net, &["10.0.0.1", "10.0.0.2"]).await;
+        settle().await;
+        assert_eq!(changes.load(Ordering::SeqCst), 1);
+
+        // an IP going away without a selected pair on it
+        set_eth0_ips(&net, &["10.0.0.1"]).await;
+        settle().await;
+        assert_eq!(changes.load(Ordering::SeqCst), 1);
+
+        set_state(&agent, ConnectionState::Failed);
+        set_eth0_ips(&net, &["10.0.0.1", "10.0.0.3"]).await;
+        settle().await;
+        assert_eq!(changes.load(Ordering::SeqCst), 2);
+
+        agent.close().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_losing_selected_ip_fires() -> Result<()> {
+        let (agent, net, changes) = monitored_agent().await?;
+        set_eth0_ips(&net, &["10.0.0.1", "10.0.0.2"]).await;
+        settle().await;
+
+        set_state(&agent, ConnectionState::Connected);
+        let pair = CandidatePair::new(host("10.0.0.1").await?, host("10.0.0.9").await?, true);
+        *agent.internal.agent_conn.selected_pair.lock().await = Some(Arc::new(pair));
+
+        set_eth0_ips(&net, &["10.0.0.1"]).await;
+        settle().await;
+        assert_eq!(changes.load(Ordering::SeqCst), 0, "fired for an unused IP");
+
+        set_eth0_ips(&net, &["10.0.0.3"]).await;
+        settle().await;
+        assert_eq!(changes.load(Ordering::SeqCst), 1);
+
+        // a new IP while still connected
+        set_eth0_ips(&net, &["10.0.0.3", "10.0.0.4"]).await;
+        settle().await;
+        assert_eq!(changes.load(Ordering::SeqCst), 1);
+
+        agent.close().await?;
+        Ok(())
+    }
+}
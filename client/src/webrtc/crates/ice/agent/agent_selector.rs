@@ -14,6 +14,10 @@ use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use tokio::time::{Duration, Instant};
 
+/// How long a valid pair waits for a higher priority pair of the other IP
+/// family to succeed, the connection attempt delay of RFC 8305.
+const HAPPY_EYEBALLS_DELAY: Duration = Duration::from_millis(250);
+
 #[async_trait]
 trait ControllingSelector {
     async fn start(&self);
@@ -73,6 +77,20 @@ impl AgentInternal {
                     .as_nanos()
                     > self.host_acceptance_min_wait.as_nanos()
             }
+            CandidateType::ServerReflexive => {
+                Instant::now()
+                    .checked_duration_since(*start_time)
+                    .unwrap_or_else(|| Duration::from_secs(0))
+                    .as_nanos()
+                    > self.srflx_acceptance_min_wait.as_nanos()
+            }
+            CandidateType::Relay => {
+                Instant::now()
+                    .checked_duration_since(*start_time)
+                    .unwrap_or_else(|| Duration::from_secs(0))
+                    .as_nanos()
+                    > self.relay_acceptance_min_wait.as_nanos()
+            }
             _ => {
                 log::error!(
                     "is_nominatable invalid candidate type {}",
@@ -83,6 +101,32 @@ impl AgentInternal {
         }
     }
 
+    /// Both IP families are checked at once, so they race. A valid pair is
+    /// held back while a higher priority pair of the other family is still
+    /// being checked, giving the preferred family a head start without
+    /// letting a broken path on it stall the connection.
+    async fn is_held_back(&self, p: &Arc<CandidatePair>) -> bool {
+        let is_ipv6 = p.local.network_type().is_ipv6();
+        let racing = {
+            let checklist = self.agent_conn.checklist.lock().await;
+            checklist.iter().any(|other| {
+                let state = other.state.load(Ordering::SeqCst);
+                (state == CandidatePairState::Waiting as u8
+                    || state == CandidatePairState::InProgress as u8)
+                    && other.local.network_type().is_ipv6() != is_ipv6
+                    && other.priority() > p.priority()
+            })
+        };
+        if !racing {
+            return false;
+        }
+
+        let mut happy_eyeballs_deadline = self.happy_eyeballs_deadline.lock().await;
+        let deadline =
+            *happy_eyeballs_deadline.get_or_insert_with(|| Instant::now() + HAPPY_EYEBALLS_DELAY);
+        Instant::now() < deadline
+    }
+
     async fn nominate_pair(&self) {
         let result = {
             let nominated_pair = self.nominated_pair.lock().await;
@@ -202,6 +246,10 @@ impl ControllingSelector for AgentInternal {
             let mut start_time = self.start_time.lock().await;
             *start_time = Instant::now();
         }
+        {
+            let mut happy_eyeballs_deadline = self.happy_eyeballs_deadline.lock().await;
+            *happy_eyeballs_deadline = None;
+        }
     }
 
     async fn contact_candidates(&self) {
@@ -226,7 +274,9 @@ impl ControllingSelector for AgentInternal {
         } else {
             let has_nominated_pair =
                 if let Some(p) = self.agent_conn.get_best_valid_candidate_pair().await {
-                    self.is_nominatable(&p.local).await && self.is_nominatable(&p.remote).await
+                    self.is_nominatable(&p.local).await
+                        && self.is_nominatable(&p.remote).await
+                        && !self.is_held_back(&p).await
                 } else {
                     false
                 };
@@ -309,6 +359,8 @@ impl ControllingSelector for AgentInternal {
             if let Some(p) = self.find_pair(local, remote).await {
                 p.state
                     .store(CandidatePairState::Succeeded as u8, Ordering::SeqCst);
+                p.update_round_trip_time(Instant::now().duration_since(pending_request.timestamp));
+                self.refresh_consent(&p).await;
                 log::trace!(
                     "Found valid candidate pair: {}, p.state: {}, isUseCandidate: {}, {}",
                     p,
@@ -466,6 +518,8 @@ impl ControlledSelector for AgentInternal {
             if let Some(p) = self.find_pair(local, remote).await {
                 p.state
                     .store(CandidatePairState::Succeeded as u8, Ordering::SeqCst);
+                p.update_round_trip_time(Instant::now().duration_since(pending_request.timestamp));
+                self.refresh_consent(&p).await;
                 log::trace!("Found valid candidate pair: {}", p);
             } else {
                 // This shouldn't happen
@@ -522,3 +576,106 @@ impl ControlledSelector for AgentInternal {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::webrtc::ice::agent::agent_config::AgentConfig;
+    use crate::webrtc::ice::agent::Agent;
+    use crate::webrtc::ice::candidate::candidate_base::CandidateBaseConfig;
+    use crate::webrtc::ice::candidate::candidate_host::CandidateHostConfig;
+    use crate::webrtc::ice::error::Result;
+    use crate::webrtc::ice::network_type::NetworkType;
+
+    async fn host(address: &str, priority: u32) -> Result<Arc<dyn Candidate + Send + Sync>> {
+        let network = if address.contains(':') {
+            "udp6"
+        } else {
+            "udp4"
+        };
+        Ok(Arc::new(
+            CandidateHostConfig {
+                base_config: CandidateBaseConfig {
+                    network: network.to_owned(),
+                    address: address.to_owned(),
+                    port: 5000,
+                    component: 1,
+                    priority,
+                    ..Default::default()
+                },
+            }
+            .new_candidate_host()
+            .await?,
+        ))
+    }
+
+    async fn pair(
+        agent: &Agent,
+        local: &str,
+        remote: &str,
+        priority: u32,
+    ) -> Result<Arc<CandidatePair>> {
+        let (local, remote) = (host(local, priority).await?, host(remote, priority).await?);
+        agent
+            .internal
+            .add_pair(Arc::clone(&local), Arc::clone(&remote))
+            .await;
+        Ok(agent
+            .internal
+            .find_pair(&local, &remote)
+            .await
+            .expect("pair was added"))
+    }
+
+    #[tokio::test]
+    async fn test_lower_priority_pair_is_held_back_by_other_family() -> Result<()> {
+        let agent = Agent::new(AgentConfig {
+            network_types: vec![NetworkType::Udp4, NetworkType::Udp6],
+            ..Default::default()
+        })
+        .await?;
+        let ipv6 = pair(&agent, "2001:db8::1", "2001:db8::2", 200).await?;
+        let ipv4 = pair(&agent, "192.0.2.1", "192.0.2.2", 100).await?;
+
+        // the higher priority pair is never held back
+        assert!(!agent.internal.is_held_back(&ipv6).await);
+
+        ipv6.state
+            .store(CandidatePairState::InProgress as u8, Ordering::SeqCst);
+        let started = Instant::now();
+        assert!(agent.internal.is_held_back(&ipv4).await);
+
+        while agent.internal.is_held_back(&ipv4).await {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let held = started.elapsed();
+        assert!(
+            held >= HAPPY_EYEBALLS_DELAY
+                && held <= HAPPY_EYEBALLS_DELAY + Duration::from_millis(100),
+            "held {:?}",
+            held
+        );
+
+        agent.close().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pair_is_released_when_other_family_fails() -> Result<()> {
+        let agent = Agent::new(AgentConfig {
+            network_types: vec![NetworkType::Udp4, NetworkType::Udp6],
+            ..Default::default()
+        })
+        .await?;
+        let ipv6 = pair(&agent, "2001:db8::1", "2001:db8::2", 200).await?;
+        let ipv4 = pair(&agent, "192.0.2.1", "192.0.2.2", 100).await?;
+        assert!(agent.internal.is_held_back(&ipv4).await);
+
+        ipv6.state
+            .store(CandidatePairState::Failed as u8, Ordering::SeqCst);
+        assert!(!agent.internal.is_held_back(&ipv4).await);
+
+        agent.close().await?;
+        Ok(())
+    }
+}
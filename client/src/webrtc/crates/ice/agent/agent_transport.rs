@@ -87,6 +87,7 @@ pub(crate) struct AgentConn {
     pub(crate) bytes_received: AtomicUsize,
     pub(crate) bytes_sent: AtomicUsize,
     pub(crate) done: AtomicBool,
+    pub(crate) consent_expired: AtomicBool,
 }
 
 impl AgentConn {
@@ -101,6 +102,7 @@ impl AgentConn {
             bytes_received: AtomicUsize::new(0),
             bytes_sent: AtomicUsize::new(0),
             done: AtomicBool::new(false),
+            consent_expired: AtomicBool::new(false),
         }
     }
     pub(crate) async fn get_selected_pair(&self) -> Option<Arc<CandidatePair>> {
@@ -184,6 +186,13 @@ impl Conn for AgentConn {
             return Err(io::Error::new(io::ErrorKind::Other, "Conn is closed").into());
         }
 
+        // RFC 7675 Section 5.1, nothing more is sent once consent expired
+        if self.consent_expired.load(Ordering::SeqCst) {
+            return Err(crate::webrtc::util::Error::Other(
+                "ErrIceConsentExpired".into(),
+            ));
+        }
+
         if is_message(buf) {
             return Err(crate::webrtc::util::Error::Other(
                 "ErrIceWriteStunMessage".into(),
@@ -1,6 +1,7 @@
 pub(crate) mod agent_config;
 pub(crate) mod agent_gather;
 pub(crate) mod agent_internal;
+pub(crate) mod agent_network_monitor;
 pub(crate) mod agent_selector;
 pub(crate) mod agent_transport;
 
@@ -10,15 +11,19 @@ use crate::webrtc::ice::external_ip_mapper::*;
 use crate::webrtc::ice::mdns::*;
 use crate::webrtc::ice::network_type::*;
 use crate::webrtc::ice::state::*;
+use crate::webrtc::ice::udp_mux::UdpMux;
+use crate::webrtc::ice::url::*;
+use crate::webrtc::mdns::conn::DnsConn;
 use agent_config::*;
 use agent_internal::*;
+use agent_network_monitor::NetworkMonitorParams;
 
 use crate::webrtc::stun::{
-    agent::*, attributes::*, fingerprint::*, integrity::*, message::*, xoraddr::*,
+    agent::*, attributes::*, error_code::*, fingerprint::*, integrity::*, message::*, xoraddr::*,
 };
 use crate::webrtc::util::{vnet::net::*, Buffer};
 use std::collections::HashMap;
-use std::net::{Ipv4Addr, SocketAddr};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
 use crate::webrtc::ice::agent::agent_gather::GatherCandidatesInternalParams;
 use crate::webrtc::ice::rand::*;
@@ -69,6 +74,8 @@ pub(crate) type OnCandidateHdlrFn = Box<
         + Send
         + Sync,
 >;
+pub(crate) type OnNetworkChangeHdlrFn =
+    Box<dyn (FnMut() -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>) + Send + Sync>;
 pub(crate) type GatherCandidateCancelFn = Box<dyn Fn() + Send + Sync>;
 
 pub(crate) struct ChanReceivers {
@@ -82,8 +89,14 @@ pub(crate) struct Agent {
     pub(crate) internal: Arc<AgentInternal>,
 
     pub(crate) interface_filter: Arc<Option<InterfaceFilterFn>>,
+    pub(crate) ip_filter: Arc<Option<IpFilterFn>>,
+    pub(crate) port_min: u16,
+    pub(crate) port_max: u16,
+    pub(crate) local_ip: Option<IpAddr>,
+    pub(crate) udp_mux: Option<Arc<UdpMux>>,
     pub(crate) mdns_mode: MulticastDnsMode,
     pub(crate) mdns_name: String,
+    pub(crate) mdns_conn: Option<Arc<DnsConn>>,
     pub(crate) net: Arc<Net>,
 
     // 1:1 D-NAT IP address mapping
@@ -91,8 +104,12 @@ pub(crate) struct Agent {
     pub(crate) gathering_state: Arc<AtomicU8>, //GatheringState,
     pub(crate) candidate_types: Vec<CandidateType>,
     pub(crate) network_types: Vec<NetworkType>,
+    pub(crate) urls: Vec<Url>,
 
     pub(crate) gather_candidate_cancel: Option<GatherCandidateCancelFn>,
+
+    // never sent on, dropping the agent stops the network monitor
+    _network_monitor_done_tx: Option<mpsc::Sender<()>>,
 }
 
 impl Agent {
@@ -107,6 +124,10 @@ impl Agent {
             return Err(Error::ErrInvalidMulticastDnshostName);
         }
 
+        if config.port_max != 0 && config.port_max < config.port_min {
+            return Err(Error::ErrPort);
+        }
+
         let mut mdns_mode = config.multicast_dns_mode;
         if mdns_mode == MulticastDnsMode::Unspecified {
             mdns_mode = MulticastDnsMode::QueryOnly;
@@ -133,7 +154,10 @@ impl Agent {
             return Err(Error::ErrLiteUsingNonHostCandidates);
         }
 
-        if !config.urls.is_empty() {
+        if !config.urls.is_empty()
+            && !candidate_types.contains(&CandidateType::ServerReflexive)
+            && !candidate_types.contains(&CandidateType::Relay)
+        {
             return Err(Error::ErrUselessUrlsProvided);
         }
 
@@ -157,20 +181,64 @@ impl Agent {
             Arc::new(Net::new(None))
         };
 
+        // mDNS is opportunistic: without it only .local candidates are lost.
+        let mdns_conn = if net.is_virtual() {
+            None
+        } else {
+            match create_multicast_dns(mdns_mode, &mdns_name) {
+                Ok(c) => c,
+                Err(err) => {
+                    log::warn!("Failed to initialize multicast DNS: {}", err);
+                    None
+                }
+            }
+        };
+
+        let network_monitor_interval = config.network_monitor_interval.unwrap_or_default();
+        let (network_monitor_done_tx, network_monitor_done_rx) =
+            if network_monitor_interval != Duration::from_secs(0) {
+                let (done_tx, done_rx) = mpsc::channel(1);
+                (Some(done_tx), Some(done_rx))
+            } else {
+                (None, None)
+            };
+
         let agent = Self {
             internal: Arc::new(ai),
             interface_filter: Arc::clone(&config.interface_filter),
+            ip_filter: Arc::clone(&config.ip_filter),
+            port_min: config.port_min,
+            port_max: config.port_max,
+            local_ip: config.local_ip,
+            udp_mux: config.udp_mux.clone(),
             mdns_mode,
             mdns_name,
+            mdns_conn,
             net,
             ext_ip_mapper: Arc::new(ext_ip_mapper),
             gathering_state: Arc::new(AtomicU8::new(0)), //GatheringState::New,
             candidate_types,
             network_types: config.network_types.clone(),
+            urls: config.urls.clone(),
 
             gather_candidate_cancel: None, //TODO: add cancel
+
+            _network_monitor_done_tx: network_monitor_done_tx,
         };
 
+        if let Some(done_rx) = network_monitor_done_rx {
+            Agent::start_network_monitor(NetworkMonitorParams {
+                interval: network_monitor_interval,
+                net: Arc::clone(&agent.net),
+                interface_filter: Arc::clone(&agent.interface_filter),
+                ip_filter: Arc::clone(&agent.ip_filter),
+                network_types: agent.network_types.clone(),
+                local_ip: agent.local_ip,
+                agent_internal: Arc::downgrade(&agent.internal),
+                done_rx,
+            });
+        }
+
         agent
             .internal
             .start_on_connection_state_change_routine(
@@ -209,6 +277,13 @@ impl Agent {
         *on_selected_candidate_pair_change_hdlr = Some(f);
     }
 
+    /// Sets a handler that is fired when the local IPs that host candidates are gathered on
+    /// change, as when moving from one network to another.
+    pub(crate) async fn on_network_change(&self, f: OnNetworkChangeHdlrFn) {
+        let mut on_network_change_hdlr = self.internal.on_network_change_hdlr.lock().await;
+        *on_network_change_hdlr = Some(f);
+    }
+
     /// Sets a handler that is fired when new candidates gathered. When the gathering process
     /// complete the last candidate is nil.
     pub(crate) async fn on_candidate(&self, f: OnCandidateHdlrFn) {
@@ -231,10 +306,58 @@ impl Agent {
                 return Ok(());
             }
 
-            if c.candidate_type() != CandidateType::Host {
-                return Err(Error::ErrAddressParseFailed);
-            }
+            let mdns_conn = match &self.mdns_conn {
+                Some(mdns_conn) => Arc::clone(mdns_conn),
+                None => {
+                    log::warn!(
+                        "remote mDNS candidate added, but mDNS is unavailable: ({})",
+                        c.address()
+                    );
+                    return Ok(());
+                }
+            };
+
+            let ai = Arc::clone(&self.internal);
+            let candidate = Arc::clone(c);
+            tokio::spawn(async move {
+                let address = candidate.address();
+                let ip = match tokio::time::timeout(
+                    MULTICAST_DNS_QUERY_TIMEOUT,
+                    mdns_conn.query(&address),
+                )
+                .await
+                {
+                    Ok(Ok(ip)) => ip,
+                    Ok(Err(err)) => {
+                        log::warn!("Failed to resolve mDNS candidate {}: {}", address, err);
+                        return;
+                    }
+                    Err(_) => {
+                        log::warn!("Timed out resolving mDNS candidate {}", address);
+                        return;
+                    }
+                };
+
+                if let Err(err) = candidate.set_ip(&ip).await {
+                    log::warn!("Failed to set ip for mDNS candidate {}: {}", address, err);
+                    return;
+                }
+
+                ai.add_remote_candidate(&candidate).await;
+            });
         } else {
+            // the mux routes packets by remote address, so two agents on it
+            // cannot both reach the same remote candidate
+            if let Some(udp_mux) = &self.udp_mux {
+                if c.network_type().is_udp() {
+                    let (local_ufrag, _) = self.get_local_user_credentials().await;
+                    let addr = c.addr().await;
+                    if udp_mux.register_address(&local_ufrag, addr).await.is_err() {
+                        return Err(Error::ErrUdpMuxAddressInUse(addr));
+                    }
+                }
+            }
+
             let ai = Arc::clone(&self.internal);
             let candidate = Arc::clone(c);
             tokio::spawn(async move {
@@ -269,12 +392,54 @@ impl Agent {
         (ufrag_pwd.local_ufrag.clone(), ufrag_pwd.local_pwd.clone())
     }
 
+    /// Sets the credentials of the remote agent, as after a restart.
+    pub(crate) async fn set_remote_credentials(
+        &self,
+        remote_ufrag: String,
+        remote_pwd: String,
+    ) -> Result<()> {
+        self.internal
+            .set_remote_credentials(remote_ufrag, remote_pwd)
+            .await
+    }
+
+    /// Returns the selected candidate pair, if one has been nominated.
+    pub(crate) async fn get_selected_candidate_pair(&self) -> Option<Arc<CandidatePair>> {
+        self.internal.agent_conn.get_selected_pair().await
+    }
+
+    /// Returns whether consent to send on the selected pair expired or was revoked, which fails
+    /// the connection.
+    pub(crate) fn consent_expired(&self) -> bool {
+        self.internal
+            .agent_conn
+            .consent_expired
+            .load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of bytes sent over the selected candidate pair.
+    pub(crate) fn bytes_sent(&self) -> usize {
+        self.internal.agent_conn.bytes_sent.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of bytes received over the selected candidate pair.
+    pub(crate) fn bytes_received(&self) -> usize {
+        self.internal
+            .agent_conn
+            .bytes_received
+            .load(Ordering::SeqCst)
+    }
+
     /// Cleans up the Agent.
     pub(crate) async fn close(&self) -> Result<()> {
         if let Some(gather_candidate_cancel) = &self.gather_candidate_cancel {
             gather_candidate_cancel();
         }
 
+        if let Some(mdns_conn) = &self.mdns_conn {
+            let _ = mdns_conn.close().await;
+        }
+
         //FIXME: deadlock here
         self.internal.close().await
     }
@@ -369,10 +534,16 @@ impl Agent {
         let params = GatherCandidatesInternalParams {
             candidate_types: self.candidate_types.clone(),
             network_types: self.network_types.clone(),
+            urls: self.urls.clone(),
             mdns_mode: self.mdns_mode,
             mdns_name: self.mdns_name.clone(),
             net: Arc::clone(&self.net),
             interface_filter: self.interface_filter.clone(),
+            ip_filter: self.ip_filter.clone(),
+            port_min: self.port_min,
+            port_max: self.port_max,
+            local_ip: self.local_ip,
+            udp_mux: self.udp_mux.clone(),
             ext_ip_mapper: Arc::clone(&self.ext_ip_mapper),
             agent_internal: Arc::clone(&self.internal),
             gathering_state: Arc::clone(&self.gathering_state),
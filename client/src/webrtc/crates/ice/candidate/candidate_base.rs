@@ -1,6 +1,9 @@
 use super::*;
 use crate::webrtc::ice::candidate::candidate_host::CandidateHostConfig;
+use crate::webrtc::ice::candidate::candidate_relay::CandidateRelayConfig;
+use crate::webrtc::ice::candidate::candidate_server_reflexive::CandidateServerReflexiveConfig;
 use crate::webrtc::ice::error::*;
+use crate::webrtc::ice::tcp_type::*;
 use crate::webrtc::ice::util::*;
 
 use async_trait::async_trait;
@@ -22,12 +25,15 @@ pub(crate) struct CandidateBaseConfig {
     pub(crate) priority: u32,
     pub(crate) foundation: String,
     pub(crate) conn: Option<Arc<dyn crate::webrtc::util::Conn + Send + Sync>>,
+    pub(crate) tcp_type: TcpType,
+    pub(crate) address_rank: u16,
 }
 
 pub(crate) struct CandidateBase {
     pub(crate) id: String,
     pub(crate) network_type: AtomicU8,
     pub(crate) candidate_type: CandidateType,
+    pub(crate) tcp_type: TcpType,
 
     pub(crate) component: AtomicU16,
     pub(crate) address: String,
@@ -44,6 +50,9 @@ pub(crate) struct CandidateBase {
 
     pub(crate) foundation_override: String,
     pub(crate) priority_override: u32,
+    // where the IP comes in the interleaved order of local IPs, 0 for the
+    // most preferred, lowering the local preference by as much
+    pub(crate) address_rank: u16,
 
     //CandidateHost
     pub(crate) network: String,
@@ -55,6 +64,7 @@ impl Default for CandidateBase {
             id: String::new(),
             network_type: AtomicU8::new(0),
             candidate_type: CandidateType::default(),
+            tcp_type: TcpType::default(),
 
             component: AtomicU16::new(0),
             address: String::new(),
@@ -71,6 +81,7 @@ impl Default for CandidateBase {
 
             foundation_override: String::new(),
             priority_override: 0,
+            address_rank: 0,
             network: String::new(),
         }
     }
@@ -187,6 +198,11 @@ impl Candidate for CandidateBase {
         self.candidate_type
     }
 
+    /// Returns candidate TCP type.
+    fn tcp_type(&self) -> TcpType {
+        self.tcp_type
+    }
+
     /// Returns the string representation of the ICECandidate.
     fn marshal(&self) -> String {
         let mut val = format!(
@@ -208,6 +224,10 @@ impl Candidate for CandidateBase {
             .as_str();
         }
 
+        if self.tcp_type != TcpType::Unspecified {
+            val += format!(" tcptype {}", self.tcp_type()).as_str();
+        }
+
         val
     }
 
@@ -260,6 +280,7 @@ impl Candidate for CandidateBase {
     fn equal(&self, other: &dyn Candidate) -> bool {
         self.network_type() == other.network_type()
             && self.candidate_type() == other.candidate_type()
+            && self.tcp_type() == other.tcp_type()
             && self.address() == other.address()
             && self.port() == other.port()
             && self.related_address() == other.related_address()
@@ -298,9 +319,34 @@ impl CandidateBase {
         self.last_sent.store(d.as_nanos() as u64, Ordering::SeqCst);
     }
 
-    /// Returns the local preference for this candidate.
+    /// Returns the local preference for this candidate. TCP candidates are
+    /// ranked by the direction of their connections, below UDP candidates,
+    /// and host candidates by their address rank.
+    ///
+    /// RFC 6544 Section 4.2, RFC 8421 Section 4
     pub(crate) fn local_preference(&self) -> u16 {
-        DEFAULT_LOCAL_PREFERENCE
+        if !self.network_type().is_tcp() {
+            return DEFAULT_LOCAL_PREFERENCE.saturating_sub(self.address_rank);
+        }
+
+        let other_pref: u16 = 8191_u16.saturating_sub(self.address_rank);
+        let direction_pref: u16 = match self.candidate_type() {
+            CandidateType::Host | CandidateType::Relay => match self.tcp_type() {
+                TcpType::Active => 6,
+                TcpType::Passive => 4,
+                TcpType::SimultaneousOpen => 2,
+                TcpType::Unspecified => 0,
+            },
+            CandidateType::ServerReflexive => match self.tcp_type() {
+                TcpType::SimultaneousOpen => 6,
+                TcpType::Active => 4,
+                TcpType::Passive => 2,
+                TcpType::Unspecified => 0,
+            },
+            CandidateType::Unspecified => 0,
+        };
+
+        (1 << 13) * direction_pref + other_pref
     }
 }
 
@@ -335,8 +381,12 @@ pub(crate) async fn unmarshal_candidate(raw: &str) -> Result<impl Candidate> {
 
     let typ = split[7];
 
+    let mut rel_addr = String::new();
+    let mut rel_port = 0;
+    let mut tcp_type = TcpType::Unspecified;
+
     if split.len() > 8 {
-        let split2 = &split[8..];
+        let mut split2 = &split[8..];
 
         if split2[0] == "raddr" {
             if split2.len() < 4 {
@@ -345,6 +395,23 @@ pub(crate) async fn unmarshal_candidate(raw: &str) -> Result<impl Candidate> {
                     Error::ErrParseRelatedAddr
                 )));
             }
+
+            // RelatedAddress
+            rel_addr = split2[1].to_owned();
+
+            // RelatedPort
+            rel_port = split2[3].parse()?;
+
+            split2 = &split2[4..];
+        }
+
+        // the remaining extensions are name and value pairs
+        for pair in split2.chunks(2) {
+            if let [name, value] = pair {
+                if *name == "tcptype" {
+                    tcp_type = TcpType::from(*value);
+                }
+            }
         }
     }
 
@@ -358,11 +425,46 @@ pub(crate) async fn unmarshal_candidate(raw: &str) -> Result<impl Candidate> {
                     component,
                     priority,
                     foundation,
+                    tcp_type,
                     ..CandidateBaseConfig::default()
                 },
             };
             config.new_candidate_host().await
         }
+        "srflx" => {
+            let config = CandidateServerReflexiveConfig {
+                base_config: CandidateBaseConfig {
+                    network,
+                    address,
+                    port,
+                    component,
+                    priority,
+                    foundation,
+                    tcp_type,
+                    ..CandidateBaseConfig::default()
+                },
+                rel_addr,
+                rel_port,
+            };
+            config.new_candidate_server_reflexive().await
+        }
+        "relay" => {
+            let config = CandidateRelayConfig {
+                base_config: CandidateBaseConfig {
+                    network,
+                    address,
+                    port,
+                    component,
+                    priority,
+                    foundation,
+                    tcp_type,
+                    ..CandidateBaseConfig::default()
+                },
+                rel_addr,
+                rel_port,
+            };
+            config.new_candidate_relay().await
+        }
         _ => Err(Error::Other(format!(
             "{:?} ({})",
             Error::ErrUnknownCandidateType,
@@ -0,0 +1,50 @@
+use super::candidate_base::*;
+use super::*;
+use crate::webrtc::ice::rand::generate_cand_id;
+
+use std::sync::atomic::{AtomicU16, AtomicU8};
+
+/// The config required to create a new `CandidateRelay`.
+#[derive(Default)]
+pub(crate) struct CandidateRelayConfig {
+    pub(crate) base_config: CandidateBaseConfig,
+
+    pub(crate) rel_addr: String,
+    pub(crate) rel_port: u16,
+}
+
+impl CandidateRelayConfig {
+    /// Creates a new relay candidate. Its conn, if any, sends and receives
+    /// through the TURN allocation, and closing it releases the allocation.
+    pub(crate) async fn new_candidate_relay(self) -> Result<CandidateBase> {
+        let ip: IpAddr = self.base_config.address.parse()?;
+
+        let mut candidate_id = self.base_config.candidate_id;
+        if candidate_id.is_empty() {
+            candidate_id = generate_cand_id();
+        }
+
+        let c = CandidateBase {
+            id: candidate_id,
+            address: self.base_config.address,
+            candidate_type: CandidateType::Relay,
+            component: AtomicU16::new(self.base_config.component),
+            port: self.base_config.port,
+            foundation_override: self.base_config.foundation,
+            priority_override: self.base_config.priority,
+            network: self.base_config.network,
+            tcp_type: self.base_config.tcp_type,
+            network_type: AtomicU8::new(NetworkType::Udp4 as u8),
+            related_address: Some(CandidateRelatedAddress {
+                address: self.rel_addr,
+                port: self.rel_port,
+            }),
+            conn: self.base_config.conn,
+            ..CandidateBase::default()
+        };
+
+        c.set_ip(&ip).await?;
+
+        Ok(c)
+    }
+}
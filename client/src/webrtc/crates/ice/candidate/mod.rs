@@ -1,16 +1,19 @@
 pub(crate) mod candidate_base;
 pub(crate) mod candidate_host;
+pub(crate) mod candidate_relay;
+pub(crate) mod candidate_server_reflexive;
 
 use crate::webrtc::ice::error::Result;
 use crate::webrtc::ice::network_type::*;
+use crate::webrtc::ice::tcp_type::*;
 use candidate_base::*;
 
 use async_trait::async_trait;
 use std::fmt;
 use std::net::{IpAddr, SocketAddr};
-use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU8, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU64, AtomicU8, Ordering};
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 use tokio::sync::{broadcast, Mutex};
 
 pub(crate) const RECEIVE_MTU: usize = 8192;
@@ -55,6 +58,10 @@ pub(crate) trait Candidate: fmt::Display {
 
     fn candidate_type(&self) -> CandidateType;
 
+    /// The direction of the candidate's TCP connections, Unspecified for
+    /// UDP candidates.
+    fn tcp_type(&self) -> TcpType;
+
     fn marshal(&self) -> String;
 
     async fn addr(&self) -> SocketAddr;
@@ -74,6 +81,8 @@ pub(crate) trait Candidate: fmt::Display {
 pub(crate) enum CandidateType {
     Unspecified,
     Host,
+    ServerReflexive,
+    Relay,
 }
 
 // String makes CandidateType printable
@@ -81,6 +90,8 @@ impl fmt::Display for CandidateType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = match *self {
             CandidateType::Host => "host",
+            CandidateType::ServerReflexive => "srflx",
+            CandidateType::Relay => "relay",
             CandidateType::Unspecified => "Unknown candidate type",
         };
         write!(f, "{}", s)
@@ -104,7 +115,8 @@ impl CandidateType {
     pub(crate) const fn preference(self) -> u16 {
         match self {
             Self::Host => 126,
-            CandidateType::Unspecified => 0,
+            Self::ServerReflexive => 100,
+            CandidateType::Relay | CandidateType::Unspecified => 0,
         }
     }
 }
@@ -182,6 +194,7 @@ pub(crate) struct CandidatePair {
     pub(crate) binding_request_count: AtomicU16,
     pub(crate) state: AtomicU8, // convert it to CandidatePairState,
     pub(crate) nominated: AtomicBool,
+    pub(crate) current_round_trip_time: AtomicU64, // in nanoseconds, 0 until measured
 }
 
 impl Default for CandidatePair {
@@ -193,6 +206,7 @@ impl Default for CandidatePair {
             state: AtomicU8::new(CandidatePairState::Waiting as u8),
             binding_request_count: AtomicU16::new(0),
             nominated: AtomicBool::new(false),
+            current_round_trip_time: AtomicU64::new(0),
         }
     }
 }
@@ -245,6 +259,23 @@ impl CandidatePair {
             state: AtomicU8::new(CandidatePairState::Waiting as u8),
             binding_request_count: AtomicU16::new(0),
             nominated: AtomicBool::new(false),
+            current_round_trip_time: AtomicU64::new(0),
+        }
+    }
+
+    /// update_round_trip_time records the round trip time of the latest
+    /// successful connectivity check on the pair.
+    pub(crate) fn update_round_trip_time(&self, rtt: Duration) {
+        self.current_round_trip_time
+            .store(rtt.as_nanos() as u64, Ordering::SeqCst);
+    }
+
+    /// current_round_trip_time returns the latest measured round trip time,
+    /// if the pair has had a successful connectivity check.
+    pub(crate) fn current_round_trip_time(&self) -> Option<Duration> {
+        match self.current_round_trip_time.load(Ordering::SeqCst) {
+            0 => None,
+            nanos => Some(Duration::from_nanos(nanos)),
         }
     }
 
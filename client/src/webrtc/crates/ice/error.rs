@@ -48,10 +48,6 @@ pub(crate) enum Error {
     #[error("attempting to gather candidates during gathering state")]
     ErrMultipleGatherAttempted,
 
-    /// Indicates we were unable to parse a candidate address.
-    #[error("failed to parse address")]
-    ErrAddressParseFailed,
-
     /// Indicates that non host candidates were selected for a lite agent.
     #[error("lite agents must only use host candidates")]
     ErrLiteUsingNonHostCandidates,
@@ -90,6 +86,55 @@ pub(crate) enum Error {
     #[error("ICE Agent can not be restarted when gathering")]
     ErrRestartWhenGathering,
 
+    /// Indicates another agent on the UDP mux already sends to the remote
+    /// address, so packets from it cannot be routed to this agent.
+    #[error("remote address {0} is used by another agent on the UDP mux")]
+    ErrUdpMuxAddressInUse(net::SocketAddr),
+
+    /// Indicates an error with an unknown scheme type.
+    #[error("unknown scheme type")]
+    ErrSchemeType,
+
+    /// Indicates query arguments are provided in a STUN URL.
+    #[error("queries not supported in stun address")]
+    ErrStunQuery,
+
+    /// Indicates an malformed query is provided.
+    #[error("invalid query")]
+    ErrInvalidQuery,
+
+    /// Indicates malformed hostname is provided.
+    #[error("invalid hostname")]
+    ErrHost,
+
+    /// Indicates the local port range is empty, or no port in it could be bound.
+    #[error("no port available in the local port range")]
+    ErrPort,
+
+    /// Indicates an unsupported transport type was provided.
+    #[error("invalid transport protocol type")]
+    ErrProtoType,
+
+    /// Indicates a URL that is not of the form scheme:host[:port][?query].
+    #[error("invalid url")]
+    ErrInvalidUrl,
+
+    /// Indicates a STUN server's host resolved to no address of the requested family.
+    #[error("no address of the requested family for the STUN server")]
+    ErrNoStunServerAddress,
+
+    /// Indicates the STUN server did not answer a binding request in time.
+    #[error("timed out waiting for the STUN server's binding response")]
+    ErrStunTimeout,
+
+    /// Indicates a TURN URL was provided without a username or credential.
+    #[error("TURN server URL is missing its username or credential")]
+    ErrTurnCredentialsMissing,
+
+    /// Indicates the TURN server did not grant an allocation in time.
+    #[error("timed out waiting for the TURN server's allocation")]
+    ErrTurnTimeout,
+
     #[error("attribute not long enough to be ICE candidate")]
     ErrAttributeTooShortIceCandidate,
     #[error("could not parse related addresses")]
@@ -112,6 +157,10 @@ pub(crate) enum Error {
     #[error("{0}")]
     Stun(#[from] crate::webrtc::stun::Error),
     #[error("{0}")]
+    Turn(#[from] crate::webrtc::turn::Error),
+    #[error("{0}")]
+    Mdns(#[from] crate::webrtc::mdns::Error),
+    #[error("{0}")]
     ParseUrl(#[from] url::ParseError),
 
     #[error("{0}")]
@@ -1,5 +1,17 @@
+use crate::webrtc::ice::error::Result;
+use crate::webrtc::mdns::config::Config;
+use crate::webrtc::mdns::conn::DnsConn;
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
+/// How long a remote mDNS candidate is queried for before it is dropped.
+pub(crate) const MULTICAST_DNS_QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+const MULTICAST_DNS_PORT: u16 = 5353;
+
 /// Represents the different Multicast modes that ICE can run.
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub(crate) enum MulticastDnsMode {
@@ -27,3 +39,106 @@ pub(crate) fn generate_multicast_dns_name() -> String {
     let u = Uuid::new_v4();
     format!("{}.local", u)
 }
+
+/// Starts the mDNS querier, and the responder for mdns_name when gathering
+/// with mDNS. Returns None when mDNS is disabled.
+pub(crate) fn create_multicast_dns(
+    mdns_mode: MulticastDnsMode,
+    mdns_name: &str,
+) -> Result<Option<Arc<DnsConn>>> {
+    let local_names = match mdns_mode {
+        MulticastDnsMode::QueryOnly => vec![],
+        MulticastDnsMode::QueryAndGather => vec![mdns_name.to_owned()],
+        _ => return Ok(None),
+    };
+
+    let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), MULTICAST_DNS_PORT);
+    let conn = DnsConn::server(
+        addr,
+        Config {
+            local_names,
+            ..Default::default()
+        },
+    )?;
+
+    Ok(Some(Arc::new(conn)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::webrtc::ice::agent::agent_config::AgentConfig;
+    use crate::webrtc::ice::agent::Agent;
+    use crate::webrtc::ice::candidate::Candidate;
+    use crate::webrtc::ice::network_type::NetworkType;
+    use tokio::net::UdpSocket;
+    use tokio::sync::{mpsc, Mutex};
+
+    async fn new_agent(multicast_dns_mode: MulticastDnsMode) -> Result<Agent> {
+        Agent::new(AgentConfig {
+            network_types: vec![NetworkType::Udp4],
+            multicast_dns_mode,
+            ..Default::default()
+        })
+        .await
+    }
+
+    async fn gather(agent: &Agent) -> Result<Vec<Arc<dyn Candidate + Send + Sync>>> {
+        let (done_tx, mut done_rx) = mpsc::channel::<()>(1);
+        let done_tx = Arc::new(Mutex::new(Some(done_tx)));
+        agent
+            .on_candidate(Box::new(move |c| {
+                let done_tx = Arc::clone(&done_tx);
+                Box::pin(async move {
+                    if c.is_none() {
+                        done_tx.lock().await.take();
+                    }
+                })
+            }))
+            .await;
+        agent.gather_candidates().await?;
+        let _ = done_rx.recv().await;
+        agent.get_local_candidates().await
+    }
+
+    #[tokio::test]
+    async fn test_remote_mdns_candidate_is_resolved() -> Result<()> {
+        let publisher = new_agent(MulticastDnsMode::QueryAndGather).await?;
+        let resolver = new_agent(MulticastDnsMode::QueryOnly).await?;
+
+        let published = gather(&publisher).await?;
+        let candidate = published
+            .first()
+            .expect("no host candidate gathered")
+            .clone();
+        assert_eq!(candidate.address(), publisher.mdns_name);
+
+        // the responder answers with the address the OS reaches the mDNS
+        // group from
+        let socket = UdpSocket::bind("0.0.0.0:0").await?;
+        socket.connect("224.0.0.251:5353").await?;
+        let expected = SocketAddr::new(socket.local_addr()?.ip(), candidate.port());
+
+        resolver.add_remote_candidate(&candidate).await?;
+
+        let resolved = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let remote_candidates = resolver.internal.remote_candidates.lock().await;
+                for c in remote_candidates.values().flatten() {
+                    if c.address() == publisher.mdns_name {
+                        return c.addr().await;
+                    }
+                }
+                drop(remote_candidates);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("mDNS candidate not resolved");
+        assert_eq!(resolved, expected);
+
+        resolver.close().await?;
+        publisher.close().await?;
+        Ok(())
+    }
+}
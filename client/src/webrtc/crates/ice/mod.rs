@@ -1,3 +1,4 @@
+pub(crate) mod active_tcp;
 pub(crate) mod agent;
 pub(crate) mod candidate;
 pub(crate) mod control;
@@ -8,6 +9,8 @@ pub(crate) mod network_type;
 pub(crate) mod priority;
 pub(crate) mod rand;
 pub(crate) mod state;
+pub(crate) mod tcp_type;
+pub(crate) mod udp_mux;
 pub(crate) mod url;
 pub(crate) mod use_candidate;
 mod util;
@@ -4,6 +4,7 @@ use std::fmt;
 use std::net::IpAddr;
 
 pub(crate) const UDP: &str = "udp";
+pub(crate) const TCP: &str = "tcp";
 
 #[must_use]
 pub(crate) fn supported_network_types() -> Vec<NetworkType> {
@@ -20,6 +21,12 @@ pub(crate) enum NetworkType {
 
     /// Indicates UDP over IPv6.
     Udp6,
+
+    /// Indicates TCP over IPv4.
+    Tcp4,
+
+    /// Indicates TCP over IPv6.
+    Tcp6,
 }
 
 impl From<u8> for NetworkType {
@@ -27,6 +34,8 @@ impl From<u8> for NetworkType {
         match v {
             1 => Self::Udp4,
             2 => Self::Udp6,
+            3 => Self::Tcp4,
+            4 => Self::Tcp6,
             _ => Self::Unspecified,
         }
     }
@@ -37,6 +46,8 @@ impl fmt::Display for NetworkType {
         let s = match *self {
             Self::Udp4 => "udp4",
             Self::Udp6 => "udp6",
+            Self::Tcp4 => "tcp4",
+            Self::Tcp6 => "tcp6",
             Self::Unspecified => "unspecified",
         };
         write!(f, "{}", s)
@@ -50,11 +61,35 @@ impl Default for NetworkType {
 }
 
 impl NetworkType {
+    /// Returns true when network is UDP4 or UDP6.
+    #[must_use]
+    pub(crate) fn is_udp(self) -> bool {
+        self == Self::Udp4 || self == Self::Udp6
+    }
+
+    /// Returns true when network is TCP4 or TCP6.
+    #[must_use]
+    pub(crate) fn is_tcp(self) -> bool {
+        self == Self::Tcp4 || self == Self::Tcp6
+    }
+
+    /// Returns the TCP network over the same IP family, or None when network
+    /// is not UDP.
+    #[must_use]
+    pub(crate) fn tcp_counterpart(self) -> Option<Self> {
+        match self {
+            Self::Udp4 => Some(Self::Tcp4),
+            Self::Udp6 => Some(Self::Tcp6),
+            _ => None,
+        }
+    }
+
     /// Returns the short network description.
     #[must_use]
     pub(crate) fn network_short(self) -> String {
         match self {
             Self::Udp4 | Self::Udp6 => UDP.to_owned(),
+            Self::Tcp4 | Self::Tcp6 => TCP.to_owned(),
             Self::Unspecified => "Unspecified".to_owned(),
         }
     }
@@ -63,8 +98,8 @@ impl NetworkType {
     #[must_use]
     pub(crate) const fn is_ipv4(self) -> bool {
         match self {
-            Self::Udp4 => true,
-            Self::Udp6 | Self::Unspecified => false,
+            Self::Udp4 | Self::Tcp4 => true,
+            Self::Udp6 | Self::Tcp6 | Self::Unspecified => false,
         }
     }
 
@@ -72,8 +107,8 @@ impl NetworkType {
     #[must_use]
     pub(crate) const fn is_ipv6(self) -> bool {
         match self {
-            Self::Udp6 => true,
-            Self::Udp4 | Self::Unspecified => false,
+            Self::Udp6 | Self::Tcp6 => true,
+            Self::Udp4 | Self::Tcp4 | Self::Unspecified => false,
         }
     }
 }
@@ -88,6 +123,12 @@ pub(crate) fn determine_network_type(network: &str, ip: &IpAddr) -> Result<Netwo
         } else {
             Ok(NetworkType::Udp6)
         }
+    } else if net.starts_with(TCP) {
+        if ipv4 {
+            Ok(NetworkType::Tcp4)
+        } else {
+            Ok(NetworkType::Tcp6)
+        }
     } else {
         Err(Error::ErrDetermineNetworkType)
     }
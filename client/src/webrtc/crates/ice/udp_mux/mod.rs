@@ -0,0 +1,472 @@
+use crate::webrtc::stun::{attributes::ATTR_USERNAME, message::*, textattrs::Username};
+use crate::webrtc::util::{Conn, Error as ConnError, IoError};
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use tokio::sync::{mpsc, Mutex};
+
+type ConnResult<T> = std::result::Result<T, ConnError>;
+
+/// The number of packets queued for an agent that has not read them yet,
+/// further packets are dropped.
+const PACKET_QUEUE_SIZE: usize = 128;
+
+/// The largest datagram read from the shared socket.
+const RECEIVE_MTU: usize = 8192;
+
+type Packet = (Vec<u8>, SocketAddr);
+
+/// UdpMux shares one UDP socket between many ICE agents. Each agent gets a
+/// conn for its local ufrag, and packets are routed to it by the remote
+/// address it sent to, or by the ufrag a STUN request from a new remote
+/// address is for.
+///
+/// A remote address belongs to the first agent that sends to it, so agents
+/// sharing a mux must reach distinct remote addresses.
+///
+/// It does not build on `mux::Mux`: a `MatchFunc` only sees the packet bytes,
+/// and an `Endpoint` reads and writes through a connected conn without
+/// `recv_from`/`send_to`, while the agents need the remote address of every
+/// packet and route by it.
+pub(crate) struct UdpMux {
+    conn: Arc<dyn Conn + Send + Sync>,
+    local_addr: SocketAddr,
+    routes: Mutex<Routes>,
+    next_conn_id: AtomicU64,
+    // never sent on, dropping the mux ends the read loop
+    _closed_ch_tx: mpsc::Sender<()>,
+}
+
+#[derive(Default)]
+struct Routes {
+    // the id tells a replaced conn from the one that replaced it
+    conns: HashMap<String, (u64, mpsc::Sender<Packet>)>,
+    addresses: HashMap<SocketAddr, String>,
+    // the read loop has ended, conns get no more packets
+    closed: bool,
+}
+
+impl UdpMux {
+    /// Starts demultiplexing packets read from conn, bound to local_addr.
+    pub(crate) fn new(conn: Arc<dyn Conn + Send + Sync>, local_addr: SocketAddr) -> Arc<Self> {
+        let (closed_ch_tx, closed_ch_rx) = mpsc::channel(1);
+        let mux = Arc::new(UdpMux {
+            conn,
+            local_addr,
+            routes: Mutex::new(Routes::default()),
+            next_conn_id: AtomicU64::new(0),
+            _closed_ch_tx: closed_ch_tx,
+        });
+
+        let conn = Arc::clone(&mux.conn);
+        let weak = Arc::downgrade(&mux);
+        tokio::spawn(async move {
+            UdpMux::read_loop(conn, weak, closed_ch_rx).await;
+        });
+
+        mux
+    }
+
+    pub(crate) fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Returns the conn for the agent with the local ufrag, replacing any
+    /// earlier conn for it.
+    pub(crate) async fn get_conn(self: &Arc<Self>, ufrag: &str) -> Arc<dyn Conn + Send + Sync> {
+        let id = self.next_conn_id.fetch_add(1, Ordering::SeqCst);
+        let (packets_tx, packets_rx) = mpsc::channel(PACKET_QUEUE_SIZE);
+        {
+            let mut routes = self.routes.lock().await;
+            // forget agents dropped without closing their conn
+            routes.conns.retain(|_, (_, tx)| !tx.is_closed());
+            let Routes {
+                conns,
+                addresses,
+                closed,
+            } = &mut *routes;
+            addresses.retain(|_, u| u != ufrag && conns.contains_key(u));
+            // once the socket failed, dropping the sender makes the conn
+            // report itself closed
+            if !*closed {
+                conns.insert(ufrag.to_owned(), (id, packets_tx));
+            }
+        }
+
+        Arc::new(UdpMuxConn {
+            id,
+            ufrag: ufrag.to_owned(),
+            mux: Arc::downgrade(self),
+            conn: Arc::clone(&self.conn),
+            local_addr: self.local_addr,
+            packets_rx: Mutex::new(packets_rx),
+        })
+    }
+
+    async fn read_loop(
+        conn: Arc<dyn Conn + Send + Sync>,
+        mux: Weak<UdpMux>,
+        mut closed_ch_rx: mpsc::Receiver<()>,
+    ) {
+        let mut buf = vec![0u8; RECEIVE_MTU];
+        loop {
+            let (n, src) = tokio::select! {
+                _ = closed_ch_rx.recv() => break,
+                result = conn.recv_from(&mut buf) => match result {
+                    Ok(r) => r,
+                    Err(err) if is_transient(&err) => {
+                        log::trace!("udp mux: failed to read: {}", err);
+                        continue;
+                    }
+                    Err(err) => {
+                        log::warn!("udp mux: ending read loop: {}", err);
+                        break;
+                    }
+                },
+            };
+
+            match mux.upgrade() {
+                Some(mux) => mux.dispatch(&buf[..n], src).await,
+                None => break,
+            }
+        }
+
+        if let Some(mux) = mux.upgrade() {
+            mux.close_conns().await;
+        }
+    }
+
+    /// Drops the packet senders of every conn, ending their pending and
+    /// future recv_from calls.
+    async fn close_conns(&self) {
+        let mut routes = self.routes.lock().await;
+        routes.closed = true;
+        routes.conns.clear();
+        routes.addresses.clear();
+    }
+
+    async fn dispatch(&self, buf: &[u8], src: SocketAddr) {
+        let mut routes = self.routes.lock().await;
+
+        let ufrag = match routes.addresses.get(&src) {
+            Some(ufrag) => ufrag.clone(),
+            None => match stun_request_ufrag(buf) {
+                Some(ufrag) if routes.conns.contains_key(&ufrag) => {
+                    routes.addresses.insert(src, ufrag.clone());
+                    ufrag
+                }
+                _ => {
+                    log::trace!("udp mux: no conn for packet from {}", src);
+                    return;
+                }
+            },
+        };
+
+        if let Some((_, packets_tx)) = routes.conns.get(&ufrag) {
+            if packets_tx.try_send((buf.to_vec(), src)).is_err() {
+                log::trace!("udp mux: dropped packet from {} for {}", src, ufrag);
+            }
+        }
+    }
+
+    /// Makes packets from addr go to the conn for ufrag, unless another live
+    /// conn already receives them.
+    pub(crate) async fn register_address(&self, ufrag: &str, addr: SocketAddr) -> ConnResult<()> {
+        let mut routes = self.routes.lock().await;
+        match routes.addresses.get(&addr) {
+            Some(owner) if owner == ufrag => Ok(()),
+            Some(owner)
+                if routes
+                    .conns
+                    .get(owner)
+                    .is_some_and(|(_, tx)| !tx.is_closed()) =>
+            {
+                Err(ConnError::ErrAddressAlreadyInUse)
+            }
+            _ => {
+                routes.addresses.insert(addr, ufrag.to_owned());
+                Ok(())
+            }
+        }
+    }
+
+    async fn remove_conn(&self, id: u64, ufrag: &str) {
+        let mut routes = self.routes.lock().await;
+        if routes.conns.get(ufrag).is_some_and(|(i, _)| *i == id) {
+            routes.conns.remove(ufrag);
+            routes.addresses.retain(|_, u| u != ufrag);
+        }
+    }
+}
+
+impl fmt::Debug for UdpMux {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UdpMux")
+            .field("local_addr", &self.local_addr)
+            .finish()
+    }
+}
+
+/// Whether a read error leaves the socket usable: ICMP errors for earlier
+/// sends are reported on the next read on some platforms.
+fn is_transient(err: &ConnError) -> bool {
+    match err {
+        ConnError::Io(IoError(err)) => matches!(
+            err.kind(),
+            io::ErrorKind::ConnectionRefused
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::Interrupted
+                | io::ErrorKind::WouldBlock
+                | io::ErrorKind::TimedOut
+        ),
+        _ => false,
+    }
+}
+
+/// Returns the local ufrag a STUN binding request is for, the first half of
+/// its USERNAME.
+fn stun_request_ufrag(buf: &[u8]) -> Option<String> {
+    if !is_message(buf) {
+        return None;
+    }
+
+    let mut m = Message {
+        raw: buf.to_vec(),
+        ..Message::default()
+    };
+    m.decode().ok()?;
+    if m.typ != BINDING_REQUEST {
+        return None;
+    }
+
+    let mut username = Username::new(ATTR_USERNAME, String::new());
+    username.get_from(&m).ok()?;
+    let username = username.to_string();
+    username.split(':').next().map(str::to_owned)
+}
+
+/// UdpMuxConn is the conn of one agent on a UdpMux.
+struct UdpMuxConn {
+    id: u64,
+    ufrag: String,
+    mux: Weak<UdpMux>,
+    conn: Arc<dyn Conn + Send + Sync>,
+    local_addr: SocketAddr,
+    packets_rx: Mutex<mpsc::Receiver<Packet>>,
+}
+
+#[async_trait]
+impl Conn for UdpMuxConn {
+    async fn recv(&self, buf: &mut [u8]) -> ConnResult<usize> {
+        let (n, _) = self.recv_from(buf).await?;
+        Ok(n)
+    }
+
+    async fn recv_from(&self, buf: &mut [u8]) -> ConnResult<(usize, SocketAddr)> {
+        let mut packets_rx = self.packets_rx.lock().await;
+        match packets_rx.recv().await {
+            Some((packet, src)) => {
+                if packet.len() > buf.len() {
+                    return Err(ConnError::ErrBufferShort);
+                }
+                buf[..packet.len()].copy_from_slice(&packet);
+                Ok((packet.len(), src))
+            }
+            None => Err(ConnError::ErrUseClosedNetworkConn),
+        }
+    }
+
+    async fn send(&self, _buf: &[u8]) -> ConnResult<usize> {
+        Err(ConnError::ErrNoRemAddr)
+    }
+
+    async fn send_to(&self, buf: &[u8], target: SocketAddr) -> ConnResult<usize> {
+        match self.mux.upgrade() {
+            Some(mux) => mux.register_address(&self.ufrag, target).await?,
+            None => return Err(ConnError::ErrUseClosedNetworkConn),
+        }
+        self.conn.send_to(buf, target).await
+    }
+
+    async fn local_addr(&self) -> ConnResult<SocketAddr> {
+        Ok(self.local_addr)
+    }
+
+    async fn remote_addr(&self) -> Option<SocketAddr> {
+        None
+    }
+
+    async fn close(&self) -> ConnResult<()> {
+        // dropping the sender ends a pending recv_from
+        if let Some(mux) = self.mux.upgrade() {
+            mux.remove_conn(self.id, &self.ufrag).await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::webrtc::stun::agent::TransactionId;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+    use tokio::net::UdpSocket;
+
+    /// FailingConn fails every read, after `transient` transient failures.
+    struct FailingConn {
+        transient: usize,
+        reads: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Conn for FailingConn {
+        async fn recv(&self, _buf: &mut [u8]) -> ConnResult<usize> {
+            Err(ConnError::ErrNoRemAddr)
+        }
+
+        async fn recv_from(&self, _buf: &mut [u8]) -> ConnResult<(usize, SocketAddr)> {
+            // let the test take its conn before the socket fails
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            let reads = self.reads.fetch_add(1, Ordering::SeqCst);
+            let kind = if reads < self.transient {
+                io::ErrorKind::ConnectionReset
+            } else {
+                io::ErrorKind::NotConnected
+            };
+            Err(io::Error::from(kind).into())
+        }
+
+        async fn send(&self, _buf: &[u8]) -> ConnResult<usize> {
+            Err(ConnError::ErrNoRemAddr)
+        }
+
+        async fn send_to(&self, buf: &[u8], _target: SocketAddr) -> ConnResult<usize> {
+            Ok(buf.len())
+        }
+
+        async fn local_addr(&self) -> ConnResult<SocketAddr> {
+            Ok(SocketAddr::from(([127, 0, 0, 1], 0)))
+        }
+
+        async fn remote_addr(&self) -> Option<SocketAddr> {
+            None
+        }
+
+        async fn close(&self) -> ConnResult<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_error_closes_conns() -> ConnResult<()> {
+        let conn = Arc::new(FailingConn {
+            transient: 3,
+            reads: AtomicUsize::new(0),
+        });
+        let mux = UdpMux::new(
+            Arc::clone(&conn) as Arc<dyn Conn + Send + Sync>,
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+        );
+        let agent_conn = mux.get_conn("ufrag").await;
+
+        let mut buf = vec![0u8; RECEIVE_MTU];
+        let result = tokio::time::timeout(Duration::from_secs(5), agent_conn.recv_from(&mut buf))
+            .await
+            .expect("recv_from should end when the socket fails");
+        assert_eq!(result.unwrap_err(), ConnError::ErrUseClosedNetworkConn);
+        // the transient errors were read past, the loop stopped at the first other one
+        assert_eq!(conn.reads.load(Ordering::SeqCst), 4);
+
+        let late_conn = mux.get_conn("late").await;
+        assert_eq!(
+            late_conn.recv_from(&mut buf).await.unwrap_err(),
+            ConnError::ErrUseClosedNetworkConn
+        );
+
+        Ok(())
+    }
+
+    fn binding_request(username: &str) -> Vec<u8> {
+        let mut m = Message::new();
+        m.build(&[
+            Box::new(BINDING_REQUEST),
+            Box::new(TransactionId::new()),
+            Box::new(Username::new(ATTR_USERNAME, username.to_owned())),
+        ])
+        .unwrap();
+        m.raw
+    }
+
+    async fn recv(conn: &Arc<dyn Conn + Send + Sync>) -> ConnResult<(Vec<u8>, SocketAddr)> {
+        let mut buf = vec![0u8; RECEIVE_MTU];
+        let (n, src) = tokio::time::timeout(Duration::from_secs(5), conn.recv_from(&mut buf))
+            .await
+            .expect("no packet routed to the conn")?;
+        buf.truncate(n);
+        Ok((buf, src))
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_ufrag_then_address() -> ConnResult<()> {
+        let socket = UdpSocket::bind("127.0.0.1:0").await?;
+        let local_addr = socket.local_addr()?;
+        let mux = UdpMux::new(Arc::new(socket), local_addr);
+        let conn_a = mux.get_conn("ufragA").await;
+        let conn_b = mux.get_conn("ufragB").await;
+
+        let remote_a = UdpSocket::bind("127.0.0.1:0").await?;
+        let remote_b = UdpSocket::bind("127.0.0.1:0").await?;
+
+        // a STUN request from a new address goes to the conn its USERNAME names
+        let request = binding_request("ufragA:remote");
+        remote_a.send_to(&request, local_addr).await?;
+        assert_eq!(recv(&conn_a).await?, (request, remote_a.local_addr()?));
+
+        let request = binding_request("ufragB:remote");
+        remote_b.send_to(&request, local_addr).await?;
+        assert_eq!(recv(&conn_b).await?, (request, remote_b.local_addr()?));
+
+        // afterwards any packet from that address goes to the same conn
+        remote_a.send_to(b"dtls", local_addr).await?;
+        assert_eq!(
+            recv(&conn_a).await?,
+            (b"dtls".to_vec(), remote_a.local_addr()?)
+        );
+        remote_b.send_to(b"sctp", local_addr).await?;
+        assert_eq!(
+            recv(&conn_b).await?,
+            (b"sctp".to_vec(), remote_b.local_addr()?)
+        );
+
+        // a remote address belongs to one conn
+        conn_a.send_to(b"to a", remote_a.local_addr()?).await?;
+        assert_eq!(
+            conn_b
+                .send_to(b"to a", remote_a.local_addr()?)
+                .await
+                .unwrap_err(),
+            ConnError::ErrAddressAlreadyInUse
+        );
+        assert_eq!(
+            mux.register_address("ufragB", remote_a.local_addr()?).await,
+            Err(ConnError::ErrAddressAlreadyInUse)
+        );
+
+        // until its conn closes
+        conn_a.close().await?;
+        conn_b.send_to(b"to a", remote_a.local_addr()?).await?;
+        remote_a.send_to(b"now b", local_addr).await?;
+        assert_eq!(
+            recv(&conn_b).await?,
+            (b"now b".to_vec(), remote_a.local_addr()?)
+        );
+
+        Ok(())
+    }
+}
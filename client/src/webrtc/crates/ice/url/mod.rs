@@ -1,3 +1,6 @@
+use crate::webrtc::ice::error::*;
+
+use std::borrow::Cow;
 use std::convert::From;
 use std::fmt;
 
@@ -102,6 +105,8 @@ pub(crate) struct Url {
     pub(crate) scheme: SchemeType,
     pub(crate) host: String,
     pub(crate) port: u16,
+    pub(crate) username: String,
+    pub(crate) password: String,
     pub(crate) proto: ProtoType,
 }
 
@@ -123,3 +128,87 @@ impl fmt::Display for Url {
         }
     }
 }
+
+impl Url {
+    /// Parses a STUN or TURN urls following the ABNF syntax described in
+    /// [IETF rfc-7064](https://tools.ietf.org/html/rfc7064) and
+    /// [IETF rfc-7065](https://tools.ietf.org/html/rfc7065) respectively.
+    pub(crate) fn parse_url(raw: &str) -> Result<Self> {
+        // work around for url crate
+        if raw.contains("//") {
+            return Err(Error::ErrInvalidUrl);
+        }
+
+        let mut s = raw.to_string();
+        match raw.find(':') {
+            Some(p) => s.replace_range(p..=p, "://"),
+            None => return Err(Error::ErrSchemeType),
+        }
+
+        let raw_parts = url::Url::parse(&s)?;
+
+        let scheme = raw_parts.scheme().into();
+
+        let host = match raw_parts.host_str() {
+            Some(host) => host
+                .trim()
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_owned(),
+            None => return Err(Error::ErrHost),
+        };
+
+        let port = match raw_parts.port() {
+            Some(port) => port,
+            None if scheme == SchemeType::Stun || scheme == SchemeType::Turn => 3478,
+            None => 5349,
+        };
+
+        let mut q_args = raw_parts.query_pairs();
+        let proto = match scheme {
+            SchemeType::Stun => {
+                if q_args.count() > 0 {
+                    return Err(Error::ErrStunQuery);
+                }
+                ProtoType::Udp
+            }
+            SchemeType::Stuns => {
+                if q_args.count() > 0 {
+                    return Err(Error::ErrStunQuery);
+                }
+                ProtoType::Tcp
+            }
+            SchemeType::Turn | SchemeType::Turns => {
+                let default_proto = if scheme == SchemeType::Turn {
+                    ProtoType::Udp
+                } else {
+                    ProtoType::Tcp
+                };
+                if q_args.count() > 1 {
+                    return Err(Error::ErrInvalidQuery);
+                }
+                match q_args.next() {
+                    Some((Cow::Borrowed("transport"), value)) => {
+                        let proto: ProtoType = value.as_ref().into();
+                        if proto == ProtoType::Unknown {
+                            return Err(Error::ErrProtoType);
+                        }
+                        proto
+                    }
+                    Some(_) => return Err(Error::ErrInvalidQuery),
+                    None => default_proto,
+                }
+            }
+            SchemeType::Unknown => return Err(Error::ErrSchemeType),
+        };
+
+        Ok(Self {
+            scheme,
+            host,
+            port,
+            username: String::new(),
+            password: String::new(),
+            proto,
+        })
+    }
+}
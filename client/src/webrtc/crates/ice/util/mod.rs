@@ -1,12 +1,23 @@
-use crate::webrtc::ice::agent::agent_config::InterfaceFilterFn;
+use crate::webrtc::ice::agent::agent_config::{InterfaceFilterFn, IpFilterFn};
 use crate::webrtc::ice::error::*;
 use crate::webrtc::ice::network_type::*;
 
-use crate::webrtc::stun::{attributes::*, integrity::*, message::*, textattrs::*};
+use crate::webrtc::stun::{
+    agent::*, attributes::*, integrity::*, message::*, textattrs::*, xoraddr::*,
+};
 use crate::webrtc::util::{vnet::net::*, Conn};
+use rand::Rng;
 use std::collections::HashSet;
-use std::net::{IpAddr, SocketAddr};
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{timeout_at, Instant};
+
+/// The largest STUN response read while gathering.
+const MAX_MESSAGE_SIZE: usize = 1280;
+
+/// How often an unanswered binding request is sent again.
+const STUN_RETRANSMIT_INTERVAL: Duration = Duration::from_millis(500);
 
 pub(crate) fn create_addr(_network: NetworkType, ip: IpAddr, port: u16) -> SocketAddr {
     /*if network.is_tcp(){
@@ -41,6 +52,7 @@ pub(crate) fn assert_inbound_message_integrity(m: &mut Message, key: &[u8]) -> R
 pub(crate) async fn local_interfaces(
     vnet: &Arc<Net>,
     interface_filter: &Option<InterfaceFilterFn>,
+    ip_filter: &Option<IpFilterFn>,
     network_types: &[NetworkType],
 ) -> HashSet<IpAddr> {
     let mut ips = HashSet::new();
@@ -65,7 +77,15 @@ pub(crate) async fn local_interfaces(
 
         for ipnet in iface.addrs() {
             let ipaddr = ipnet.addr();
-            if ipv4requested && ipaddr.is_ipv4() || ipv6requested && ipaddr.is_ipv6() {
+            if let Some(filter) = ip_filter {
+                if !filter(ipaddr) {
+                    continue;
+                }
+            }
+
+            if ipv4requested && ipaddr.is_ipv4()
+                || ipv6requested && matches!(ipaddr, IpAddr::V6(ip) if is_supported_ipv6(&ip))
+            {
                 ips.insert(ipaddr);
             }
         }
@@ -74,9 +94,208 @@ pub(crate) async fn local_interfaces(
     ips
 }
 
+/// Link-local addresses need a scope to be reached, and the IPv4-compatible
+/// and site-local ranges are deprecated, so none of them are gathered.
+fn is_supported_ipv6(ip: &Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    !(segments[..6] == [0; 6] && !ip.is_loopback()
+        || segments[0] & 0xffc0 == 0xfe80
+        || segments[0] & 0xffc0 == 0xfec0)
+}
+
+/// How much an IP is preferred within its family, after RFC 6724's default
+/// policy table, with loopback last since no remote agent can reach it.
+fn ip_precedence(ip: IpAddr) -> u8 {
+    match ip {
+        _ if ip.is_loopback() => 0,
+        IpAddr::V6(ip) if ip.segments()[0] & 0xfe00 == 0xfc00 => 3,
+        IpAddr::V6(_) => 40,
+        IpAddr::V4(ip) if ip.is_link_local() => 1,
+        IpAddr::V4(_) => 35,
+    }
+}
+
+/// Orders local IPs as RFC 8421 section 4 asks: by precedence within each
+/// family, alternating between the families starting with the one holding
+/// the most preferred IP, so checks on one family never all wait behind the
+/// other's.
+pub(crate) fn interleave_ip_families(ips: impl IntoIterator<Item = IpAddr>) -> Vec<IpAddr> {
+    let by_precedence =
+        |a: &IpAddr, b: &IpAddr| ip_precedence(*b).cmp(&ip_precedence(*a)).then(a.cmp(b));
+    let (mut ipv6, mut ipv4): (Vec<IpAddr>, Vec<IpAddr>) =
+        ips.into_iter().partition(IpAddr::is_ipv6);
+    ipv6.sort_by(by_precedence);
+    ipv4.sort_by(by_precedence);
+
+    let ipv6_first = match (ipv6.first(), ipv4.first()) {
+        (Some(v6), Some(v4)) => ip_precedence(*v6) >= ip_precedence(*v4),
+        _ => true,
+    };
+    let (first, second) = if ipv6_first {
+        (ipv6, ipv4)
+    } else {
+        (ipv4, ipv6)
+    };
+
+    let mut ordered = Vec::with_capacity(first.len() + second.len());
+    let (mut first, mut second) = (first.into_iter(), second.into_iter());
+    loop {
+        match (first.next(), second.next()) {
+            (None, None) => break,
+            (a, b) => ordered.extend(a.into_iter().chain(b)),
+        }
+    }
+    ordered
+}
+
+/// Binds a UDP conn to laddr, on a port between port_min and port_max unless
+/// laddr has a port. Ports are tried in turn from a random one in the range.
 pub(crate) async fn listen_udp_in_port_range(
     vnet: &Arc<Net>,
+    port_max: u16,
+    port_min: u16,
     laddr: SocketAddr,
 ) -> Result<Arc<dyn Conn + Send + Sync>> {
-    return Ok(vnet.bind(laddr).await?);
+    if laddr.port() != 0 || (port_min == 0 && port_max == 0) {
+        return Ok(vnet.bind(laddr).await?);
+    }
+
+    let i = port_min.max(1);
+    let j = if port_max == 0 { u16::MAX } else { port_max };
+    if i > j {
+        return Err(Error::ErrPort);
+    }
+
+    let port_start = rand::thread_rng().gen_range(i..=j);
+    let mut port_current = port_start;
+    loop {
+        let laddr = SocketAddr::new(laddr.ip(), port_current);
+        match vnet.bind(laddr).await {
+            Ok(c) => return Ok(c),
+            Err(err) => log::debug!("failed to listen {}: {}", laddr, err),
+        }
+
+        port_current = if port_current == j {
+            i
+        } else {
+            port_current + 1
+        };
+        if port_current == port_start {
+            return Err(Error::ErrPort);
+        }
+    }
+}
+
+/// Resolves a `host:port` address to its first address of the requested family.
+pub(crate) async fn resolve_addr(use_ipv4: bool, host_port: &str) -> Result<SocketAddr> {
+    tokio::net::lookup_host(host_port)
+        .await?
+        .find(|addr| addr.is_ipv4() == use_ipv4)
+        .ok_or(Error::ErrNoStunServerAddress)
+}
+
+/// Asks a STUN server which address it sees the conn's packets coming from.
+pub(crate) async fn get_xormapped_addr(
+    conn: &Arc<dyn Conn + Send + Sync>,
+    server_addr: SocketAddr,
+    deadline: Duration,
+) -> Result<XorMappedAddress> {
+    let resp = stun_request(conn, server_addr, deadline).await?;
+    let mut addr = XorMappedAddress::default();
+    addr.get_from(&resp)?;
+    Ok(addr)
+}
+
+/// Sends a binding request to the server until a matching success response
+/// arrives or the deadline passes.
+pub(crate) async fn stun_request(
+    conn: &Arc<dyn Conn + Send + Sync>,
+    server_addr: SocketAddr,
+    deadline: Duration,
+) -> Result<Message> {
+    let mut request = Message::new();
+    request.build(&[Box::new(BINDING_REQUEST), Box::new(TransactionId::new())])?;
+
+    let deadline = Instant::now() + deadline;
+    let mut bs = vec![0_u8; MAX_MESSAGE_SIZE];
+    loop {
+        conn.send_to(&request.raw, server_addr).await?;
+
+        let retransmit_at = (Instant::now() + STUN_RETRANSMIT_INTERVAL).min(deadline);
+        while let Ok(result) = timeout_at(retransmit_at, conn.recv_from(&mut bs)).await {
+            let (n, _) = result?;
+
+            let mut res = Message::new();
+            res.raw = bs[..n].to_vec();
+            if res.decode().is_err() || res.transaction_id != request.transaction_id {
+                continue;
+            }
+            if res.typ != BINDING_SUCCESS {
+                return Err(Error::Other(format!(
+                    "unexpected STUN response: {}",
+                    res.typ
+                )));
+            }
+            return Ok(res);
+        }
+
+        if Instant::now() >= deadline {
+            return Err(Error::ErrStunTimeout);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_interleave_ip_families() {
+        let ordered = interleave_ip_families([
+            ip("127.0.0.1"),
+            ip("::1"),
+            ip("192.0.2.1"),
+            ip("2001:db8::2"),
+            ip("2001:db8::1"),
+            ip("198.51.100.1"),
+        ]);
+        assert_eq!(
+            ordered,
+            vec![
+                ip("2001:db8::1"),
+                ip("192.0.2.1"),
+                ip("2001:db8::2"),
+                ip("198.51.100.1"),
+                ip("::1"),
+                ip("127.0.0.1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interleave_ip_families_starts_with_preferred_family() {
+        // a unique local IPv6 is less preferred than a global IPv4
+        let ordered = interleave_ip_families([ip("fd00::1"), ip("192.0.2.1"), ip("::1")]);
+        assert_eq!(ordered, vec![ip("192.0.2.1"), ip("fd00::1"), ip("::1")]);
+    }
+
+    #[test]
+    fn test_is_supported_ipv6() {
+        for (addr, supported) in [
+            ("2001:db8::1", true),
+            ("fd00::1", true),
+            ("::1", true),
+            ("fe80::1", false),
+            ("febf::1", false),
+            ("fec0::1", false),
+            ("::192.0.2.1", false),
+        ] {
+            let addr: Ipv6Addr = addr.parse().unwrap();
+            assert_eq!(is_supported_ipv6(&addr), supported, "{}", addr);
+        }
+    }
 }
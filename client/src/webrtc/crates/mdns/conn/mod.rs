@@ -0,0 +1,440 @@
+use crate::webrtc::mdns::config::*;
+use crate::webrtc::mdns::error::*;
+use crate::webrtc::mdns::message::name::*;
+use crate::webrtc::mdns::message::{
+    header::*, parser::*, question::*, resource::a::*, resource::*, *,
+};
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use socket2::SockAddr;
+use tokio::net::UdpSocket;
+use tokio::sync::{mpsc, Mutex};
+
+use crate::webrtc::util::ifaces;
+
+pub(crate) const DEFAULT_DEST_ADDR: &str = "224.0.0.251:5353";
+
+const MULTICAST_GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+const INBOUND_BUFFER_SIZE: usize = 512;
+const DEFAULT_QUERY_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_MESSAGE_RECORDS: usize = 3;
+const RESPONSE_TTL: u32 = 120;
+const MULTICAST_TTL: u32 = 255;
+
+// DnsConn represents a mDNS Server
+pub(crate) struct DnsConn {
+    socket: Arc<UdpSocket>,
+    dst_addr: SocketAddr,
+
+    query_interval: Duration,
+    queries: Arc<Mutex<Vec<Query>>>,
+
+    is_server_closed: Arc<AtomicBool>,
+    close_server: mpsc::Sender<()>,
+}
+
+struct Query {
+    name_with_suffix: String,
+    query_result_chan: mpsc::Sender<IpAddr>,
+}
+
+impl DnsConn {
+    /// server binds a mDNS socket to addr, joins the mDNS multicast group on
+    /// every IPv4 interface and starts answering for config.local_names.
+    pub(crate) fn server(addr: SocketAddr, config: Config) -> Result<Self> {
+        let socket = socket2::Socket::new(
+            socket2::Domain::IPV4,
+            socket2::Type::DGRAM,
+            Some(socket2::Protocol::UDP),
+        )?;
+
+        // Browsers and the OS resolver usually hold 5353 already.
+        #[cfg(unix)]
+        socket.set_reuse_port(true)?;
+
+        socket.set_reuse_address(true)?;
+        socket.set_broadcast(true)?;
+        socket.set_multicast_ttl_v4(MULTICAST_TTL)?;
+        socket.set_nonblocking(true)?;
+
+        socket.bind(&SockAddr::from(addr))?;
+        {
+            let interfaces = ifaces::ifaces().map_err(|e| Error::Other(e.to_string()))?;
+
+            let mut joined = false;
+            for interface in &interfaces {
+                if let Some(SocketAddr::V4(e)) = interface.addr {
+                    if let Err(err) = socket.join_multicast_v4(&MULTICAST_GROUP, e.ip()) {
+                        log::trace!("failed to join mDNS group on {}: {}", e.ip(), err);
+                        continue;
+                    }
+                    joined = true;
+                }
+            }
+
+            if !joined {
+                return Err(Error::ErrJoiningMulticastGroup);
+            }
+        }
+
+        let socket = Arc::new(UdpSocket::from_std(socket.into())?);
+
+        let local_names = config
+            .local_names
+            .iter()
+            .map(|l| l.to_string() + ".")
+            .collect();
+
+        let dst_addr: SocketAddr = DEFAULT_DEST_ADDR
+            .parse()
+            .map_err(|_| Error::Other("invalid mDNS destination address".to_owned()))?;
+
+        let is_server_closed = Arc::new(AtomicBool::new(false));
+        let (close_server, close_server_rx) = mpsc::channel(1);
+
+        let c = DnsConn {
+            query_interval: if config.query_interval != Duration::from_secs(0) {
+                config.query_interval
+            } else {
+                DEFAULT_QUERY_INTERVAL
+            },
+            queries: Arc::new(Mutex::new(vec![])),
+            socket: Arc::clone(&socket),
+            dst_addr,
+            is_server_closed: Arc::clone(&is_server_closed),
+            close_server,
+        };
+
+        let queries = Arc::clone(&c.queries);
+        tokio::spawn(async move {
+            DnsConn::start(
+                close_server_rx,
+                is_server_closed,
+                socket,
+                local_names,
+                dst_addr,
+                queries,
+            )
+            .await
+        });
+
+        Ok(c)
+    }
+
+    /// close stops the server. Pending queries return ErrConnectionClosed.
+    pub(crate) async fn close(&self) -> Result<()> {
+        if self.is_server_closed.swap(true, Ordering::SeqCst) {
+            return Err(Error::ErrConnectionClosed);
+        }
+
+        // Dropping the result senders wakes every pending query.
+        self.queries.lock().await.clear();
+
+        let _ = self.close_server.send(()).await;
+        Ok(())
+    }
+
+    /// query sends mDNS questions for name every query_interval until an
+    /// answer arrives or the conn is closed, and returns the answered address.
+    pub(crate) async fn query(&self, name: &str) -> Result<IpAddr> {
+        if self.is_server_closed.load(Ordering::SeqCst) {
+            return Err(Error::ErrConnectionClosed);
+        }
+
+        let name_with_suffix = name.to_owned() + ".";
+
+        let (query_tx, mut query_rx) = mpsc::channel(1);
+        {
+            let mut queries = self.queries.lock().await;
+            queries.push(Query {
+                name_with_suffix: name_with_suffix.clone(),
+                query_result_chan: query_tx,
+            });
+        }
+
+        self.send_question(&name_with_suffix).await;
+
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(self.query_interval) => {
+                    self.send_question(&name_with_suffix).await
+                }
+                res = query_rx.recv() => {
+                    return res.ok_or(Error::ErrConnectionClosed);
+                }
+            }
+        }
+    }
+
+    async fn send_question(&self, name: &str) {
+        let packed_name = match Name::new(name) {
+            Ok(pn) => pn,
+            Err(err) => {
+                log::warn!("Failed to construct mDNS packet: {}", err);
+                return;
+            }
+        };
+
+        let raw_query = {
+            let mut msg = Message {
+                header: Header::default(),
+                questions: vec![Question {
+                    typ: DnsType::A,
+                    class: DNSCLASS_INET,
+                    name: packed_name,
+                }],
+                ..Default::default()
+            };
+
+            match msg.pack() {
+                Ok(v) => v,
+                Err(err) => {
+                    log::error!("Failed to construct mDNS packet {}", err);
+                    return;
+                }
+            }
+        };
+
+        if let Err(err) = self.socket.send_to(&raw_query, self.dst_addr).await {
+            log::error!("Failed to send mDNS packet {}", err);
+        }
+    }
+
+    async fn start(
+        mut closed_rx: mpsc::Receiver<()>,
+        is_server_closed: Arc<AtomicBool>,
+        socket: Arc<UdpSocket>,
+        local_names: Vec<String>,
+        dst_addr: SocketAddr,
+        queries: Arc<Mutex<Vec<Query>>>,
+    ) {
+        let mut b = vec![0u8; INBOUND_BUFFER_SIZE];
+
+        loop {
+            let (n, src) = tokio::select! {
+                _ = closed_rx.recv() => {
+                    is_server_closed.store(true, Ordering::SeqCst);
+                    return;
+                }
+                result = socket.recv_from(&mut b) => match result {
+                    Ok(r) => r,
+                    Err(err) => {
+                        log::trace!("Failed to read mDNS packet: {}", err);
+                        continue;
+                    }
+                },
+            };
+
+            let mut p = Parser::default();
+            let header = match p.start(&b[..n]) {
+                Ok(h) => h,
+                Err(err) => {
+                    log::trace!("Failed to parse mDNS packet {}", err);
+                    continue;
+                }
+            };
+
+            if header.response {
+                handle_answers(&mut p, &queries).await;
+            } else {
+                handle_questions(&mut p, &socket, &local_names, src, dst_addr).await;
+            }
+        }
+    }
+}
+
+async fn handle_questions(
+    p: &mut Parser<'_>,
+    socket: &UdpSocket,
+    local_names: &[String],
+    src: SocketAddr,
+    dst_addr: SocketAddr,
+) {
+    let mut interface_addr = None;
+    for _ in 0..=MAX_MESSAGE_RECORDS {
+        let q = match p.question() {
+            Ok(q) => q,
+            Err(err) => {
+                if Error::ErrSectionDone != err {
+                    log::trace!("Failed to parse mDNS packet {}", err);
+                }
+                return;
+            }
+        };
+
+        if q.typ != DnsType::A && q.typ != DnsType::All {
+            continue;
+        }
+        if !local_names.contains(&q.name.data) {
+            continue;
+        }
+
+        let addr = match interface_addr {
+            Some(addr) => addr,
+            None => match get_interface_addr_for_ip(src).await {
+                Ok(addr) => *interface_addr.insert(addr),
+                Err(err) => {
+                    log::warn!(
+                        "Failed to get local interface to communicate with {}: {}",
+                        src,
+                        err
+                    );
+                    return;
+                }
+            },
+        };
+
+        if let Err(err) = send_answer(socket, addr, &q.name.data, dst_addr).await {
+            log::warn!("Failed to send mDNS answer: {}", err);
+        }
+    }
+}
+
+async fn handle_answers(p: &mut Parser<'_>, queries: &Mutex<Vec<Query>>) {
+    if let Err(err) = p.skip_all_questions() {
+        log::trace!("Failed to parse mDNS packet {}", err);
+        return;
+    }
+
+    for _ in 0..=MAX_MESSAGE_RECORDS {
+        let a = match p.answer_header() {
+            Ok(a) => a,
+            Err(err) => {
+                if Error::ErrSectionDone != err {
+                    log::trace!("Failed to parse mDNS packet {}", err);
+                }
+                return;
+            }
+        };
+
+        if a.typ != DnsType::A && a.typ != DnsType::Aaaa {
+            if let Err(err) = p.skip_answer() {
+                log::trace!("Failed to parse mDNS packet {}", err);
+                return;
+            }
+            continue;
+        }
+
+        let ip = match p.resource_body() {
+            Ok(body) => body.ip(),
+            Err(err) => {
+                log::trace!("Failed to parse mDNS packet {}", err);
+                return;
+            }
+        };
+
+        let mut qs = queries.lock().await;
+        for j in (0..qs.len()).rev() {
+            if qs[j].name_with_suffix == a.name.data {
+                let _ = qs[j].query_result_chan.try_send(ip);
+                qs.remove(j);
+            }
+        }
+    }
+}
+
+async fn send_answer(
+    socket: &UdpSocket,
+    interface_addr: IpAddr,
+    name: &str,
+    dst_addr: SocketAddr,
+) -> Result<()> {
+    let a = match interface_addr {
+        IpAddr::V4(ip) => ip.octets(),
+        IpAddr::V6(_) => return Err(Error::Other("unexpected IPv6 address".to_owned())),
+    };
+
+    let raw_answer = {
+        let mut msg = Message {
+            header: Header {
+                response: true,
+                authoritative: true,
+                ..Default::default()
+            },
+            answers: vec![Resource {
+                header: ResourceHeader {
+                    typ: DnsType::A,
+                    class: DNSCLASS_INET,
+                    name: Name::new(name)?,
+                    ttl: RESPONSE_TTL,
+                    ..Default::default()
+                },
+                body: Some(Box::new(AResource { a })),
+            }],
+            ..Default::default()
+        };
+
+        msg.pack()?
+    };
+
+    socket.send_to(&raw_answer, dst_addr).await?;
+    Ok(())
+}
+
+// get_interface_addr_for_ip returns the local address the OS would use to
+// reach addr, which is the address we answer with.
+async fn get_interface_addr_for_ip(addr: SocketAddr) -> std::io::Result<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(addr).await?;
+    Ok(socket.local_addr()?.ip())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEST_NAME: &str = "dns-conn-test.local";
+
+    fn mdns_addr() -> SocketAddr {
+        SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 5353)
+    }
+
+    #[tokio::test]
+    async fn test_query_resolves_published_name() -> Result<()> {
+        let responder = DnsConn::server(
+            mdns_addr(),
+            Config {
+                local_names: vec![TEST_NAME.to_owned()],
+                ..Default::default()
+            },
+        )?;
+        let querier = DnsConn::server(mdns_addr(), Config::default())?;
+
+        let expected = get_interface_addr_for_ip(DEFAULT_DEST_ADDR.parse().unwrap()).await?;
+        let ip = tokio::time::timeout(Duration::from_secs(5), querier.query(TEST_NAME))
+            .await
+            .expect("mDNS query timed out")?;
+        assert_eq!(ip, expected);
+
+        querier.close().await?;
+        responder.close().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_close_ends_pending_query() -> Result<()> {
+        let querier = Arc::new(DnsConn::server(mdns_addr(), Config::default())?);
+
+        let pending = {
+            let querier = Arc::clone(&querier);
+            tokio::spawn(async move { querier.query("unanswered-test.local").await })
+        };
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        querier.close().await?;
+
+        let result = tokio::time::timeout(Duration::from_secs(1), pending)
+            .await
+            .expect("query still pending after close")
+            .unwrap();
+        assert!(matches!(result, Err(Error::ErrConnectionClosed)));
+        assert!(matches!(
+            querier.query(TEST_NAME).await,
+            Err(Error::ErrConnectionClosed)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use thiserror::Error;
+
+use std::io;
+use std::string::FromUtf8Error;
+
+pub(crate) type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Error, PartialEq)]
+#[non_exhaustive]
+pub(crate) enum Error {
+    #[error("mDNS: failed to join multicast group")]
+    ErrJoiningMulticastGroup,
+    #[error("mDNS: connection is closed")]
+    ErrConnectionClosed,
+    #[error("parsing/packing of this type isn't available yet")]
+    ErrNotStarted,
+    #[error("parsing/packing of this section has completed")]
+    ErrSectionDone,
+    #[error("insufficient data for base length type")]
+    ErrBaseLen,
+    #[error("insufficient data for calculated length type")]
+    ErrCalcLen,
+    #[error("segment prefix is reserved")]
+    ErrReserved,
+    #[error("too many pointers (>10)")]
+    ErrTooManyPtr,
+    #[error("invalid pointer")]
+    ErrInvalidPtr,
+    #[error("nil resource body")]
+    ErrNilResourceBody,
+    #[error("insufficient data for resource body length")]
+    ErrResourceLen,
+    #[error("segment length too long")]
+    ErrSegTooLong,
+    #[error("zero length segment")]
+    ErrZeroSegLen,
+    #[error("resource length too long")]
+    ErrResTooLong,
+    #[error("too many Questions to pack (>65535)")]
+    ErrTooManyQuestions,
+    #[error("too many Answers to pack (>65535)")]
+    ErrTooManyAnswers,
+    #[error("name is not in canonical format (it must end with a .)")]
+    ErrNonCanonicalName,
+    #[error("{0}")]
+    Io(#[source] IoError),
+    #[error("utf-8 error: {0}")]
+    Utf8(#[from] FromUtf8Error),
+    #[error("{0}")]
+    Other(String),
+}
+
+#[derive(Debug, Error)]
+#[error("io error: {0}")]
+pub(crate) struct IoError(#[from] pub(crate) io::Error);
+
+// Workaround for wanting PartialEq for io::Error.
+impl PartialEq for IoError {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.kind() == other.0.kind()
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(IoError(e))
+    }
+}
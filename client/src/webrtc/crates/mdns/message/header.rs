@@ -0,0 +1,114 @@
+use super::*;
+
+// Header is a representation of a DNS message header.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Header {
+    pub(crate) id: u16,
+    pub(crate) response: bool,
+    pub(crate) authoritative: bool,
+}
+
+impl Header {
+    pub(crate) fn pack(&self) -> (u16, u16) {
+        let id = self.id;
+        let mut bits = 0;
+        if self.authoritative {
+            bits |= HEADER_BIT_AA
+        }
+        if self.response {
+            bits |= HEADER_BIT_QR
+        }
+
+        (id, bits)
+    }
+}
+
+#[derive(Default, Copy, Clone, PartialOrd, PartialEq, Eq)]
+pub(crate) enum Section {
+    #[default]
+    NotStarted = 0,
+    Header = 1,
+    Questions = 2,
+    Answers = 3,
+    Authorities = 4,
+    Additionals = 5,
+    Done = 6,
+}
+
+impl From<u8> for Section {
+    fn from(v: u8) -> Self {
+        match v {
+            0 => Section::NotStarted,
+            1 => Section::Header,
+            2 => Section::Questions,
+            3 => Section::Answers,
+            4 => Section::Authorities,
+            5 => Section::Additionals,
+            _ => Section::Done,
+        }
+    }
+}
+
+// header is the wire format for a DNS message header.
+#[derive(Default)]
+pub(crate) struct HeaderInternal {
+    pub(crate) id: u16,
+    pub(crate) bits: u16,
+    pub(crate) questions: u16,
+    pub(crate) answers: u16,
+    pub(crate) authorities: u16,
+    pub(crate) additionals: u16,
+}
+
+impl HeaderInternal {
+    pub(crate) fn count(&self, sec: Section) -> u16 {
+        match sec {
+            Section::Questions => self.questions,
+            Section::Answers => self.answers,
+            Section::Authorities => self.authorities,
+            Section::Additionals => self.additionals,
+            _ => 0,
+        }
+    }
+
+    // pack appends the wire format of the header to msg.
+    pub(crate) fn pack(&self, mut msg: Vec<u8>) -> Vec<u8> {
+        msg = pack_uint16(msg, self.id);
+        msg = pack_uint16(msg, self.bits);
+        msg = pack_uint16(msg, self.questions);
+        msg = pack_uint16(msg, self.answers);
+        msg = pack_uint16(msg, self.authorities);
+        msg = pack_uint16(msg, self.additionals);
+        msg
+    }
+
+    pub(crate) fn unpack(&mut self, msg: &[u8], off: usize) -> Result<usize> {
+        let (id, off) = unpack_uint16(msg, off)?;
+        self.id = id;
+
+        let (bits, off) = unpack_uint16(msg, off)?;
+        self.bits = bits;
+
+        let (questions, off) = unpack_uint16(msg, off)?;
+        self.questions = questions;
+
+        let (answers, off) = unpack_uint16(msg, off)?;
+        self.answers = answers;
+
+        let (authorities, off) = unpack_uint16(msg, off)?;
+        self.authorities = authorities;
+
+        let (additionals, off) = unpack_uint16(msg, off)?;
+        self.additionals = additionals;
+
+        Ok(off)
+    }
+
+    pub(crate) fn header(&self) -> Header {
+        Header {
+            id: self.id,
+            response: (self.bits & HEADER_BIT_QR) != 0,
+            authoritative: (self.bits & HEADER_BIT_AA) != 0,
+        }
+    }
+}
@@ -0,0 +1,159 @@
+pub(crate) mod header;
+pub(crate) mod name;
+mod packer;
+pub(crate) mod parser;
+pub(crate) mod question;
+pub(crate) mod resource;
+
+use crate::webrtc::mdns::error::*;
+use header::*;
+use packer::*;
+use question::*;
+use resource::*;
+
+use std::collections::HashMap;
+use std::fmt;
+
+// Message formats
+
+// A Type is a type of DNS request and response.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) enum DnsType {
+    // ResourceHeader.Type and question.Type
+    A = 1,
+    Aaaa = 28,
+
+    // question.Type
+    All = 255,
+
+    #[default]
+    Unsupported = 0,
+}
+
+impl From<u16> for DnsType {
+    fn from(v: u16) -> Self {
+        match v {
+            1 => DnsType::A,
+            28 => DnsType::Aaaa,
+
+            // question.Type
+            255 => DnsType::All,
+
+            _ => DnsType::Unsupported,
+        }
+    }
+}
+
+impl fmt::Display for DnsType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match *self {
+            DnsType::A => "A",
+            DnsType::Aaaa => "AAAA",
+            DnsType::All => "ALL",
+            DnsType::Unsupported => "Unsupported",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl DnsType {
+    // pack_type appends the wire format of field to msg.
+    pub(crate) fn pack(&self, msg: Vec<u8>) -> Vec<u8> {
+        pack_uint16(msg, *self as u16)
+    }
+
+    pub(crate) fn unpack(&mut self, msg: &[u8], off: usize) -> Result<usize> {
+        let (t, o) = unpack_uint16(msg, off)?;
+        *self = DnsType::from(t);
+        Ok(o)
+    }
+
+    pub(crate) fn skip(msg: &[u8], off: usize) -> Result<usize> {
+        skip_uint16(msg, off)
+    }
+}
+
+// A Class is a type of network.
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct DnsClass(pub(crate) u16);
+
+// ResourceHeader.Class and question.Class
+pub(crate) const DNSCLASS_INET: DnsClass = DnsClass(1);
+
+impl DnsClass {
+    // pack_class appends the wire format of field to msg.
+    pub(crate) fn pack(&self, msg: Vec<u8>) -> Vec<u8> {
+        pack_uint16(msg, self.0)
+    }
+
+    pub(crate) fn unpack(&mut self, msg: &[u8], off: usize) -> Result<usize> {
+        let (c, o) = unpack_uint16(msg, off)?;
+        *self = DnsClass(c);
+        Ok(o)
+    }
+
+    pub(crate) fn skip(msg: &[u8], off: usize) -> Result<usize> {
+        skip_uint16(msg, off)
+    }
+}
+
+// Internal constants.
+
+// UINT16LEN is the length (in bytes) of a uint16.
+const UINT16LEN: usize = 2;
+
+// UINT32LEN is the length (in bytes) of a uint32.
+const UINT32LEN: usize = 4;
+
+const HEADER_BIT_QR: u16 = 1 << 15; // query/response (response=1)
+const HEADER_BIT_AA: u16 = 1 << 10; // authoritative
+
+// Message is a representation of a DNS message. Only the sections mDNS
+// queries and answers use are packed.
+#[derive(Default, Debug)]
+pub(crate) struct Message {
+    pub(crate) header: Header,
+    pub(crate) questions: Vec<Question>,
+    pub(crate) answers: Vec<Resource>,
+}
+
+impl Message {
+    // Pack packs a full Message.
+    pub(crate) fn pack(&mut self) -> Result<Vec<u8>> {
+        // Validate the lengths. It is very unlikely that anyone will try to
+        // pack more than 65535 of any particular type, but it is possible and
+        // we should fail gracefully.
+        if self.questions.len() > u16::MAX as usize {
+            return Err(Error::ErrTooManyQuestions);
+        }
+        if self.answers.len() > u16::MAX as usize {
+            return Err(Error::ErrTooManyAnswers);
+        }
+
+        let (id, bits) = self.header.pack();
+
+        let h = HeaderInternal {
+            id,
+            bits,
+            questions: self.questions.len() as u16,
+            answers: self.answers.len() as u16,
+            ..Default::default()
+        };
+
+        let mut msg = h.pack(vec![]);
+
+        // RFC 1035 allows (but does not require) compression for packing. RFC
+        // 1035 requires unpacking implementations to support compression, so
+        // unconditionally enabling it is fine.
+        let mut compression = Some(HashMap::new());
+
+        for question in &self.questions {
+            msg = question.pack(msg, &mut compression, 0)?;
+        }
+        for answer in &mut self.answers {
+            msg = answer.pack(msg, &mut compression, 0)?;
+        }
+
+        Ok(msg)
+    }
+}
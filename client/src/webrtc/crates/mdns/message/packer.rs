@@ -0,0 +1,65 @@
+use super::*;
+use crate::webrtc::mdns::error::*;
+
+// pack_bytes appends the wire format of field to msg.
+pub(crate) fn pack_bytes(mut msg: Vec<u8>, field: &[u8]) -> Vec<u8> {
+    msg.extend_from_slice(field);
+    msg
+}
+
+pub(crate) fn unpack_bytes(msg: &[u8], off: usize, field: &mut [u8]) -> Result<usize> {
+    let new_off = off + field.len();
+    if new_off > msg.len() {
+        return Err(Error::ErrBaseLen);
+    }
+    field.copy_from_slice(&msg[off..new_off]);
+    Ok(new_off)
+}
+
+// pack_uint16 appends the wire format of field to msg.
+pub(crate) fn pack_uint16(mut msg: Vec<u8>, field: u16) -> Vec<u8> {
+    msg.extend_from_slice(&field.to_be_bytes());
+    msg
+}
+
+pub(crate) fn unpack_uint16(msg: &[u8], off: usize) -> Result<(u16, usize)> {
+    if off + UINT16LEN > msg.len() {
+        return Err(Error::ErrBaseLen);
+    }
+
+    Ok((
+        (msg[off] as u16) << 8 | (msg[off + 1] as u16),
+        off + UINT16LEN,
+    ))
+}
+
+pub(crate) fn skip_uint16(msg: &[u8], off: usize) -> Result<usize> {
+    if off + UINT16LEN > msg.len() {
+        return Err(Error::ErrBaseLen);
+    }
+    Ok(off + UINT16LEN)
+}
+
+// pack_uint32 appends the wire format of field to msg.
+pub(crate) fn pack_uint32(mut msg: Vec<u8>, field: u32) -> Vec<u8> {
+    msg.extend_from_slice(&field.to_be_bytes());
+    msg
+}
+
+pub(crate) fn unpack_uint32(msg: &[u8], off: usize) -> Result<(u32, usize)> {
+    if off + UINT32LEN > msg.len() {
+        return Err(Error::ErrBaseLen);
+    }
+    let v = (msg[off] as u32) << 24
+        | (msg[off + 1] as u32) << 16
+        | (msg[off + 2] as u32) << 8
+        | (msg[off + 3] as u32);
+    Ok((v, off + UINT32LEN))
+}
+
+pub(crate) fn skip_uint32(msg: &[u8], off: usize) -> Result<usize> {
+    if off + UINT32LEN > msg.len() {
+        return Err(Error::ErrBaseLen);
+    }
+    Ok(off + UINT32LEN)
+}
@@ -0,0 +1,156 @@
+use crate::webrtc::mdns::error::*;
+use crate::webrtc::mdns::message::header::{Header, HeaderInternal, Section};
+use crate::webrtc::mdns::message::name::Name;
+use crate::webrtc::mdns::message::question::Question;
+use crate::webrtc::mdns::message::resource::{
+    unpack_resource_body, Resource, ResourceBody, ResourceHeader,
+};
+use crate::webrtc::mdns::message::{DnsClass, DnsType};
+
+// A Parser allows incrementally parsing a DNS message.
+//
+// When parsing is started, the Header is parsed. Next, each question can be
+// either parsed or skipped. After all Questions have been either parsed or
+// skipped, all Answers can be either parsed or skipped in the same way.
+//
+// Note that there is no requirement to fully skip or parse the message.
+#[derive(Default)]
+pub(crate) struct Parser<'a> {
+    pub(crate) msg: &'a [u8],
+    pub(crate) header: HeaderInternal,
+
+    pub(crate) section: Section,
+    pub(crate) off: usize,
+    pub(crate) index: usize,
+    pub(crate) res_header_valid: bool,
+    pub(crate) res_header: ResourceHeader,
+}
+
+impl<'a> Parser<'a> {
+    // start parses the header and enables the parsing of Questions.
+    pub(crate) fn start(&mut self, msg: &'a [u8]) -> Result<Header> {
+        *self = Parser {
+            msg,
+            ..Default::default()
+        };
+        self.off = self.header.unpack(msg, 0)?;
+        self.section = Section::Questions;
+        Ok(self.header.header())
+    }
+
+    fn check_advance(&mut self, sec: Section) -> Result<()> {
+        if self.section < sec {
+            return Err(Error::ErrNotStarted);
+        }
+        if self.section > sec {
+            return Err(Error::ErrSectionDone);
+        }
+        self.res_header_valid = false;
+        if self.index == self.header.count(sec) as usize {
+            self.index = 0;
+            self.section = Section::from(1 + self.section as u8);
+            return Err(Error::ErrSectionDone);
+        }
+        Ok(())
+    }
+
+    fn resource_header(&mut self, sec: Section) -> Result<ResourceHeader> {
+        if self.res_header_valid {
+            return Ok(self.res_header.clone());
+        }
+        self.check_advance(sec)?;
+        let mut hdr = ResourceHeader::default();
+        let off = hdr.unpack(self.msg, self.off)?;
+
+        self.res_header_valid = true;
+        self.res_header = hdr.clone();
+        self.off = off;
+        Ok(hdr)
+    }
+
+    fn skip_resource(&mut self, sec: Section) -> Result<()> {
+        if self.res_header_valid {
+            let new_off = self.off + self.res_header.length as usize;
+            if new_off > self.msg.len() {
+                return Err(Error::ErrResourceLen);
+            }
+            self.off = new_off;
+            self.res_header_valid = false;
+            self.index += 1;
+            return Ok(());
+        }
+        self.check_advance(sec)?;
+
+        self.off = Resource::skip(self.msg, self.off)?;
+        self.index += 1;
+        Ok(())
+    }
+
+    // question parses a single question.
+    pub(crate) fn question(&mut self) -> Result<Question> {
+        self.check_advance(Section::Questions)?;
+        let mut name = Name::new("")?;
+        let mut off = name.unpack(self.msg, self.off)?;
+        let mut typ = DnsType::Unsupported;
+        off = typ.unpack(self.msg, off)?;
+        let mut class = DnsClass::default();
+        off = class.unpack(self.msg, off)?;
+        self.off = off;
+        self.index += 1;
+        Ok(Question { name, typ, class })
+    }
+
+    // skip_question skips a single question.
+    pub(crate) fn skip_question(&mut self) -> Result<()> {
+        self.check_advance(Section::Questions)?;
+        let mut off = Name::skip(self.msg, self.off)?;
+        off = DnsType::skip(self.msg, off)?;
+        off = DnsClass::skip(self.msg, off)?;
+        self.off = off;
+        self.index += 1;
+        Ok(())
+    }
+
+    // skip_all_questions skips all Questions.
+    pub(crate) fn skip_all_questions(&mut self) -> Result<()> {
+        loop {
+            if let Err(err) = self.skip_question() {
+                if Error::ErrSectionDone == err {
+                    return Ok(());
+                } else {
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    // answer_header parses a single answer ResourceHeader.
+    pub(crate) fn answer_header(&mut self) -> Result<ResourceHeader> {
+        self.resource_header(Section::Answers)
+    }
+
+    // skip_answer skips a single answer Resource.
+    pub(crate) fn skip_answer(&mut self) -> Result<()> {
+        self.skip_resource(Section::Answers)
+    }
+
+    // resource_body parses a single resource_boy.
+    //
+    // One of the XXXHeader methods must have been called before calling this
+    // method.
+    pub(crate) fn resource_body(&mut self) -> Result<Box<dyn ResourceBody + Send + Sync>> {
+        if !self.res_header_valid {
+            return Err(Error::ErrNotStarted);
+        }
+        let (rb, _off) = unpack_resource_body(
+            self.res_header.typ,
+            self.msg,
+            self.off,
+            self.res_header.length as usize,
+        )?;
+        self.off += self.res_header.length as usize;
+        self.res_header_valid = false;
+        self.index += 1;
+        Ok(rb)
+    }
+}
@@ -0,0 +1,26 @@
+use super::name::*;
+use super::*;
+
+use std::collections::HashMap;
+
+// A question is a DNS query.
+#[derive(Default, Debug, PartialEq, Eq, Clone)]
+pub(crate) struct Question {
+    pub(crate) name: Name,
+    pub(crate) typ: DnsType,
+    pub(crate) class: DnsClass,
+}
+
+impl Question {
+    // pack appends the wire format of the question to msg.
+    pub(crate) fn pack(
+        &self,
+        mut msg: Vec<u8>,
+        compression: &mut Option<HashMap<String, usize>>,
+        compression_off: usize,
+    ) -> Result<Vec<u8>> {
+        msg = self.name.pack(msg, compression, compression_off)?;
+        msg = self.typ.pack(msg);
+        Ok(self.class.pack(msg))
+    }
+}
@@ -0,0 +1,6 @@
+pub(crate) mod config;
+pub(crate) mod conn;
+mod error;
+pub(crate) mod message;
+
+pub(crate) use error::Error;
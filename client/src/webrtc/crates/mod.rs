@@ -1,6 +1,8 @@
 pub(crate) mod dtls;
 pub(crate) mod ice;
+pub(crate) mod mdns;
 pub(crate) mod sctp;
 pub(crate) mod sdp;
 pub(crate) mod stun;
+pub(crate) mod turn;
 pub(crate) mod util;
@@ -51,7 +51,7 @@ pub(crate) struct AssociationInternal {
     // Congestion control parameters
     pub(crate) max_receive_buffer_size: u32,
     pub(crate) cwnd: u32,     // my congestion window size
-    rwnd: u32,                // calculated peer's receiver windows size
+    pub(crate) rwnd: u32,     // calculated peer's receiver windows size
     pub(crate) ssthresh: u32, // slow start threshold
     partial_bytes_acked: u32,
     pub(crate) in_fast_recovery: bool,
@@ -1950,8 +1950,32 @@ impl AssociationInternal {
         }
 
         // PR-SCTP
-        if self.streams.contains_key(&c.stream_identifier) {
-            c.set_abandoned(true);
+        if let Some(s) = self.streams.get(&c.stream_identifier) {
+            let reliability_type: ReliabilityType =
+                s.reliability_type.load(Ordering::SeqCst).into();
+            let reliability_value = s.reliability_value.load(Ordering::SeqCst);
+
+            let abandon = match reliability_type {
+                ReliabilityType::Reliable => false,
+                // the first transmission counts as one
+                ReliabilityType::Rexmit => c.nsent > reliability_value,
+                ReliabilityType::Timed => SystemTime::now()
+                    .duration_since(c.since)
+                    .map(|elapsed| elapsed.as_millis() >= reliability_value as u128)
+                    .unwrap_or(false),
+            };
+            if abandon {
+                c.set_abandoned(true);
+                log::trace!(
+                    "[{}] marked as abandoned: tsn={} ppi={} (sent {} times, {:?} {})",
+                    self.name,
+                    c.tsn,
+                    c.payload_type,
+                    c.nsent,
+                    reliability_type,
+                    reliability_value
+                );
+            }
         } else {
             log::error!("[{}] stream {} not found)", self.name, c.stream_identifier);
         }
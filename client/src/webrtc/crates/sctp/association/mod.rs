@@ -1,5 +1,5 @@
 mod association_internal;
-mod association_stats;
+pub(crate) mod association_stats;
 
 use crate::webrtc::sctp::chunk::chunk_abort::ChunkAbort;
 use crate::webrtc::sctp::chunk::chunk_cookie_ack::ChunkCookieAck;
@@ -208,6 +208,8 @@ pub(crate) struct Config {
 pub(crate) struct Association {
     name: String,
     net_conn: Arc<dyn Conn + Send + Sync>,
+    bytes_received: Arc<AtomicUsize>,
+    bytes_sent: Arc<AtomicUsize>,
 
     pub(crate) association_internal: Arc<Mutex<AssociationInternal>>,
 }
@@ -357,6 +359,8 @@ impl Association {
             Association {
                 name,
                 net_conn,
+                bytes_received,
+                bytes_sent,
                 association_internal,
             },
             handshake_completed_ch_rx,
@@ -477,4 +481,44 @@ impl Association {
         let mut ai = self.association_internal.lock().await;
         ai.open_stream(stream_identifier)
     }
+
+    /// bytes_sent returns the number of bytes sent
+    pub(crate) fn bytes_sent(&self) -> usize {
+        self.bytes_sent.load(Ordering::SeqCst)
+    }
+
+    /// bytes_received returns the number of bytes received
+    pub(crate) fn bytes_received(&self) -> usize {
+        self.bytes_received.load(Ordering::SeqCst)
+    }
+
+    /// srtt returns the smoothed round trip time in milliseconds, 0 until measured
+    pub(crate) async fn srtt(&self) -> u64 {
+        let ai = self.association_internal.lock().await;
+        ai.rto_mgr.srtt
+    }
+
+    /// rto returns the current retransmission timeout in milliseconds
+    pub(crate) async fn rto(&self) -> u64 {
+        let ai = self.association_internal.lock().await;
+        ai.rto_mgr.get_rto()
+    }
+
+    /// cwnd returns the congestion window size
+    pub(crate) async fn cwnd(&self) -> u32 {
+        let ai = self.association_internal.lock().await;
+        ai.cwnd
+    }
+
+    /// rwnd returns the peer's receiver window size
+    pub(crate) async fn rwnd(&self) -> u32 {
+        let ai = self.association_internal.lock().await;
+        ai.rwnd
+    }
+
+    /// stats returns the association's chunk and retransmission counters
+    pub(crate) async fn stats(&self) -> Arc<AssociationStats> {
+        let ai = self.association_internal.lock().await;
+        Arc::clone(&ai.stats)
+    }
 }
@@ -16,6 +16,30 @@ use std::task::{Context, Poll};
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 use tokio::sync::{mpsc, Mutex, Notify};
 
+/// ReliabilityType is the partial reliability policy of a stream, as
+/// described in <https://www.rfc-editor.org/rfc/rfc3758>
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(C)]
+pub(crate) enum ReliabilityType {
+    /// ReliabilityTypeReliable is used for reliable transmission
+    #[default]
+    Reliable = 0,
+    /// ReliabilityTypeRexmit is used for partial reliability by retransmission count
+    Rexmit = 1,
+    /// ReliabilityTypeTimed is used for partial reliability by retransmission duration
+    Timed = 2,
+}
+
+impl From<u8> for ReliabilityType {
+    fn from(v: u8) -> ReliabilityType {
+        match v {
+            1 => ReliabilityType::Rexmit,
+            2 => ReliabilityType::Timed,
+            _ => ReliabilityType::Reliable,
+        }
+    }
+}
+
 pub(crate) type OnBufferedAmountLowFn =
     Box<dyn (FnMut() -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>) + Send + Sync>;
 
@@ -34,6 +58,9 @@ pub(crate) struct Stream {
     pub(crate) reassembly_queue: Mutex<ReassemblyQueue>,
     pub(crate) sequence_number: AtomicU16,
     pub(crate) read_notifier: Notify,
+    pub(crate) unordered: AtomicBool,
+    pub(crate) reliability_type: AtomicU8, //ReliabilityType,
+    pub(crate) reliability_value: AtomicU32,
     pub(crate) closed: AtomicBool,
     pub(crate) buffered_amount: AtomicUsize,
     pub(crate) buffered_amount_low: AtomicUsize,
@@ -51,6 +78,9 @@ impl fmt::Debug for Stream {
             .field("stream_identifier", &self.stream_identifier)
             .field("reassembly_queue", &self.reassembly_queue)
             .field("sequence_number", &self.sequence_number)
+            .field("unordered", &self.unordered)
+            .field("reliability_type", &self.reliability_type)
+            .field("reliability_value", &self.reliability_value)
             .field("closed", &self.closed)
             .field("buffered_amount", &self.buffered_amount)
             .field("buffered_amount_low", &self.buffered_amount_low)
@@ -80,6 +110,9 @@ impl Stream {
             reassembly_queue: Mutex::new(ReassemblyQueue::new(stream_identifier)),
             sequence_number: AtomicU16::new(0),
             read_notifier: Notify::new(),
+            unordered: AtomicBool::new(false),
+            reliability_type: AtomicU8::new(ReliabilityType::Reliable as u8),
+            reliability_value: AtomicU32::new(0),
             closed: AtomicBool::new(false),
             buffered_amount: AtomicUsize::new(0),
             buffered_amount_low: AtomicUsize::new(0),
@@ -88,6 +121,28 @@ impl Stream {
         }
     }
 
+    /// set_reliability_params sets reliability parameters for this stream.
+    /// rel_val is the maximum number of transmissions for Rexmit, and the
+    /// lifetime in milliseconds for Timed.
+    pub(crate) fn set_reliability_params(
+        &self,
+        unordered: bool,
+        rel_type: ReliabilityType,
+        rel_val: u32,
+    ) {
+        log::debug!(
+            "[{}] reliability params: ordered={} type={:?} value={}",
+            self.name,
+            !unordered,
+            rel_type,
+            rel_val
+        );
+        self.unordered.store(unordered, Ordering::SeqCst);
+        self.reliability_type
+            .store(rel_type as u8, Ordering::SeqCst);
+        self.reliability_value.store(rel_val, Ordering::SeqCst);
+    }
+
     /// read reads a packet of len(p) bytes, dropping the Payload Protocol Identifier.
     /// Returns EOF when the stream is reset or an error if the stream is closed
     /// otherwise.
@@ -195,7 +250,8 @@ impl Stream {
         // From draft-ietf-rtcweb-data-protocol-09, section 6:
         //   All Data Channel Establishment Protocol messages MUST be sent using
         //   ordered delivery and reliable transmission.
-        let unordered = ppi != PayloadProtocolIdentifier::Dcep;
+        let unordered =
+            ppi != PayloadProtocolIdentifier::Dcep && self.unordered.load(Ordering::SeqCst);
 
         let mut chunks = vec![];
 
@@ -256,6 +312,11 @@ impl Stream {
         Ok(())
     }
 
+    /// buffered_amount returns the number of bytes of data currently queued to be sent over this stream.
+    pub(crate) fn buffered_amount(&self) -> usize {
+        self.buffered_amount.load(Ordering::SeqCst)
+    }
+
     /// set_buffered_amount_low_threshold is used to update the threshold.
     /// See buffered_amount_low_threshold().
     pub(crate) fn set_buffered_amount_low_threshold(&self, th: usize) {
@@ -15,6 +15,7 @@ pub(crate) const ATTR_KEY_GROUP: &str = "group";
 pub(crate) const ATTR_KEY_CONNECTION_SETUP: &str = "setup";
 pub(crate) const ATTR_KEY_MID: &str = "mid";
 pub(crate) const ATTR_KEY_ICELITE: &str = "ice-lite";
+pub(crate) const ATTR_KEY_MAX_MESSAGE_SIZE: &str = "max-message-size";
 
 /// Version describes the value provided by the "v=" field which gives
 /// the version of the Session Description Protocol.
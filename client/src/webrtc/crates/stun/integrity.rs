@@ -3,9 +3,13 @@ use crate::webrtc::stun::checks::*;
 use crate::webrtc::stun::error::*;
 use crate::webrtc::stun::message::*;
 
+use md5::{Digest, Md5};
 use ring::hmac;
 use std::fmt;
 
+// separator for credentials.
+pub(crate) const CREDENTIALS_SEP: &str = ":";
+
 // MessageIntegrity represents MESSAGE-INTEGRITY attribute.
 //
 // add_to and Check methods are using zero-allocation version of hmac, see
@@ -63,6 +67,23 @@ impl MessageIntegrity {
         MessageIntegrity(password.as_bytes().to_vec())
     }
 
+    // new_long_term_integrity returns new MessageIntegrity with key for long-term
+    // credentials. Each value is expected to be SASL-prepared.
+    //
+    // RFC 5389 Section 15.4
+    pub(crate) fn new_long_term_integrity(
+        username: String,
+        realm: String,
+        password: String,
+    ) -> Self {
+        let s = [username, realm, password].join(CREDENTIALS_SEP);
+
+        let mut h = Md5::new();
+        h.update(s.as_bytes());
+
+        MessageIntegrity(h.finalize().to_vec())
+    }
+
     // Check checks MESSAGE-INTEGRITY attribute.
     //
     // CPU costly, see BenchmarkMessageIntegrity_Check.
@@ -0,0 +1,371 @@
+pub(crate) mod relay_conn;
+pub(crate) mod stun_conn;
+
+use crate::webrtc::stun::agent::TransactionId;
+use crate::webrtc::stun::attributes::*;
+use crate::webrtc::stun::error_code::*;
+use crate::webrtc::stun::integrity::*;
+use crate::webrtc::stun::message::*;
+use crate::webrtc::stun::textattrs::*;
+use crate::webrtc::turn::error::*;
+use crate::webrtc::turn::proto::chandata::ChannelData;
+use crate::webrtc::turn::proto::*;
+use crate::webrtc::util::Conn;
+use relay_conn::RelayConn;
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, oneshot, Mutex};
+
+const DEFAULT_RTO: Duration = Duration::from_millis(200);
+const MAX_RTX_INTERVAL: Duration = Duration::from_millis(1600);
+const MAX_RTX_COUNT: u16 = 7;
+/// How long a request sent over a stream waits for its response, since
+/// reliable transports are not retransmitted over.
+///
+/// RFC 5389 Section 7.2.2
+const RELIABLE_TRANSACTION_TIMEOUT: Duration = Duration::from_millis(39_500);
+const MAX_DATA_BUFFER_SIZE: usize = u16::MAX as usize; // message size limit for Chromium
+/// How many packets from peers are held until the relay conn reads them.
+const RELAYED_QUEUE_SIZE: usize = 256;
+
+/// A packet a peer sent to the relayed address, with the peer's address.
+pub(crate) type RelayedPacket = (Vec<u8>, SocketAddr);
+
+/// ClientConfig is a bag of config parameters for Client.
+pub(crate) struct ClientConfig {
+    pub(crate) turn_server_addr: SocketAddr,
+    pub(crate) username: String,
+    pub(crate) password: String,
+    /// The conn the client talks to the TURN server over.
+    pub(crate) conn: Arc<dyn Conn + Send + Sync>,
+    /// Whether conn is a stream, such as a `StunConn`, which requests are
+    /// not retransmitted over.
+    pub(crate) reliable: bool,
+}
+
+#[derive(Default)]
+struct Credentials {
+    realm: String,
+    nonce: String,
+    integrity: MessageIntegrity,
+}
+
+/// Client is a TURN client (RFC 5766) holding at most one UDP allocation.
+/// It owns the conn to the server, and demultiplexes what arrives on it into
+/// transaction responses and data relayed from peers.
+pub(crate) struct Client {
+    conn: Arc<dyn Conn + Send + Sync>,
+    reliable: bool,
+    turn_server_addr: SocketAddr,
+    username: String,
+    password: String,
+    credentials: Mutex<Credentials>,
+    transactions: Mutex<HashMap<TransactionId, oneshot::Sender<Message>>>,
+    relayed_tx: Mutex<Option<mpsc::Sender<RelayedPacket>>>,
+    channels: Mutex<HashMap<ChannelNumber, SocketAddr>>,
+    closed_ch: Mutex<Option<broadcast::Sender<()>>>,
+}
+
+impl Client {
+    /// Creates a client and starts reading from its conn.
+    pub(crate) fn new(config: ClientConfig) -> Arc<Self> {
+        let (closed_tx, closed_rx) = broadcast::channel(1);
+        let client = Arc::new(Client {
+            conn: config.conn,
+            reliable: config.reliable,
+            turn_server_addr: config.turn_server_addr,
+            username: config.username,
+            password: config.password,
+            credentials: Mutex::new(Credentials::default()),
+            transactions: Mutex::new(HashMap::new()),
+            relayed_tx: Mutex::new(None),
+            channels: Mutex::new(HashMap::new()),
+            closed_ch: Mutex::new(Some(closed_tx)),
+        });
+
+        tokio::spawn(Arc::clone(&client).read_loop(closed_rx));
+
+        client
+    }
+
+    /// Allocates a relayed UDP transport address on the server, and returns
+    /// a conn that sends and receives through it.
+    pub(crate) async fn allocate(self: &Arc<Self>) -> Result<RelayConn> {
+        {
+            let relayed_tx = self.relayed_tx.lock().await;
+            if relayed_tx.is_some() {
+                return Err(Error::ErrAlreadyAllocated);
+            }
+        }
+
+        let res = self
+            .request(
+                METHOD_ALLOCATE,
+                &[&RequestedTransport {
+                    protocol: PROTO_UDP,
+                }],
+            )
+            .await?;
+
+        let mut relayed = RelayedAddress::default();
+        relayed.get_from(&res)?;
+        let mut lifetime = Lifetime::default();
+        lifetime.get_from(&res)?;
+
+        let (relayed_tx, relayed_rx) = mpsc::channel(RELAYED_QUEUE_SIZE);
+        {
+            let mut tx = self.relayed_tx.lock().await;
+            *tx = Some(relayed_tx);
+        }
+
+        Ok(RelayConn::new(
+            Arc::clone(self),
+            SocketAddr::new(relayed.ip, relayed.port),
+            lifetime.0,
+            relayed_rx,
+        )
+        .await)
+    }
+
+    /// Stops the client, failing pending transactions and ending the relay
+    /// conn's reads. The server-side allocation is left to expire.
+    pub(crate) async fn close(&self) {
+        {
+            let mut closed_ch = self.closed_ch.lock().await;
+            if closed_ch.take().is_none() {
+                return;
+            }
+        }
+
+        {
+            let mut transactions = self.transactions.lock().await;
+            transactions.clear();
+        }
+        {
+            let mut relayed_tx = self.relayed_tx.lock().await;
+            relayed_tx.take();
+        }
+
+        let _ = self.conn.close().await;
+    }
+
+    /// Returns a receiver that errors once the client is closed, or None if
+    /// it already is.
+    async fn closed_rx(&self) -> Option<broadcast::Receiver<()>> {
+        let closed_ch = self.closed_ch.lock().await;
+        closed_ch.as_ref().map(|tx| tx.subscribe())
+    }
+
+    async fn bind_channel(&self, number: ChannelNumber, peer: SocketAddr) -> Result<()> {
+        self.request(METHOD_CHANNEL_BIND, &[&number, &PeerAddress::from(peer)])
+            .await?;
+
+        let mut channels = self.channels.lock().await;
+        channels.insert(number, peer);
+
+        Ok(())
+    }
+
+    /// Sends a raw STUN message or ChannelData message to the server.
+    async fn send(&self, raw: &[u8]) -> Result<usize> {
+        Ok(self.conn.send_to(raw, self.turn_server_addr).await?)
+    }
+
+    /// Sends a request with the given attributes, adding the long-term
+    /// credentials once the server has challenged for them. A 401 answer
+    /// to the first request, and a stale nonce, are retried once.
+    async fn request(&self, method: Method, attrs: &[&(dyn Setter + Sync)]) -> Result<Message> {
+        let mut challenged = false;
+        let mut nonce_refreshed = false;
+        loop {
+            let mut msg = Message::new();
+            msg.build(&[
+                Box::new(MessageType {
+                    method,
+                    class: CLASS_REQUEST,
+                }),
+                Box::new(TransactionId::new()),
+            ])?;
+            for attr in attrs {
+                attr.add_to(&mut msg)?;
+            }
+            {
+                let credentials = self.credentials.lock().await;
+                if !credentials.nonce.is_empty() {
+                    Username::new(ATTR_USERNAME, self.username.clone()).add_to(&mut msg)?;
+                    Realm::new(ATTR_REALM, credentials.realm.clone()).add_to(&mut msg)?;
+                    Nonce::new(ATTR_NONCE, credentials.nonce.clone()).add_to(&mut msg)?;
+                    credentials.integrity.add_to(&mut msg)?;
+                }
+            }
+
+            let res = self.perform_transaction(&msg).await?;
+            if res.typ.method != method {
+                return Err(Error::ErrUnexpectedResponse);
+            }
+            if res.typ.class == CLASS_SUCCESS_RESPONSE {
+                return Ok(res);
+            }
+
+            let mut code = ErrorCodeAttribute::default();
+            code.get_from(&res)?;
+            let retry = match code.code {
+                CODE_UNAUTHORIZED if !challenged => {
+                    challenged = true;
+                    let realm = Realm::get_from_as(&res, ATTR_REALM)?;
+                    let mut credentials = self.credentials.lock().await;
+                    credentials.integrity = MessageIntegrity::new_long_term_integrity(
+                        self.username.clone(),
+                        realm.text.clone(),
+                        self.password.clone(),
+                    );
+                    credentials.realm = realm.text;
+                    true
+                }
+                CODE_STALE_NONCE if !nonce_refreshed => {
+                    nonce_refreshed = true;
+                    true
+                }
+                _ => false,
+            };
+            if !retry {
+                return Err(Error::Other(format!(
+                    "{} error response (error {}: {})",
+                    method,
+                    code.code.0,
+                    String::from_utf8_lossy(&code.reason)
+                )));
+            }
+
+            let nonce = Nonce::get_from_as(&res, ATTR_NONCE)?;
+            let mut credentials = self.credentials.lock().await;
+            credentials.nonce = nonce.text;
+        }
+    }
+
+    /// Sends the request until its response arrives, retransmitting with a
+    /// doubling timeout unless the conn is reliable.
+    async fn perform_transaction(&self, msg: &Message) -> Result<Message> {
+        let (res_tx, mut res_rx) = oneshot::channel();
+        {
+            let mut transactions = self.transactions.lock().await;
+            transactions.insert(msg.transaction_id, res_tx);
+        }
+
+        let mut result = Err(Error::ErrTransactionTimeout);
+        let (attempts, mut rto) = if self.reliable {
+            (1, RELIABLE_TRANSACTION_TIMEOUT)
+        } else {
+            (MAX_RTX_COUNT + 1, DEFAULT_RTO)
+        };
+        for _ in 0..attempts {
+            if let Err(err) = self.send(&msg.raw).await {
+                result = Err(err);
+                break;
+            }
+            match tokio::time::timeout(rto, &mut res_rx).await {
+                Ok(Ok(res)) => {
+                    result = Ok(res);
+                    break;
+                }
+                Ok(Err(_)) => {
+                    result = Err(Error::ErrClosed);
+                    break;
+                }
+                Err(_) => rto = (rto * 2).min(MAX_RTX_INTERVAL),
+            }
+        }
+
+        let mut transactions = self.transactions.lock().await;
+        transactions.remove(&msg.transaction_id);
+
+        result
+    }
+
+    async fn read_loop(self: Arc<Self>, mut closed_rx: broadcast::Receiver<()>) {
+        let mut buf = vec![0u8; MAX_DATA_BUFFER_SIZE];
+        loop {
+            let (n, from) = tokio::select! {
+                result = self.conn.recv_from(&mut buf) => match result {
+                    Ok(result) => result,
+                    Err(err) => {
+                        log::debug!("exiting TURN read loop: {}", err);
+                        break;
+                    }
+                },
+                _ = closed_rx.recv() => break,
+            };
+
+            if from != self.turn_server_addr {
+                log::debug!("dropping packet from {}, not the TURN server", from);
+                continue;
+            }
+
+            if let Err(err) = self.handle_inbound(&buf[..n]).await {
+                log::debug!("failed to handle packet from the TURN server: {}", err);
+            }
+        }
+    }
+
+    async fn handle_inbound(&self, data: &[u8]) -> Result<()> {
+        if is_message(data) {
+            let mut msg = Message::new();
+            msg.raw = data.to_vec();
+            msg.decode()?;
+
+            if msg.typ.class == CLASS_INDICATION {
+                if msg.typ.method == METHOD_DATA {
+                    let mut peer = PeerAddress::default();
+                    peer.get_from(&msg)?;
+                    let mut data = Data::default();
+                    data.get_from(&msg)?;
+
+                    self.deliver(data.0, SocketAddr::new(peer.ip, peer.port))
+                        .await;
+                }
+                return Ok(());
+            }
+
+            let mut transactions = self.transactions.lock().await;
+            if let Some(res_tx) = transactions.remove(&msg.transaction_id) {
+                let _ = res_tx.send(msg);
+            }
+
+            Ok(())
+        } else if ChannelData::is_channel_data(data) {
+            let mut ch_data = ChannelData {
+                raw: data.to_vec(),
+                ..Default::default()
+            };
+            ch_data.decode()?;
+
+            let peer = {
+                let channels = self.channels.lock().await;
+                channels.get(&ch_data.number).copied()
+            };
+            match peer {
+                Some(peer) => {
+                    self.deliver(ch_data.data, peer).await;
+                    Ok(())
+                }
+                None => Err(Error::ErrInvalidChannelNumber),
+            }
+        } else {
+            Err(Error::Other(
+                "neither a STUN message nor ChannelData".to_owned(),
+            ))
+        }
+    }
+
+    /// Hands a relayed packet to the relay conn, dropping it like a full
+    /// socket buffer would when the conn is not reading.
+    async fn deliver(&self, data: Vec<u8>, from: SocketAddr) {
+        let relayed_tx = self.relayed_tx.lock().await;
+        if let Some(relayed_tx) = &*relayed_tx {
+            let _ = relayed_tx.try_send((data, from));
+        }
+    }
+}
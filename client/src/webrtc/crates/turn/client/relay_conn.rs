@@ -0,0 +1,713 @@
+use super::*;
+use crate::webrtc::util;
+
+use async_trait::async_trait;
+use std::net::IpAddr;
+use tokio::time::Instant;
+
+type ConnResult<T> = std::result::Result<T, util::Error>;
+
+/// Permissions expire after 5 minutes, they are refreshed well before that.
+///
+/// RFC 5766 Section 8
+const PERMISSION_REFRESH_INTERVAL: Duration = Duration::from_secs(120);
+/// Channel bindings expire after 10 minutes.
+///
+/// RFC 5766 Section 11
+const BINDING_REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// How long closing waits for the server to release the allocation.
+const DEALLOCATE_TIMEOUT: Duration = Duration::from_secs(1);
+/// How long a failed allocation refresh waits before trying again.
+const REFRESH_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The state of a permission or a channel binding. `refreshed_at` is None
+/// until the server first accepts it.
+#[derive(Default)]
+struct Grant {
+    refreshed_at: Option<Instant>,
+    refreshing: bool,
+}
+
+impl Grant {
+    fn pending() -> Self {
+        Grant {
+            refreshing: true,
+            ..Default::default()
+        }
+    }
+
+    fn is_granted(&self) -> bool {
+        self.refreshed_at.is_some()
+    }
+
+    /// Whether a refresh should start, which it does when none is running
+    /// and the last one is older than the interval.
+    fn needs_refresh(&self, interval: Duration) -> bool {
+        !self.refreshing && self.refreshed_at.is_none_or(|at| at.elapsed() >= interval)
+    }
+}
+
+struct Binding {
+    number: ChannelNumber,
+    grant: Grant,
+}
+
+struct RelayConnInternal {
+    client: Arc<Client>,
+    permissions: Mutex<HashMap<IpAddr, Grant>>,
+    bindings: Mutex<HashMap<SocketAddr, Binding>>,
+    next_channel: Mutex<u16>,
+}
+
+/// RelayConn sends and receives through a TURN allocation, as if it were a
+/// UDP socket bound to the relayed address. Sending to a peer creates a
+/// permission for it first, and binds a channel in the background so later
+/// packets go out as ChannelData instead of Send indications. Both are
+/// refreshed while packets keep flowing, and the allocation is refreshed
+/// for as long as the conn is open.
+pub(crate) struct RelayConn {
+    relayed_addr: SocketAddr,
+    relayed_rx: Mutex<mpsc::Receiver<RelayedPacket>>,
+    internal: Arc<RelayConnInternal>,
+}
+
+impl RelayConn {
+    pub(super) async fn new(
+        client: Arc<Client>,
+        relayed_addr: SocketAddr,
+        lifetime: Duration,
+        relayed_rx: mpsc::Receiver<RelayedPacket>,
+    ) -> Self {
+        if let Some(closed_rx) = client.closed_rx().await {
+            tokio::spawn(Self::refresh_allocation_loop(
+                Arc::clone(&client),
+                lifetime,
+                closed_rx,
+            ));
+        }
+
+        RelayConn {
+            relayed_addr,
+            relayed_rx: Mutex::new(relayed_rx),
+            internal: Arc::new(RelayConnInternal {
+                client,
+                permissions: Mutex::new(HashMap::new()),
+                bindings: Mutex::new(HashMap::new()),
+                next_channel: Mutex::new(MIN_CHANNEL_NUMBER),
+            }),
+        }
+    }
+
+    /// Refreshes the allocation at half its lifetime until the client closes.
+    async fn refresh_allocation_loop(
+        client: Arc<Client>,
+        mut lifetime: Duration,
+        mut closed_rx: broadcast::Receiver<()>,
+    ) {
+        let mut wait = lifetime / 2;
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = closed_rx.recv() => return,
+            }
+
+            match client
+                .request(METHOD_REFRESH, &[&Lifetime(DEFAULT_LIFETIME)])
+                .await
+            {
+                Ok(res) => {
+                    let mut updated = Lifetime::default();
+                    if updated.get_from(&res).is_ok() {
+                        lifetime = updated.0;
+                    }
+                    wait = lifetime / 2;
+                }
+                Err(Error::ErrClosed) => return,
+                Err(err) => {
+                    log::warn!("failed to refresh the TURN allocation: {}", err);
+                    wait = REFRESH_RETRY_INTERVAL.min(lifetime / 2);
+                }
+            }
+        }
+    }
+}
+
+impl RelayConnInternal {
+    async fn send_to(self: &Arc<Self>, buf: &[u8], peer: SocketAddr) -> Result<usize> {
+        self.create_permission(peer.ip()).await?;
+
+        if let Some(number) = self.bound_channel(peer).await {
+            let mut ch_data = ChannelData {
+                data: buf.to_vec(),
+                number,
+                ..Default::default()
+            };
+            ch_data.encode();
+            self.client.send(&ch_data.raw).await?;
+        } else {
+            let mut msg = Message::new();
+            msg.build(&[
+                Box::new(MessageType {
+                    method: METHOD_SEND,
+                    class: CLASS_INDICATION,
+                }),
+                Box::new(TransactionId::new()),
+                Box::new(PeerAddress::from(peer)),
+                Box::new(Data(buf.to_vec())),
+            ])?;
+            self.client.send(&msg.raw).await?;
+        }
+
+        Ok(buf.len())
+    }
+
+    /// Makes sure the server relays packets from the peer's IP. The first
+    /// send to a peer waits for the permission, later ones refresh it in the
+    /// background.
+    async fn create_permission(self: &Arc<Self>, ip: IpAddr) -> Result<()> {
+        {
+            let mut permissions = self.permissions.lock().await;
+            if let Some(permission) = permissions.get_mut(&ip) {
+                if permission.needs_refresh(PERMISSION_REFRESH_INTERVAL) {
+                    permission.refreshing = true;
+                    let internal = Arc::clone(self);
+                    tokio::spawn(async move {
+                        let _ = internal.refresh_permission(ip).await;
+                    });
+                }
+                // a pending permission belongs to a concurrent first send,
+                // this packet goes out without waiting for it
+                return Ok(());
+            }
+            permissions.insert(ip, Grant::pending());
+        }
+
+        self.refresh_permission(ip).await
+    }
+
+    async fn refresh_permission(&self, ip: IpAddr) -> Result<()> {
+        let result = self
+            .client
+            .request(
+                METHOD_CREATE_PERMISSION,
+                &[&PeerAddress::from(SocketAddr::new(ip, 0))],
+            )
+            .await;
+
+        let mut permissions = self.permissions.lock().await;
+        match &result {
+            Ok(_) => {
+                if let Some(permission) = permissions.get_mut(&ip) {
+                    permission.refreshing = false;
+                    permission.refreshed_at = Some(Instant::now());
+                }
+            }
+            Err(err) => {
+                log::warn!("failed to create a TURN permission for {}: {}", ip, err);
+                permissions.remove(&ip);
+            }
+        }
+
+        result.map(|_| ())
+    }
+
+    /// Returns the channel bound to the peer, and starts binding or
+    /// refreshing one in the background when needed.
+    async fn bound_channel(self: &Arc<Self>, peer: SocketAddr) -> Option<ChannelNumber> {
+        let mut bindings = self.bindings.lock().await;
+
+        if !bindings.contains_key(&peer) {
+            let number = self.next_channel_number(&bindings).await?;
+            bindings.insert(
+                peer,
+                Binding {
+                    number,
+                    grant: Grant::default(),
+                },
+            );
+        }
+
+        let binding = bindings.get_mut(&peer)?;
+        if binding.grant.needs_refresh(BINDING_REFRESH_INTERVAL) {
+            binding.grant.refreshing = true;
+            let number = binding.number;
+            let internal = Arc::clone(self);
+            tokio::spawn(async move {
+                internal.refresh_binding(number, peer).await;
+            });
+        }
+
+        if binding.grant.is_granted() {
+            Some(binding.number)
+        } else {
+            None
+        }
+    }
+
+    async fn next_channel_number(
+        &self,
+        bindings: &HashMap<SocketAddr, Binding>,
+    ) -> Option<ChannelNumber> {
+        let mut next_channel = self.next_channel.lock().await;
+        for _ in MIN_CHANNEL_NUMBER..=MAX_CHANNEL_NUMBER {
+            let number = ChannelNumber(*next_channel);
+            *next_channel = if *next_channel == MAX_CHANNEL_NUMBER {
+                MIN_CHANNEL_NUMBER
+            } else {
+                *next_channel + 1
+            };
+            if !bindings.values().any(|binding| binding.number == number) {
+                return Some(number);
+            }
+        }
+
+        log::warn!("{}", Error::ErrNoFreeChannel);
+        None
+    }
+
+    async fn refresh_binding(&self, number: ChannelNumber, peer: SocketAddr) {
+        let result = self.client.bind_channel(number, peer).await;
+
+        let mut bindings = self.bindings.lock().await;
+        match result {
+            Ok(()) => {
+                if let Some(binding) = bindings.get_mut(&peer) {
+                    binding.grant.refreshing = false;
+                    binding.grant.refreshed_at = Some(Instant::now());
+                }
+            }
+            Err(err) => {
+                // Send indications keep working, binding is retried on a
+                // later send
+                log::warn!("failed to bind channel {} to {}: {}", number, peer, err);
+                if let Some(binding) = bindings.get_mut(&peer) {
+                    binding.grant.refreshing = false;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Conn for RelayConn {
+    async fn recv(&self, buf: &mut [u8]) -> ConnResult<usize> {
+        let (n, _) = self.recv_from(buf).await?;
+        Ok(n)
+    }
+
+    async fn recv_from(&self, buf: &mut [u8]) -> ConnResult<(usize, SocketAddr)> {
+        let mut relayed_rx = self.relayed_rx.lock().await;
+        match relayed_rx.recv().await {
+            Some((data, from)) => {
+                if data.len() > buf.len() {
+                    return Err(util::Error::ErrBufferShort);
+                }
+                buf[..data.len()].copy_from_slice(&data);
+                Ok((data.len(), from))
+            }
+            None => Err(util::Error::ErrUseClosedNetworkConn),
+        }
+    }
+
+    async fn send(&self, _buf: &[u8]) -> ConnResult<usize> {
+        Err(util::Error::ErrNoRemAddr)
+    }
+
+    async fn send_to(&self, buf: &[u8], target: SocketAddr) -> ConnResult<usize> {
+        Ok(self.internal.send_to(buf, target).await?)
+    }
+
+    async fn local_addr(&self) -> ConnResult<SocketAddr> {
+        Ok(self.relayed_addr)
+    }
+
+    async fn remote_addr(&self) -> Option<SocketAddr> {
+        None
+    }
+
+    /// Releases the allocation, then closes the client.
+    async fn close(&self) -> ConnResult<()> {
+        let client = &self.internal.client;
+        let _ = tokio::time::timeout(
+            DEALLOCATE_TIMEOUT,
+            client.request(METHOD_REFRESH, &[&Lifetime(Duration::from_secs(0))]),
+        )
+        .await;
+        client.close().await;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::webrtc::stun::xoraddr::XorMappedAddress;
+    use std::sync::Mutex as SyncMutex;
+    use tokio::net::UdpSocket;
+
+    const USERNAME: &str = "user";
+    const PASSWORD: &str = "pass";
+    const REALM: &str = "turn.test";
+    const LIFETIME: Duration = Duration::from_secs(1);
+    const WAIT: Duration = Duration::from_secs(5);
+
+    type ServerLog = Arc<SyncMutex<Vec<(Instant, String)>>>;
+
+    /// A TURN server stand-in on loopback that challenges for long-term
+    /// credentials, answers the first CreatePermission with a stale nonce,
+    /// and relays between the client and peers through a second socket.
+    struct TestServer {
+        addr: SocketAddr,
+        relayed_addr: SocketAddr,
+        log: ServerLog,
+    }
+
+    impl TestServer {
+        async fn start() -> Result<Self> {
+            let conn = UdpSocket::bind("127.0.0.1:0").await?;
+            let relay = UdpSocket::bind("127.0.0.1:0").await?;
+            let server = TestServer {
+                addr: conn.local_addr()?,
+                relayed_addr: relay.local_addr()?,
+                log: Arc::new(SyncMutex::new(vec![])),
+            };
+
+            let log = Arc::clone(&server.log);
+            let relayed_addr = server.relayed_addr;
+            tokio::spawn(async move {
+                let mut state = ServerState {
+                    nonce: "nonce-1".to_owned(),
+                    nonce_expired: false,
+                    client: None,
+                    channels: HashMap::new(),
+                    relayed_addr,
+                    log,
+                };
+                let mut buf = vec![0u8; 1500];
+                let mut relay_buf = vec![0u8; 1500];
+                loop {
+                    tokio::select! {
+                        result = conn.recv_from(&mut buf) => {
+                            let Ok((n, from)) = result else { break };
+                            state.client = Some(from);
+                            for (raw, to) in state.handle_client(&buf[..n]) {
+                                let sock = if to == from { &conn } else { &relay };
+                                let _ = sock.send_to(&raw, to).await;
+                            }
+                        }
+                        result = relay.recv_from(&mut relay_buf) => {
+                            let Ok((n, from)) = result else { break };
+                            if let Some((raw, to)) = state.handle_peer(&relay_buf[..n], from) {
+                                let _ = conn.send_to(&raw, to).await;
+                            }
+                        }
+                    }
+                }
+            });
+
+            Ok(server)
+        }
+
+        fn entries(&self) -> Vec<String> {
+            let log = self.log.lock().unwrap();
+            log.iter().map(|(_, entry)| entry.clone()).collect()
+        }
+
+        fn logged_at(&self, entry: &str) -> Option<Instant> {
+            let log = self.log.lock().unwrap();
+            log.iter().find(|(_, e)| e == entry).map(|(at, _)| *at)
+        }
+
+        async fn wait_for(&self, entry: &str) -> Instant {
+            tokio::time::timeout(WAIT, async {
+                loop {
+                    if let Some(at) = self.logged_at(entry) {
+                        return at;
+                    }
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+            .await
+            .unwrap_or_else(|_| panic!("server never logged {entry:?}: {:?}", self.entries()))
+        }
+    }
+
+    struct ServerState {
+        nonce: String,
+        nonce_expired: bool,
+        client: Option<SocketAddr>,
+        channels: HashMap<u16, SocketAddr>,
+        relayed_addr: SocketAddr,
+        log: ServerLog,
+    }
+
+    impl ServerState {
+        fn log(&self, entry: String) {
+            let mut log = self.log.lock().unwrap();
+            log.push((Instant::now(), entry));
+        }
+
+        /// Returns the packets to send, each to the client or to a peer.
+        fn handle_client(&mut self, data: &[u8]) -> Vec<(Vec<u8>, SocketAddr)> {
+            let client = self.client.unwrap();
+
+            if ChannelData::is_channel_data(data) {
+                let mut ch_data = ChannelData {
+                    raw: data.to_vec(),
+                    ..Default::default()
+                };
+                ch_data.decode().unwrap();
+                self.log("channel data".to_owned());
+                return match self.channels.get(&ch_data.number.0) {
+                    Some(peer) => vec![(ch_data.data, *peer)],
+                    None => vec![],
+                };
+            }
+
+            let mut req = Message::new();
+            req.raw = data.to_vec();
+            req.decode().unwrap();
+
+            if req.typ.class == CLASS_INDICATION {
+                assert_eq!(req.typ.method, METHOD_SEND);
+                let mut peer = PeerAddress::default();
+                peer.get_from(&req).unwrap();
+                let mut data = Data::default();
+                data.get_from(&req).unwrap();
+                self.log("send indication".to_owned());
+                return vec![(data.0, SocketAddr::new(peer.ip, peer.port))];
+            }
+
+            let name = match req.typ.method {
+                METHOD_ALLOCATE => "allocate",
+                METHOD_REFRESH => "refresh",
+                METHOD_CREATE_PERMISSION => "create permission",
+                METHOD_CHANNEL_BIND => "channel bind",
+                method => panic!("unexpected request {method}"),
+            };
+
+            if !req.contains(ATTR_MESSAGE_INTEGRITY) {
+                self.log(format!("{name}: 401"));
+                let res = self.error(&req, CODE_UNAUTHORIZED);
+                return vec![(res.raw, client)];
+            }
+            let username = Username::get_from_as(&req, ATTR_USERNAME).unwrap();
+            let realm = Realm::get_from_as(&req, ATTR_REALM).unwrap();
+            assert_eq!(username.text, USERNAME);
+            assert_eq!(realm.text, REALM);
+            MessageIntegrity::new_long_term_integrity(
+                USERNAME.to_owned(),
+                REALM.to_owned(),
+                PASSWORD.to_owned(),
+            )
+            .check(&mut req)
+            .unwrap();
+
+            let nonce = Nonce::get_from_as(&req, ATTR_NONCE).unwrap();
+            if req.typ.method == METHOD_CREATE_PERMISSION && !self.nonce_expired {
+                self.nonce_expired = true;
+                self.nonce = "nonce-2".to_owned();
+            }
+            if nonce.text != self.nonce {
+                self.log(format!("{name}: 438"));
+                let res = self.error(&req, CODE_STALE_NONCE);
+                return vec![(res.raw, client)];
+            }
+
+            let mut res = Message::new();
+            res.build(&[
+                Box::new(MessageType {
+                    method: req.typ.method,
+                    class: CLASS_SUCCESS_RESPONSE,
+                }),
+                Box::new(req.transaction_id),
+            ])
+            .unwrap();
+            match req.typ.method {
+                METHOD_ALLOCATE => {
+                    XorMappedAddress {
+                        ip: self.relayed_addr.ip(),
+                        port: self.relayed_addr.port(),
+                    }
+                    .add_to_as(&mut res, ATTR_XOR_RELAYED_ADDRESS)
+                    .unwrap();
+                    Lifetime(LIFETIME).add_to(&mut res).unwrap();
+                    self.log(name.to_owned());
+                }
+                METHOD_REFRESH => {
+                    let mut lifetime = Lifetime::default();
+                    lifetime.get_from(&req).unwrap();
+                    Lifetime(lifetime.0.min(LIFETIME)).add_to(&mut res).unwrap();
+                    self.log(format!("{name} {}", lifetime.0.as_secs()));
+                }
+                METHOD_CHANNEL_BIND => {
+                    let number = req.get(ATTR_CHANNEL_NUMBER).unwrap();
+                    let number = u16::from_be_bytes([number[0], number[1]]);
+                    let mut peer = PeerAddress::default();
+                    peer.get_from(&req).unwrap();
+                    self.channels
+                        .insert(number, SocketAddr::new(peer.ip, peer.port));
+                    self.log(format!("{name} {number:#x}"));
+                }
+                _ => self.log(name.to_owned()),
+            }
+
+            vec![(res.raw, client)]
+        }
+
+        /// Relays a packet from a peer to the client, over the peer's channel
+        /// when one is bound and in a Data indication otherwise.
+        fn handle_peer(&mut self, data: &[u8], from: SocketAddr) -> Option<(Vec<u8>, SocketAddr)> {
+            let client = self.client?;
+
+            let number = self
+                .channels
+                .iter()
+                .find(|(_, peer)| **peer == from)
+                .map(|(number, _)| *number);
+            if let Some(number) = number {
+                let mut ch_data = ChannelData {
+                    data: data.to_vec(),
+                    number: ChannelNumber(number),
+                    ..Default::default()
+                };
+                ch_data.encode();
+                self.log("relayed channel data".to_owned());
+                return Some((ch_data.raw, client));
+            }
+
+            let mut msg = Message::new();
+            msg.build(&[
+                Box::new(MessageType {
+                    method: METHOD_DATA,
+                    class: CLASS_INDICATION,
+                }),
+                Box::new(TransactionId::new()),
+                Box::new(PeerAddress::from(from)),
+                Box::new(Data(data.to_vec())),
+            ])
+            .unwrap();
+            self.log("relayed data indication".to_owned());
+            Some((msg.raw, client))
+        }
+
+        fn error(&self, req: &Message, code: ErrorCode) -> Message {
+            let mut res = Message::new();
+            res.build(&[
+                Box::new(MessageType {
+                    method: req.typ.method,
+                    class: CLASS_ERROR_RESPONSE,
+                }),
+                Box::new(req.transaction_id),
+                Box::new(ErrorCodeAttribute {
+                    code,
+                    reason: vec![],
+                }),
+                Box::new(Realm::new(ATTR_REALM, REALM.to_owned())),
+                Box::new(Nonce::new(ATTR_NONCE, self.nonce.clone())),
+            ])
+            .unwrap();
+            res
+        }
+    }
+
+    async fn recv_from(conn: &RelayConn) -> Result<(Vec<u8>, SocketAddr)> {
+        let mut buf = vec![0u8; 1500];
+        let (n, from) = tokio::time::timeout(WAIT, conn.recv_from(&mut buf))
+            .await
+            .map_err(|_| Error::ErrTransactionTimeout)??;
+        Ok((buf[..n].to_vec(), from))
+    }
+
+    async fn peer_recv_from(peer: &UdpSocket) -> Result<(Vec<u8>, SocketAddr)> {
+        let mut buf = vec![0u8; 1500];
+        let (n, from) = tokio::time::timeout(WAIT, peer.recv_from(&mut buf))
+            .await
+            .map_err(|_| Error::ErrTransactionTimeout)??;
+        Ok((buf[..n].to_vec(), from))
+    }
+
+    #[tokio::test]
+    async fn test_relay_conn_through_server() -> Result<()> {
+        let server = TestServer::start().await?;
+        let client = Client::new(ClientConfig {
+            turn_server_addr: server.addr,
+            username: USERNAME.to_owned(),
+            password: PASSWORD.to_owned(),
+            conn: Arc::new(UdpSocket::bind("127.0.0.1:0").await?),
+            reliable: false,
+        });
+
+        let relay_conn = client.allocate().await?;
+        assert_eq!(relay_conn.local_addr().await?, server.relayed_addr);
+        assert_eq!(server.entries(), vec!["allocate: 401", "allocate"]);
+
+        let peer = UdpSocket::bind("127.0.0.1:0").await?;
+        let peer_addr = peer.local_addr()?;
+
+        // Without a channel, the server relays in a Data indication
+        peer.send_to(b"hello", server.relayed_addr).await?;
+        assert_eq!(
+            recv_from(&relay_conn).await?,
+            (b"hello".to_vec(), peer_addr)
+        );
+        server.wait_for("relayed data indication").await;
+
+        // The first send waits for the permission, retrying on a stale
+        // nonce, and goes out as a Send indication
+        relay_conn.send_to(b"ping", peer_addr).await?;
+        assert_eq!(
+            peer_recv_from(&peer).await?,
+            (b"ping".to_vec(), server.relayed_addr)
+        );
+        server.wait_for("create permission: 438").await;
+        server.wait_for("create permission").await;
+        server.wait_for("send indication").await;
+
+        // Once the channel is bound, both directions use ChannelData
+        server
+            .wait_for(&format!("channel bind {MIN_CHANNEL_NUMBER:#x}"))
+            .await;
+        tokio::time::timeout(WAIT, async {
+            loop {
+                {
+                    let bindings = relay_conn.internal.bindings.lock().await;
+                    if bindings
+                        .get(&peer_addr)
+                        .is_some_and(|binding| binding.grant.is_granted())
+                    {
+                        break;
+                    }
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .map_err(|_| Error::ErrTransactionTimeout)?;
+
+        relay_conn.send_to(b"ping again", peer_addr).await?;
+        assert_eq!(
+            peer_recv_from(&peer).await?,
+            (b"ping again".to_vec(), server.relayed_addr)
+        );
+        server.wait_for("channel data").await;
+
+        peer.send_to(b"pong", server.relayed_addr).await?;
+        assert_eq!(recv_from(&relay_conn).await?, (b"pong".to_vec(), peer_addr));
+        server.wait_for("relayed channel data").await;
+
+        // The allocation is refreshed at half its lifetime
+        let allocated_at = server.logged_at("allocate").unwrap();
+        let refreshed_at = server
+            .wait_for(&format!("refresh {}", DEFAULT_LIFETIME.as_secs()))
+            .await;
+        assert!(refreshed_at.duration_since(allocated_at) >= LIFETIME / 2);
+
+        // Closing releases the allocation
+        relay_conn.close().await?;
+        server.wait_for("refresh 0").await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+use crate::webrtc::stun::attributes::nearest_padded_value_length;
+use crate::webrtc::stun::message::MESSAGE_HEADER_SIZE;
+use crate::webrtc::turn::error::*;
+use crate::webrtc::turn::proto::chandata::CHANNEL_DATA_HEADER_SIZE;
+use crate::webrtc::util::{self, Conn};
+
+use async_trait::async_trait;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio_rustls::rustls::{ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
+use tokio_rustls::TlsConnector;
+
+type ConnResult<T> = std::result::Result<T, util::Error>;
+
+/// StunConn carries STUN messages and ChannelData messages over a byte
+/// stream such as a TCP or TLS connection to a TURN server, packetizing
+/// the stream by the length each message carries in its header.
+///
+/// Over a stream ChannelData is padded to a multiple of 4 bytes, which
+/// reads skip.
+///
+/// RFC 5766 Section 11.5
+pub(crate) struct StunConn {
+    reader: Mutex<Box<dyn AsyncRead + Send + Unpin>>,
+    writer: Mutex<Box<dyn AsyncWrite + Send + Unpin>>,
+    local_addr: SocketAddr,
+    remote_addr: SocketAddr,
+}
+
+impl StunConn {
+    pub(crate) fn new<S>(stream: S, local_addr: SocketAddr, remote_addr: SocketAddr) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (reader, writer) = tokio::io::split(stream);
+        StunConn {
+            reader: Mutex::new(Box::new(reader)),
+            writer: Mutex::new(Box::new(writer)),
+            local_addr,
+            remote_addr,
+        }
+    }
+}
+
+#[async_trait]
+impl Conn for StunConn {
+    async fn recv(&self, buf: &mut [u8]) -> ConnResult<usize> {
+        let (n, _) = self.recv_from(buf).await?;
+        Ok(n)
+    }
+
+    async fn recv_from(&self, buf: &mut [u8]) -> ConnResult<(usize, SocketAddr)> {
+        let mut reader = self.reader.lock().await;
+
+        let mut header = [0u8; CHANNEL_DATA_HEADER_SIZE];
+        reader.read_exact(&mut header).await?;
+        let length = u16::from_be_bytes([header[2], header[3]]) as usize;
+
+        // the two most significant bits are 0b00 for STUN and 0b01 for
+        // ChannelData, anything else means the stream lost its framing
+        let (size, padded_size) = match header[0] >> 6 {
+            0b00 => (MESSAGE_HEADER_SIZE + length, MESSAGE_HEADER_SIZE + length),
+            0b01 => (
+                CHANNEL_DATA_HEADER_SIZE + length,
+                CHANNEL_DATA_HEADER_SIZE + nearest_padded_value_length(length),
+            ),
+            _ => return Err(util::Error::Other("invalid STUN stream framing".to_owned())),
+        };
+        if size > buf.len() {
+            return Err(util::Error::ErrBufferShort);
+        }
+
+        buf[..CHANNEL_DATA_HEADER_SIZE].copy_from_slice(&header);
+        reader
+            .read_exact(&mut buf[CHANNEL_DATA_HEADER_SIZE..size])
+            .await?;
+
+        let mut padding = [0u8; 3];
+        reader
+            .read_exact(&mut padding[..padded_size - size])
+            .await?;
+
+        Ok((size, self.remote_addr))
+    }
+
+    async fn send(&self, buf: &[u8]) -> ConnResult<usize> {
+        let mut writer = self.writer.lock().await;
+        writer.write_all(buf).await?;
+        writer.flush().await?;
+        Ok(buf.len())
+    }
+
+    /// Writes to the stream, whose far end is the only destination.
+    async fn send_to(&self, buf: &[u8], _target: SocketAddr) -> ConnResult<usize> {
+        self.send(buf).await
+    }
+
+    async fn local_addr(&self) -> ConnResult<SocketAddr> {
+        Ok(self.local_addr)
+    }
+
+    async fn remote_addr(&self) -> Option<SocketAddr> {
+        Some(self.remote_addr)
+    }
+
+    async fn close(&self) -> ConnResult<()> {
+        let mut writer = self.writer.lock().await;
+        writer.shutdown().await?;
+        Ok(())
+    }
+}
+
+impl StunConn {
+    /// Carries STUN over a TCP connection to the TURN server.
+    pub(crate) fn from_tcp(stream: TcpStream) -> Result<Self> {
+        let local_addr = stream.local_addr()?;
+        let remote_addr = stream.peer_addr()?;
+        Ok(StunConn::new(stream, local_addr, remote_addr))
+    }
+
+    /// Carries STUN over TLS on a TCP connection to the TURN server. The
+    /// server's certificate must be valid for `server_name` and chain to
+    /// one of the bundled web PKI roots.
+    pub(crate) async fn from_tls(stream: TcpStream, server_name: &str) -> Result<Self> {
+        let local_addr = stream.local_addr()?;
+        let remote_addr = stream.peer_addr()?;
+
+        let server_name = ServerName::try_from(server_name)
+            .map_err(|err| Error::Other(format!("invalid TLS server name: {}", err)))?;
+
+        let mut roots = RootCertStore::empty();
+        roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(
+                ta.subject,
+                ta.spki,
+                ta.name_constraints,
+            )
+        }));
+        let config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        let stream = TlsConnector::from(Arc::new(config))
+            .connect(server_name, stream)
+            .await?;
+
+        Ok(StunConn::new(stream, local_addr, remote_addr))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+    use tokio::io::DuplexStream;
+
+    // a binding request carrying one 4 byte attribute
+    const STUN_MESSAGE: [u8; 28] = [
+        0x00, 0x01, 0x00, 0x08, 0x21, 0x12, 0xa4, 0x42, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12,
+        0x80, 0x22, 0x00, 0x04, b't', b'e', b's', b't',
+    ];
+
+    // ChannelData for channel 0x4000 carrying 5 bytes, padded to 8
+    const CHANNEL_DATA: [u8; 9] = [0x40, 0x00, 0x00, 0x05, b'h', b'e', b'l', b'l', b'o'];
+    const PADDING: [u8; 3] = [0; 3];
+
+    fn pipe() -> (StunConn, DuplexStream) {
+        let (client, server) = tokio::io::duplex(1024);
+        let addr = SocketAddr::from(([127, 0, 0, 1], 3478));
+        (StunConn::new(client, addr, addr), server)
+    }
+
+    #[tokio::test]
+    async fn test_recv_stun_split_across_reads() -> ConnResult<()> {
+        let (conn, mut server) = pipe();
+        let writer = tokio::spawn(async move {
+            server.write_all(&STUN_MESSAGE[..2]).await?;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            server.write_all(&STUN_MESSAGE[2..23]).await?;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            server.write_all(&STUN_MESSAGE[23..]).await?;
+            Ok::<_, std::io::Error>(server)
+        });
+
+        let mut buf = [0u8; 1500];
+        let (n, from) = conn.recv_from(&mut buf).await?;
+        assert_eq!(&buf[..n], &STUN_MESSAGE[..]);
+        assert_eq!(from, SocketAddr::from(([127, 0, 0, 1], 3478)));
+        writer.await.unwrap()?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recv_skips_channel_data_padding() -> ConnResult<()> {
+        let (conn, mut server) = pipe();
+        server.write_all(&CHANNEL_DATA).await?;
+        server.write_all(&PADDING).await?;
+        server.write_all(&STUN_MESSAGE).await?;
+        server.write_all(&CHANNEL_DATA).await?;
+        server.write_all(&PADDING).await?;
+
+        let mut buf = [0u8; 1500];
+        let n = conn.recv(&mut buf).await?;
+        assert_eq!(&buf[..n], &CHANNEL_DATA[..]);
+        let n = conn.recv(&mut buf).await?;
+        assert_eq!(&buf[..n], &STUN_MESSAGE[..]);
+        let n = conn.recv(&mut buf).await?;
+        assert_eq!(&buf[..n], &CHANNEL_DATA[..]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recv_buffer_short() -> ConnResult<()> {
+        let (conn, mut server) = pipe();
+        server.write_all(&STUN_MESSAGE).await?;
+
+        let mut buf = [0u8; 20];
+        assert_eq!(
+            conn.recv(&mut buf).await.unwrap_err(),
+            util::Error::ErrBufferShort
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recv_invalid_framing() -> ConnResult<()> {
+        let (conn, mut server) = pipe();
+        server.write_all(&[0xc0, 0x00, 0x00, 0x00]).await?;
+
+        let mut buf = [0u8; 1500];
+        assert!(matches!(
+            conn.recv(&mut buf).await,
+            Err(util::Error::Other(_))
+        ));
+
+        Ok(())
+    }
+}
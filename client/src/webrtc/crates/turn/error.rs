@@ -0,0 +1,58 @@
+use thiserror::Error;
+
+use std::io;
+
+pub(crate) type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Error, PartialEq)]
+#[non_exhaustive]
+pub(crate) enum Error {
+    #[error("unexpected EOF")]
+    ErrUnexpectedEof,
+    #[error("channel number not in [0x4000, 0x7FFF]")]
+    ErrInvalidChannelNumber,
+    #[error("channelData length != len(Data)")]
+    ErrBadChannelDataLength,
+    #[error("the TURN server did not answer the request")]
+    ErrTransactionTimeout,
+    #[error("the TURN client is closed")]
+    ErrClosed,
+    #[error("the TURN client already has an allocation")]
+    ErrAlreadyAllocated,
+    #[error("the server answered with an unexpected response type")]
+    ErrUnexpectedResponse,
+    #[error("no free channel number left")]
+    ErrNoFreeChannel,
+    #[error("{0}")]
+    Stun(#[from] crate::webrtc::stun::Error),
+    #[error("{0}")]
+    Util(#[from] crate::webrtc::util::Error),
+    #[error("{0}")]
+    Io(#[source] IoError),
+    #[error("{0}")]
+    Other(String),
+}
+
+#[derive(Debug, Error)]
+#[error("io error: {0}")]
+pub(crate) struct IoError(#[from] pub(crate) io::Error);
+
+// Workaround for wanting PartialEq for io::Error.
+impl PartialEq for IoError {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.kind() == other.0.kind()
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(IoError(e))
+    }
+}
+
+// The relay conn surfaces TURN failures through the util::Conn interface.
+impl From<Error> for crate::webrtc::util::Error {
+    fn from(e: Error) -> Self {
+        crate::webrtc::util::Error::Other(e.to_string())
+    }
+}
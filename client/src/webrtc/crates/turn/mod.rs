@@ -0,0 +1,5 @@
+pub(crate) mod client;
+mod error;
+pub(crate) mod proto;
+
+pub(crate) use error::Error;
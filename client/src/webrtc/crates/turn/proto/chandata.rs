@@ -0,0 +1,76 @@
+use super::ChannelNumber;
+use crate::webrtc::stun::attributes::nearest_padded_value_length;
+use crate::webrtc::turn::error::*;
+
+const CHANNEL_DATA_LENGTH_SIZE: usize = 2;
+const CHANNEL_DATA_NUMBER_SIZE: usize = CHANNEL_DATA_LENGTH_SIZE;
+pub(crate) const CHANNEL_DATA_HEADER_SIZE: usize =
+    CHANNEL_DATA_LENGTH_SIZE + CHANNEL_DATA_NUMBER_SIZE;
+
+// ChannelData represents The ChannelData Message.
+//
+// See RFC 5766 Section 11.4
+#[derive(Default, Debug)]
+pub(crate) struct ChannelData {
+    pub(crate) data: Vec<u8>, // can be subslice of Raw
+    pub(crate) number: ChannelNumber,
+    pub(crate) raw: Vec<u8>,
+}
+
+impl ChannelData {
+    // encode encodes ChannelData Message to raw. The data is padded to a
+    // multiple of 4 bytes, which stream transports require and datagram
+    // transports accept.
+    pub(crate) fn encode(&mut self) {
+        self.raw.clear();
+        self.raw.extend_from_slice(&self.number.0.to_be_bytes());
+        self.raw
+            .extend_from_slice(&(self.data.len() as u16).to_be_bytes());
+        self.raw.extend_from_slice(&self.data);
+        let padded = nearest_padded_value_length(self.raw.len());
+        self.raw.resize(padded, 0);
+    }
+
+    // decode decodes The ChannelData Message from raw.
+    pub(crate) fn decode(&mut self) -> Result<()> {
+        let buf = &self.raw;
+        if buf.len() < CHANNEL_DATA_HEADER_SIZE {
+            return Err(Error::ErrUnexpectedEof);
+        }
+        let num = u16::from_be_bytes([buf[0], buf[1]]);
+        self.number = ChannelNumber(num);
+        if !self.number.is_valid() {
+            return Err(Error::ErrInvalidChannelNumber);
+        }
+        let l = u16::from_be_bytes([
+            buf[CHANNEL_DATA_NUMBER_SIZE],
+            buf[CHANNEL_DATA_NUMBER_SIZE + 1],
+        ]) as usize;
+        if l > buf[CHANNEL_DATA_HEADER_SIZE..].len() {
+            return Err(Error::ErrBadChannelDataLength);
+        }
+        self.data = buf[CHANNEL_DATA_HEADER_SIZE..CHANNEL_DATA_HEADER_SIZE + l].to_vec();
+
+        Ok(())
+    }
+
+    // is_channel_data returns true if buf looks like the ChannelData Message.
+    pub(crate) fn is_channel_data(buf: &[u8]) -> bool {
+        if buf.len() < CHANNEL_DATA_HEADER_SIZE {
+            return false;
+        }
+
+        if u16::from_be_bytes([
+            buf[CHANNEL_DATA_NUMBER_SIZE],
+            buf[CHANNEL_DATA_NUMBER_SIZE + 1],
+        ]) as usize
+            > buf[CHANNEL_DATA_HEADER_SIZE..].len()
+        {
+            return false;
+        }
+
+        // Quick check for channel number.
+        let num = ChannelNumber(u16::from_be_bytes([buf[0], buf[1]]));
+        num.is_valid()
+    }
+}
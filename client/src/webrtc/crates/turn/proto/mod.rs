@@ -0,0 +1,253 @@
+pub(crate) mod chandata;
+
+use crate::webrtc::stun::attributes::*;
+use crate::webrtc::stun::checks::*;
+use crate::webrtc::stun::message::*;
+use crate::webrtc::stun::xoraddr::*;
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+type StunResult<T> = std::result::Result<T, crate::webrtc::stun::Error>;
+
+// Protocol is IANA assigned protocol number.
+#[derive(PartialEq, Eq, Default, Debug, Clone, Copy)]
+pub(crate) struct Protocol(pub(crate) u8);
+
+// PROTO_UDP is IANA assigned protocol number for UDP.
+pub(crate) const PROTO_UDP: Protocol = Protocol(17);
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let others = format!("{}", self.0);
+        let s = match *self {
+            PROTO_UDP => "UDP",
+            _ => others.as_str(),
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+const REQUESTED_TRANSPORT_SIZE: usize = 4;
+
+// RequestedTransport represents REQUESTED-TRANSPORT attribute.
+//
+// This attribute is used by the client to request a specific transport
+// protocol for the allocated transport address.
+//
+// RFC 5766 Section 14.7
+#[derive(Default, Debug, PartialEq, Eq)]
+pub(crate) struct RequestedTransport {
+    pub(crate) protocol: Protocol,
+}
+
+impl Setter for RequestedTransport {
+    // add_to adds REQUESTED-TRANSPORT to message.
+    fn add_to(&self, m: &mut Message) -> StunResult<()> {
+        let mut v = vec![0; REQUESTED_TRANSPORT_SIZE];
+        v[0] = self.protocol.0;
+        // b[1:4] is RFFU = 0.
+        // The RFFU field MUST be set to zero on transmission and MUST be
+        // ignored on reception. It is reserved for future uses.
+        m.add(ATTR_REQUESTED_TRANSPORT, &v);
+        Ok(())
+    }
+}
+
+const LIFETIME_SIZE: usize = 4; // 4 bytes, 32 bits
+
+// DEFAULT_LIFETIME in RFC 5766 is 10 minutes.
+//
+// RFC 5766 Section 2.2
+pub(crate) const DEFAULT_LIFETIME: Duration = Duration::from_secs(10 * 60);
+
+// Lifetime represents LIFETIME attribute.
+//
+// The LIFETIME attribute represents the duration for which the server
+// will maintain an allocation in the absence of a refresh. The value
+// portion of this attribute is 4-bytes long and consists of a 32-bit
+// unsigned integral value representing the number of seconds remaining
+// until expiration.
+//
+// RFC 5766 Section 14.2
+#[derive(Default, Debug, PartialEq, Eq)]
+pub(crate) struct Lifetime(pub(crate) Duration);
+
+impl Setter for Lifetime {
+    // add_to adds LIFETIME to message.
+    fn add_to(&self, m: &mut Message) -> StunResult<()> {
+        let v = (self.0.as_secs() as u32).to_be_bytes();
+        m.add(ATTR_LIFETIME, &v);
+        Ok(())
+    }
+}
+
+impl Getter for Lifetime {
+    // get_from decodes LIFETIME from message.
+    fn get_from(&mut self, m: &Message) -> StunResult<()> {
+        let v = m.get(ATTR_LIFETIME)?;
+
+        check_size(ATTR_LIFETIME, v.len(), LIFETIME_SIZE)?;
+
+        let seconds = u32::from_be_bytes([v[0], v[1], v[2], v[3]]);
+        self.0 = Duration::from_secs(seconds as u64);
+
+        Ok(())
+    }
+}
+
+// Data represents DATA attribute.
+//
+// The DATA attribute is present in all Send and Data indications. The
+// value portion of this attribute is variable length and consists of
+// the application data (that is, the data that would immediately follow
+// the UDP header if the data was been sent directly between the client
+// and the peer).
+//
+// RFC 5766 Section 14.4
+#[derive(Default, Debug, PartialEq, Eq)]
+pub(crate) struct Data(pub(crate) Vec<u8>);
+
+impl Setter for Data {
+    // add_to adds DATA to message.
+    fn add_to(&self, m: &mut Message) -> StunResult<()> {
+        m.add(ATTR_DATA, &self.0);
+        Ok(())
+    }
+}
+
+impl Getter for Data {
+    // get_from decodes DATA from message.
+    fn get_from(&mut self, m: &Message) -> StunResult<()> {
+        self.0 = m.get(ATTR_DATA)?;
+        Ok(())
+    }
+}
+
+// ChannelNumber represents CHANNEL-NUMBER attribute. Encoded as uint16.
+//
+// The CHANNEL-NUMBER attribute contains the number of the channel.
+//
+// RFC 5766 Section 14.1
+#[derive(Default, Eq, PartialEq, Debug, Copy, Clone, Hash)]
+pub(crate) struct ChannelNumber(pub(crate) u16);
+
+impl fmt::Display for ChannelNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+const CHANNEL_NUMBER_SIZE: usize = 4;
+
+// See https://tools.ietf.org/html/rfc5766#section-11:
+//
+// 0x4000 through 0x7FFF: These values are the allowed channel
+// numbers (16,383 possible values).
+pub(crate) const MIN_CHANNEL_NUMBER: u16 = 0x4000;
+pub(crate) const MAX_CHANNEL_NUMBER: u16 = 0x7FFF;
+
+impl Setter for ChannelNumber {
+    // add_to adds CHANNEL-NUMBER to message.
+    fn add_to(&self, m: &mut Message) -> StunResult<()> {
+        let mut v = vec![0; CHANNEL_NUMBER_SIZE];
+        v[..2].copy_from_slice(&self.0.to_be_bytes());
+        // v[2:4] are zeroes (RFFU = 0)
+        m.add(ATTR_CHANNEL_NUMBER, &v);
+        Ok(())
+    }
+}
+
+impl ChannelNumber {
+    // is_valid returns true if c in [0x4000, 0x7FFF].
+    pub(crate) fn is_valid(&self) -> bool {
+        self.0 >= MIN_CHANNEL_NUMBER && self.0 <= MAX_CHANNEL_NUMBER
+    }
+}
+
+// PeerAddress implements XOR-PEER-ADDRESS attribute.
+//
+// The XOR-PEER-ADDRESS specifies the address and port of the peer as
+// seen from the TURN server. (For example, the peer's server-reflexive
+// transport address if the peer is behind a NAT.)
+//
+// RFC 5766 Section 14.3
+#[derive(PartialEq, Eq, Debug)]
+pub(crate) struct PeerAddress {
+    pub(crate) ip: IpAddr,
+    pub(crate) port: u16,
+}
+
+impl Default for PeerAddress {
+    fn default() -> Self {
+        PeerAddress {
+            ip: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            port: 0,
+        }
+    }
+}
+
+impl From<SocketAddr> for PeerAddress {
+    fn from(addr: SocketAddr) -> Self {
+        PeerAddress {
+            ip: addr.ip(),
+            port: addr.port(),
+        }
+    }
+}
+
+impl Setter for PeerAddress {
+    // add_to adds XOR-PEER-ADDRESS to message.
+    fn add_to(&self, m: &mut Message) -> StunResult<()> {
+        let a = XorMappedAddress {
+            ip: self.ip,
+            port: self.port,
+        };
+        a.add_to_as(m, ATTR_XOR_PEER_ADDRESS)
+    }
+}
+
+impl Getter for PeerAddress {
+    // get_from decodes XOR-PEER-ADDRESS from message.
+    fn get_from(&mut self, m: &Message) -> StunResult<()> {
+        let mut a = XorMappedAddress::default();
+        a.get_from_as(m, ATTR_XOR_PEER_ADDRESS)?;
+        self.ip = a.ip;
+        self.port = a.port;
+        Ok(())
+    }
+}
+
+// RelayedAddress implements XOR-RELAYED-ADDRESS attribute.
+//
+// It specifies the address and port that the server allocated to the
+// client. It is encoded in the same way as XOR-MAPPED-ADDRESS.
+//
+// RFC 5766 Section 14.5
+#[derive(PartialEq, Eq, Debug)]
+pub(crate) struct RelayedAddress {
+    pub(crate) ip: IpAddr,
+    pub(crate) port: u16,
+}
+
+impl Default for RelayedAddress {
+    fn default() -> Self {
+        RelayedAddress {
+            ip: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            port: 0,
+        }
+    }
+}
+
+impl Getter for RelayedAddress {
+    // get_from decodes XOR-RELAYED-ADDRESS from message.
+    fn get_from(&mut self, m: &Message) -> StunResult<()> {
+        let mut a = XorMappedAddress::default();
+        a.get_from_as(m, ATTR_XOR_RELAYED_ADDRESS)?;
+        self.ip = a.ip;
+        self.port = a.port;
+        Ok(())
+    }
+}
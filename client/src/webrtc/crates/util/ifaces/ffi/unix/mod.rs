@@ -117,19 +117,7 @@ pub(crate) fn nix_socketaddr_to_sockaddr(
             let sa: *const nix::sys::socket::sockaddr_in6 = sa as *const nix::libc::sockaddr_in6;
             let sa = &unsafe { *sa };
             let (addr, port) = (sa.sin6_addr.s6_addr, sa.sin6_port);
-            (
-                IpAddr::V6(net::Ipv6Addr::new(
-                    addr[0] as u16,
-                    addr[1] as u16,
-                    addr[2] as u16,
-                    addr[3] as u16,
-                    addr[4] as u16,
-                    addr[5] as u16,
-                    addr[6] as u16,
-                    addr[7] as u16,
-                )),
-                port,
-            )
+            (IpAddr::V6(net::Ipv6Addr::from(addr)), port)
         }
         _ => return None,
     };
@@ -31,4 +31,4 @@ pub(crate) use crate::webrtc::util::buffer::Buffer;
 pub(crate) use crate::webrtc::util::conn::Conn;
 
 mod error;
-pub(crate) use error::Error;
+pub(crate) use error::{Error, IoError};
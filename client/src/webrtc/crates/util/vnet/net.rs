@@ -249,7 +249,7 @@ pub(crate) struct NetConfig {
 // up to the transport (UDP / TCP) layer.
 pub(crate) enum Net {
     VNet(Arc<Mutex<VNet>>),
-    Ifs(Vec<Interface>),
+    Ifs,
 }
 
 impl Net {
@@ -294,43 +294,19 @@ impl Net {
 
             Net::VNet(Arc::new(Mutex::new(vnet)))
         } else {
-            let interfaces = match ifaces::ifaces() {
-                Ok(ifs) => ifs,
-                Err(_) => vec![],
-            };
-
-            let mut m: HashMap<String, Vec<IpNet>> = HashMap::new();
-            for iface in interfaces {
-                if let Some(addrs) = m.get_mut(&iface.name) {
-                    if let Some(addr) = iface.addr {
-                        if let Ok(inet) = Interface::convert(addr, iface.mask) {
-                            addrs.push(inet);
-                        }
-                    }
-                } else if let Some(addr) = iface.addr {
-                    if let Ok(inet) = Interface::convert(addr, iface.mask) {
-                        m.insert(iface.name, vec![inet]);
-                    }
-                }
-            }
-
-            let mut ifs = vec![];
-            for (name, addrs) in m.into_iter() {
-                ifs.push(Interface::new(name, addrs));
-            }
-
-            Net::Ifs(ifs)
+            Net::Ifs
         }
     }
 
-    // Interfaces returns a list of the system's network interfaces.
+    // Interfaces returns a list of the network interfaces. The system's are
+    // read again on every call, so that changes to them are seen.
     pub(crate) async fn get_interfaces(&self) -> Vec<Interface> {
         match self {
             Net::VNet(vnet) => {
                 let net = vnet.lock().await;
                 net.get_interfaces().to_vec()
             }
-            Net::Ifs(ifs) => ifs.clone(),
+            Net::Ifs => system_interfaces(),
         }
     }
 
@@ -338,7 +314,7 @@ impl Net {
     pub(crate) fn is_virtual(&self) -> bool {
         match self {
             Net::VNet(_) => true,
-            Net::Ifs(_) => false,
+            Net::Ifs => false,
         }
     }
 
@@ -348,7 +324,37 @@ impl Net {
                 let net = vnet.lock().await;
                 net.bind(addr).await
             }
-            Net::Ifs(_) => Ok(Arc::new(UdpSocket::bind(addr).await?)),
+            Net::Ifs => Ok(Arc::new(UdpSocket::bind(addr).await?)),
         }
     }
 }
+
+// the system's interfaces, with the addresses of each grouped under its name
+fn system_interfaces() -> Vec<Interface> {
+    let interfaces = match ifaces::ifaces() {
+        Ok(ifs) => ifs,
+        Err(_) => vec![],
+    };
+
+    let mut m: HashMap<String, Vec<IpNet>> = HashMap::new();
+    for iface in interfaces {
+        if let Some(addrs) = m.get_mut(&iface.name) {
+            if let Some(addr) = iface.addr {
+                if let Ok(inet) = Interface::convert(addr, iface.mask) {
+                    addrs.push(inet);
+                }
+            }
+        } else if let Some(addr) = iface.addr {
+            if let Ok(inet) = Interface::convert(addr, iface.mask) {
+                m.insert(iface.name, vec![inet]);
+            }
+        }
+    }
+
+    let mut ifs = vec![];
+    for (name, addrs) in m.into_iter() {
+        ifs.push(Interface::new(name, addrs));
+    }
+
+    ifs
+}
@@ -0,0 +1,20 @@
+/// DataChannelConfig can be used to configure properties of the underlying
+/// channel such as data reliability.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RTCDataChannelInit {
+    /// ordered indicates if data is allowed to be delivered out of order. The
+    /// default value of true, guarantees that data will be delivered in order.
+    pub(crate) ordered: Option<bool>,
+
+    /// max_packet_life_time limits the time (in milliseconds) during which the
+    /// channel will transmit or retransmit data if not acknowledged.
+    pub(crate) max_packet_life_time: Option<u16>,
+
+    /// max_retransmits limits the number of times a channel will retransmit data
+    /// if not successfully delivered.
+    pub(crate) max_retransmits: Option<u16>,
+
+    /// priority is the DCEP priority announced for the channel, see
+    /// <https://www.rfc-editor.org/rfc/rfc8832#section-8.2.2>
+    pub(crate) priority: Option<u16>,
+}
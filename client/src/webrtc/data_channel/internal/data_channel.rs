@@ -8,6 +8,7 @@ use crate::webrtc::util::marshal::*;
 
 use bytes::{Buf, Bytes};
 use derive_builder::Builder;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 /// Config is used to configure the data channel.
@@ -17,12 +18,24 @@ pub(crate) struct Config {
     pub(crate) label: String,
     #[builder(default)]
     pub(crate) protocol: String,
+    #[builder(default)]
+    pub(crate) channel_type: ChannelType,
+    #[builder(default)]
+    pub(crate) priority: u16,
+    #[builder(default)]
+    pub(crate) reliability_parameter: u32,
 }
 
 /// DataChannel represents a data channel
 #[derive(Debug, Default, Clone)]
 pub(crate) struct DataChannel {
     stream: Arc<Stream>,
+
+    // stats
+    messages_sent: Arc<AtomicUsize>,
+    messages_received: Arc<AtomicUsize>,
+    bytes_sent: Arc<AtomicUsize>,
+    bytes_received: Arc<AtomicUsize>,
 }
 
 impl DataChannel {
@@ -46,7 +59,20 @@ impl DataChannel {
 
     /// Client opens a data channel over an SCTP stream
     async fn client(stream: Arc<Stream>, config: Config) -> Result<Self> {
+        let (unordered, reliability_type) = match config.channel_type {
+            ChannelType::Reliable => (false, ReliabilityType::Reliable),
+            ChannelType::ReliableUnordered => (true, ReliabilityType::Reliable),
+            ChannelType::PartialReliableRexmit => (false, ReliabilityType::Rexmit),
+            ChannelType::PartialReliableRexmitUnordered => (true, ReliabilityType::Rexmit),
+            ChannelType::PartialReliableTimed => (false, ReliabilityType::Timed),
+            ChannelType::PartialReliableTimedUnordered => (true, ReliabilityType::Timed),
+        };
+        stream.set_reliability_params(unordered, reliability_type, config.reliability_parameter);
+
         let msg = Message::DataChannelOpen(DataChannelOpen {
+            channel_type: config.channel_type,
+            priority: config.priority,
+            reliability_parameter: config.reliability_parameter,
             label: config.label.bytes().collect(),
             protocol: config.protocol.bytes().collect(),
         })
@@ -104,6 +130,9 @@ impl DataChannel {
                 _ => {}
             };
 
+            self.messages_received.fetch_add(1, Ordering::SeqCst);
+            self.bytes_received.fetch_add(n, Ordering::SeqCst);
+
             return Ok((n, is_string));
         }
     }
@@ -120,8 +149,8 @@ impl DataChannel {
             }
             _ => {
                 // Note: DATA_CHANNEL_OPEN message is handled inside Server() method.
-                // Therefore, the message will not reach here.
-                panic!("client should only ever receive a DataChannelAck message");
+                // Therefore, the message should not reach here.
+                log::warn!("client should only ever receive a DataChannelAck message");
             }
         };
 
@@ -151,19 +180,52 @@ impl DataChannel {
             (true, _) => PayloadProtocolIdentifier::String,
         };
 
-        if data_len == 0 {
+        let n = if data_len == 0 {
             let _ = self
                 .stream
                 .write_sctp(&Bytes::from_static(&[0]), ppi)
                 .await?;
-            Ok(0)
+            0
         } else {
-            Ok(self.stream.write_sctp(data, ppi).await?)
-        }
+            self.stream.write_sctp(data, ppi).await?
+        };
+
+        self.messages_sent.fetch_add(1, Ordering::SeqCst);
+        self.bytes_sent.fetch_add(n, Ordering::SeqCst);
+
+        Ok(n)
     }
 
     pub(crate) async fn close(&self) {
-        self.stream.close().await.unwrap();
+        if let Err(err) = self.stream.close().await {
+            log::warn!("Failed to close data channel stream: {}", err);
+        }
+    }
+
+    /// messages_sent returns the number of messages sent
+    pub(crate) fn messages_sent(&self) -> usize {
+        self.messages_sent.load(Ordering::SeqCst)
+    }
+
+    /// messages_received returns the number of messages received
+    pub(crate) fn messages_received(&self) -> usize {
+        self.messages_received.load(Ordering::SeqCst)
+    }
+
+    /// bytes_sent returns the number of bytes sent
+    pub(crate) fn bytes_sent(&self) -> usize {
+        self.bytes_sent.load(Ordering::SeqCst)
+    }
+
+    /// bytes_received returns the number of bytes received
+    pub(crate) fn bytes_received(&self) -> usize {
+        self.bytes_received.load(Ordering::SeqCst)
+    }
+
+    /// BufferedAmount returns the number of bytes of data currently queued to be
+    /// sent over this stream.
+    pub(crate) fn buffered_amount(&self) -> usize {
+        self.stream.buffered_amount()
     }
 
     /// SetBufferedAmountLowThreshold is used to update the threshold.
@@ -4,6 +4,105 @@ use crate::webrtc::data_channel::internal::error::Error;
 type Result<T> = std::result::Result<T, crate::webrtc::util::Error>;
 
 const CHANNEL_OPEN_HEADER_LEN: usize = 11;
+const CHANNEL_TYPE_LEN: usize = 1;
+
+/// Channel priorities defined in <https://www.rfc-editor.org/rfc/rfc8832#section-8.2.2>
+pub(crate) const CHANNEL_PRIORITY_BELOW_NORMAL: u16 = 128;
+pub(crate) const CHANNEL_PRIORITY_NORMAL: u16 = 256;
+pub(crate) const CHANNEL_PRIORITY_HIGH: u16 = 512;
+pub(crate) const CHANNEL_PRIORITY_EXTRA_HIGH: u16 = 1024;
+
+/// The channel types defined in <https://www.rfc-editor.org/rfc/rfc8832#section-5.1>
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Default)]
+pub(crate) enum ChannelType {
+    /// Reliable ordered delivery. The reliability parameter is ignored.
+    #[default]
+    Reliable,
+    /// Reliable unordered delivery. The reliability parameter is ignored.
+    ReliableUnordered,
+    /// Ordered delivery, each message is retransmitted at most the
+    /// reliability parameter number of times.
+    PartialReliableRexmit,
+    /// Unordered delivery, each message is retransmitted at most the
+    /// reliability parameter number of times.
+    PartialReliableRexmitUnordered,
+    /// Ordered delivery, each message is retransmitted only within the
+    /// reliability parameter number of milliseconds.
+    PartialReliableTimed,
+    /// Unordered delivery, each message is retransmitted only within the
+    /// reliability parameter number of milliseconds.
+    PartialReliableTimedUnordered,
+}
+
+const CHANNEL_TYPE_RELIABLE: u8 = 0x00;
+const CHANNEL_TYPE_RELIABLE_UNORDERED: u8 = 0x80;
+const CHANNEL_TYPE_PARTIAL_RELIABLE_REXMIT: u8 = 0x01;
+const CHANNEL_TYPE_PARTIAL_RELIABLE_REXMIT_UNORDERED: u8 = 0x81;
+const CHANNEL_TYPE_PARTIAL_RELIABLE_TIMED: u8 = 0x02;
+const CHANNEL_TYPE_PARTIAL_RELIABLE_TIMED_UNORDERED: u8 = 0x82;
+
+impl MarshalSize for ChannelType {
+    fn marshal_size(&self) -> usize {
+        CHANNEL_TYPE_LEN
+    }
+}
+
+impl Marshal for ChannelType {
+    fn marshal_to(&self, mut buf: &mut [u8]) -> Result<usize> {
+        let required_len = self.marshal_size();
+        if buf.remaining_mut() < required_len {
+            return Err(Error::UnexpectedEndOfBuffer {
+                expected: required_len,
+                actual: buf.remaining_mut(),
+            }
+            .into());
+        }
+
+        let b = match self {
+            Self::Reliable => CHANNEL_TYPE_RELIABLE,
+            Self::ReliableUnordered => CHANNEL_TYPE_RELIABLE_UNORDERED,
+            Self::PartialReliableRexmit => CHANNEL_TYPE_PARTIAL_RELIABLE_REXMIT,
+            Self::PartialReliableRexmitUnordered => CHANNEL_TYPE_PARTIAL_RELIABLE_REXMIT_UNORDERED,
+            Self::PartialReliableTimed => CHANNEL_TYPE_PARTIAL_RELIABLE_TIMED,
+            Self::PartialReliableTimedUnordered => CHANNEL_TYPE_PARTIAL_RELIABLE_TIMED_UNORDERED,
+        };
+
+        buf.put_u8(b);
+
+        Ok(CHANNEL_TYPE_LEN)
+    }
+}
+
+impl Unmarshal for ChannelType {
+    fn unmarshal<B>(buf: &mut B) -> Result<Self>
+    where
+        B: Buf,
+    {
+        let required_len = CHANNEL_TYPE_LEN;
+        if buf.remaining() < required_len {
+            return Err(Error::UnexpectedEndOfBuffer {
+                expected: required_len,
+                actual: buf.remaining(),
+            }
+            .into());
+        }
+
+        let b = buf.get_u8();
+        match b {
+            CHANNEL_TYPE_RELIABLE => Ok(Self::Reliable),
+            CHANNEL_TYPE_RELIABLE_UNORDERED => Ok(Self::ReliableUnordered),
+            CHANNEL_TYPE_PARTIAL_RELIABLE_REXMIT => Ok(Self::PartialReliableRexmit),
+            CHANNEL_TYPE_PARTIAL_RELIABLE_REXMIT_UNORDERED => {
+                Ok(Self::PartialReliableRexmitUnordered)
+            }
+            CHANNEL_TYPE_PARTIAL_RELIABLE_TIMED => Ok(Self::PartialReliableTimed),
+            CHANNEL_TYPE_PARTIAL_RELIABLE_TIMED_UNORDERED => {
+                Ok(Self::PartialReliableTimedUnordered)
+            }
+            _ => Err(Error::InvalidChannelType(b).into()),
+        }
+    }
+}
 
 /// The data-part of an data-channel OPEN message without the message type.
 ///
@@ -30,6 +129,9 @@ const CHANNEL_OPEN_HEADER_LEN: usize = 11;
 /// ```
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub(crate) struct DataChannelOpen {
+    pub(crate) channel_type: ChannelType,
+    pub(crate) priority: u16,
+    pub(crate) reliability_parameter: u32,
     pub(crate) label: Vec<u8>,
     pub(crate) protocol: Vec<u8>,
 }
@@ -54,6 +156,10 @@ impl Marshal for DataChannelOpen {
             .into());
         }
 
+        let n = self.channel_type.marshal_to(buf)?;
+        buf = &mut buf[n..];
+        buf.put_u16(self.priority);
+        buf.put_u32(self.reliability_parameter);
         buf.put_u16(self.label.len() as u16);
         buf.put_u16(self.protocol.len() as u16);
         buf.put_slice(self.label.as_slice());
@@ -76,6 +182,9 @@ impl Unmarshal for DataChannelOpen {
             .into());
         }
 
+        let channel_type = ChannelType::unmarshal(buf)?;
+        let priority = buf.get_u16();
+        let reliability_parameter = buf.get_u32();
         let label_len = buf.get_u16() as usize;
         let protocol_len = buf.get_u16() as usize;
 
@@ -94,6 +203,12 @@ impl Unmarshal for DataChannelOpen {
         buf.copy_to_slice(&mut label[..]);
         buf.copy_to_slice(&mut protocol[..]);
 
-        Ok(Self { label, protocol })
+        Ok(Self {
+            channel_type,
+            priority,
+            reliability_parameter,
+            label,
+            protocol,
+        })
     }
 }
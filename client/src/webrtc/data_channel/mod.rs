@@ -1,3 +1,4 @@
+pub(crate) mod data_channel_init;
 pub(crate) mod data_channel_state;
 pub(crate) mod internal;
 
@@ -6,9 +7,13 @@ use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
 use std::sync::{Arc, Weak};
 
+use crate::webrtc::data_channel::internal::message::message_channel_open::{
+    ChannelType, CHANNEL_PRIORITY_NORMAL,
+};
 use crate::webrtc::sctp::stream::OnBufferedAmountLowFn;
 use tokio::sync::Mutex;
 
+use data_channel_init::RTCDataChannelInit;
 use data_channel_state::RTCDataChannelState;
 
 use crate::webrtc::error::{Error, OnErrorHdlrFn, Result};
@@ -23,6 +28,11 @@ pub(crate) type OnOpenHdlrFn =
 #[derive(Default)]
 pub(crate) struct RTCDataChannel {
     label: String,
+    id: Mutex<Option<u16>>,
+    priority: u16,
+    ordered: bool,
+    max_packet_lifetime: Option<u16>,
+    max_retransmits: Option<u16>,
     protocol: String,
 
     ready_state: Arc<AtomicU8>, // DataChannelState
@@ -47,9 +57,13 @@ pub(crate) struct RTCDataChannel {
 
 impl RTCDataChannel {
     // create the DataChannel object before the networking is set up.
-    pub(crate) fn new(label: &str, protocol: &str) -> Self {
+    pub(crate) fn new(label: &str, protocol: &str, options: RTCDataChannelInit) -> Self {
         RTCDataChannel {
             label: label.to_string(),
+            priority: options.priority.unwrap_or(CHANNEL_PRIORITY_NORMAL),
+            ordered: options.ordered.unwrap_or(true),
+            max_packet_lifetime: options.max_packet_life_time,
+            max_retransmits: options.max_retransmits,
             protocol: protocol.to_string(),
             ready_state: Arc::new(AtomicU8::new(RTCDataChannelState::Connecting as u8)),
             detach_called: Arc::new(AtomicBool::new(false)),
@@ -69,13 +83,28 @@ impl RTCDataChannel {
                 }
             }
 
+            // stream ids are allocated when the channel opens, see
+            // <https://www.rfc-editor.org/rfc/rfc8832#section-6>
+            let id = sctp_transport
+                .generate_data_channel_id(sctp_transport.dtls_transport.role())
+                .await?;
+            {
+                let mut dc_id = self.id.lock().await;
+                *dc_id = Some(id);
+            }
+
+            let (channel_type, reliability_parameter) = self.channel_type();
             let cfg = crate::webrtc::internal::data_channel::Config {
                 label: self.label.clone(),
                 protocol: self.protocol.clone(),
+                channel_type,
+                priority: self.priority,
+                reliability_parameter,
             };
 
-            let dc = crate::webrtc::internal::data_channel::DataChannel::dial(&association, 0, cfg)
-                .await?;
+            let dc =
+                crate::webrtc::internal::data_channel::DataChannel::dial(&association, id, cfg)
+                    .await?;
 
             // buffered_amount_low_threshold and on_buffered_amount_low might be set earlier
             dc.set_buffered_amount_low_threshold(
@@ -96,6 +125,37 @@ impl RTCDataChannel {
         }
     }
 
+    /// channel_type maps the ordered and max_retransmits/max_packet_lifetime
+    /// options to a DCEP channel type and its reliability parameter.
+    fn channel_type(&self) -> (ChannelType, u32) {
+        match (self.max_retransmits, self.max_packet_lifetime) {
+            (Some(max_retransmits), _) => {
+                let channel_type = if self.ordered {
+                    ChannelType::PartialReliableRexmit
+                } else {
+                    ChannelType::PartialReliableRexmitUnordered
+                };
+                (channel_type, max_retransmits as u32)
+            }
+            (None, Some(max_packet_lifetime)) => {
+                let channel_type = if self.ordered {
+                    ChannelType::PartialReliableTimed
+                } else {
+                    ChannelType::PartialReliableTimedUnordered
+                };
+                (channel_type, max_packet_lifetime as u32)
+            }
+            (None, None) => {
+                let channel_type = if self.ordered {
+                    ChannelType::Reliable
+                } else {
+                    ChannelType::ReliableUnordered
+                };
+                (channel_type, 0)
+            }
+        }
+    }
+
     /// on_open sets an event handler which is invoked when
     /// the underlying data transport has been established (or re-established).
     pub(crate) async fn on_open(&self, f: OnOpenHdlrFn) {
@@ -171,6 +231,20 @@ impl RTCDataChannel {
         }
     }
 
+    /// data_channel returns the underlying data channel, it is None until the
+    /// channel is opened.
+    pub(crate) async fn data_channel(
+        &self,
+    ) -> Option<Arc<crate::webrtc::internal::data_channel::DataChannel>> {
+        self.data_channel.lock().await.clone()
+    }
+
+    /// id represents the SCTP stream id of the channel, it is None until the
+    /// channel is opened.
+    pub(crate) async fn id(&self) -> Option<u16> {
+        *self.id.lock().await
+    }
+
     /// ready_state represents the state of the DataChannel object.
     pub(crate) fn ready_state(&self) -> RTCDataChannelState {
         self.ready_state.load(Ordering::SeqCst).into()
@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
 
 /// DTLSFingerprint specifies the hash function algorithm and certificate
 /// fingerprint as described in <https://tools.ietf.org/html/rfc4572>.
@@ -13,3 +15,66 @@ pub(crate) struct RTCDtlsFingerprint {
     /// <https://tools.ietf.org/html/rfc4572#section-5>.
     pub(crate) value: String,
 }
+
+impl RTCDtlsFingerprint {
+    /// matches reports whether the DER encoded certificate hashes to this
+    /// fingerprint. Unsupported hash algorithms never match.
+    pub(crate) fn matches(&self, certificate: &[u8]) -> bool {
+        let hashed = match self.algorithm.to_lowercase().as_str() {
+            "sha-1" => Sha1::digest(certificate).to_vec(),
+            "sha-256" => Sha256::digest(certificate).to_vec(),
+            "sha-384" => Sha384::digest(certificate).to_vec(),
+            "sha-512" => Sha512::digest(certificate).to_vec(),
+            algorithm => {
+                log::warn!("unsupported fingerprint algorithm: {}", algorithm);
+                return false;
+            }
+        };
+
+        let value: Vec<String> = hashed.iter().map(|x| format!("{:02x}", x)).collect();
+        value.join(":").eq_ignore_ascii_case(&self.value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CERTIFICATE: &[u8] = b"not really a DER certificate";
+
+    fn sha256_fingerprint(certificate: &[u8]) -> String {
+        let value: Vec<String> = Sha256::digest(certificate)
+            .iter()
+            .map(|x| format!("{:02x}", x))
+            .collect();
+        value.join(":")
+    }
+
+    #[test]
+    fn test_matches_sha256() {
+        let fingerprint = RTCDtlsFingerprint {
+            algorithm: "sha-256".to_owned(),
+            value: sha256_fingerprint(CERTIFICATE),
+        };
+        assert!(fingerprint.matches(CERTIFICATE));
+        assert!(!fingerprint.matches(b"another certificate"));
+    }
+
+    #[test]
+    fn test_matches_ignores_case() {
+        let fingerprint = RTCDtlsFingerprint {
+            algorithm: "SHA-256".to_owned(),
+            value: sha256_fingerprint(CERTIFICATE).to_uppercase(),
+        };
+        assert!(fingerprint.matches(CERTIFICATE));
+    }
+
+    #[test]
+    fn test_unsupported_algorithm_never_matches() {
+        let fingerprint = RTCDtlsFingerprint {
+            algorithm: "md5".to_owned(),
+            value: sha256_fingerprint(CERTIFICATE),
+        };
+        assert!(!fingerprint.matches(CERTIFICATE));
+    }
+}
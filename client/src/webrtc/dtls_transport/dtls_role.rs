@@ -35,6 +35,17 @@ impl Default for DTLSRole {
     }
 }
 
+impl From<u8> for DTLSRole {
+    fn from(v: u8) -> Self {
+        match v {
+            1 => DTLSRole::Auto,
+            2 => DTLSRole::Client,
+            3 => DTLSRole::Server,
+            _ => DTLSRole::Unspecified,
+        }
+    }
+}
+
 impl fmt::Display for DTLSRole {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
@@ -5,11 +5,13 @@ use std::sync::Arc;
 
 use crate::webrtc::dtls::config::ClientAuthType;
 use crate::webrtc::dtls::conn::DTLSConn;
+use crate::webrtc::dtls::handshaker::VerifyPeerCertificateFn;
 use crate::webrtc::util::Conn;
 use tokio::sync::Mutex;
 
 use dtls_role::*;
 
+use crate::webrtc::dtls_transport::dtls_fingerprint::RTCDtlsFingerprint;
 use crate::webrtc::dtls_transport::dtls_parameters::DTLSParameters;
 use crate::webrtc::dtls_transport::dtls_transport_state::RTCDtlsTransportState;
 use crate::webrtc::error::{Error, Result};
@@ -17,6 +19,7 @@ use crate::webrtc::ice_transport::ice_transport_state::RTCIceTransportState;
 use crate::webrtc::ice_transport::RTCIceTransport;
 use crate::webrtc::mux::mux_func::match_dtls;
 use crate::webrtc::peer_connection::certificate::RTCCertificate;
+use crate::webrtc::setting_engine::SettingEngine;
 
 pub(crate) mod dtls_fingerprint;
 pub(crate) mod dtls_parameters;
@@ -40,18 +43,23 @@ pub(crate) struct RTCDtlsTransport {
 
     pub(crate) remote_parameters: Mutex<DTLSParameters>,
     pub(crate) state: AtomicU8, //DTLSTransportState,
+    pub(crate) role: AtomicU8,  //DTLSRole,
     pub(crate) on_state_change_handler: Arc<Mutex<Option<OnDTLSTransportStateChangeHdlrFn>>>,
     pub(crate) conn: Mutex<Option<Arc<DTLSConn>>>,
+
+    pub(crate) setting_engine: Arc<SettingEngine>,
 }
 
 impl RTCDtlsTransport {
     pub(crate) fn new(
         ice_transport: Arc<RTCIceTransport>,
         certificates: Vec<RTCCertificate>,
+        setting_engine: Arc<SettingEngine>,
     ) -> Self {
         RTCDtlsTransport {
             ice_transport,
             certificates,
+            setting_engine,
             state: AtomicU8::new(RTCDtlsTransportState::New as u8),
             ..Default::default()
         }
@@ -71,6 +79,24 @@ impl RTCDtlsTransport {
         }
     }
 
+    /// on_state_change sets a handler that is fired when the DTLS
+    /// connection state changes.
+    pub(crate) async fn on_state_change(&self, f: OnDTLSTransportStateChangeHdlrFn) {
+        let mut on_state_change_handler = self.on_state_change_handler.lock().await;
+        *on_state_change_handler = Some(f);
+    }
+
+    /// role returns the DTLS role of the transport, as negotiated by the
+    /// a=setup attribute of the server's answer. Until the transport starts,
+    /// it is the DTLS client role the client takes unless the server asks
+    /// for it.
+    pub(crate) fn role(&self) -> DTLSRole {
+        match DTLSRole::from(self.role.load(Ordering::SeqCst)) {
+            DTLSRole::Unspecified => DTLSRole::Client,
+            role => role,
+        }
+    }
+
     /// state returns the current dtls_transport transport state.
     pub(crate) fn state(&self) -> RTCDtlsTransportState {
         self.state.load(Ordering::SeqCst).into()
@@ -86,6 +112,17 @@ impl RTCDtlsTransport {
             return Err(Error::ErrInvalidDTLSStart);
         }
 
+        let verify_peer_certificate = if self
+            .setting_engine
+            .disable_certificate_fingerprint_verification
+        {
+            log::warn!("DTLS certificate fingerprint verification is disabled");
+            None
+        } else {
+            Some(fingerprint_verifier(remote_parameters.fingerprints.clone()))
+        };
+
+        let remote_parameters_role = remote_parameters.role;
         {
             let mut rp = self.remote_parameters.lock().await;
             *rp = remote_parameters;
@@ -96,15 +133,29 @@ impl RTCDtlsTransport {
         } else {
             return Err(Error::ErrNonCertificate);
         };
+        // the client offers a=setup:actpass, so it is the DTLS server only
+        // when the server answers a=setup:active
+        let role = if remote_parameters_role == DTLSRole::Client {
+            DTLSRole::Server
+        } else {
+            DTLSRole::Client
+        };
+        self.role.store(role as u8, Ordering::SeqCst);
+
         self.state_change(RTCDtlsTransportState::Connecting).await;
 
         Ok((
-            DTLSRole::Client,
+            role,
             crate::webrtc::dtls::config::Config {
                 certificates: vec![certificate],
+                cipher_suites: self.setting_engine.dtls_cipher_suites.clone(),
+                flight_interval: self.setting_engine.dtls_flight_interval.unwrap_or_default(),
                 srtp_protection_profiles: vec![],
                 client_auth: ClientAuthType::RequireAnyClientCert,
+                // the server's certificate is self-signed, it is trusted
+                // through the fingerprint in its answer instead of a chain
                 insecure_skip_verify: true,
+                verify_peer_certificate,
                 ..Default::default()
             },
         ))
@@ -115,14 +166,14 @@ impl RTCDtlsTransport {
         let dtls_conn_result = if let Some(dtls_endpoint) =
             self.ice_transport.new_endpoint(Box::new(match_dtls)).await
         {
-            let (_, dtls_config) = self.prepare_transport(remote_parameters).await?;
+            let (role, dtls_config) = self.prepare_transport(remote_parameters).await?;
 
             // Connect as DTLS Client/Server, function is blocking and we
             // must not hold the DTLSTransport lock
             crate::webrtc::dtls::conn::DTLSConn::new(
                 dtls_endpoint as Arc<dyn Conn + Send + Sync>,
                 dtls_config,
-                true,
+                role == DTLSRole::Client,
                 None,
             )
             .await
@@ -149,6 +200,25 @@ impl RTCDtlsTransport {
         Ok(())
     }
 
+    /// stop stops and closes the DTLSTransport object.
+    pub(crate) async fn stop(&self) -> Result<()> {
+        // Try closing everything and collect the errors
+        let result = {
+            let mut conn = self.conn.lock().await;
+            if let Some(conn) = conn.take() {
+                conn.close().await.map_err(Error::from)
+            } else {
+                Ok(())
+            }
+        };
+
+        if self.state() != RTCDtlsTransportState::Failed {
+            self.state_change(RTCDtlsTransportState::Closed).await;
+        }
+
+        result
+    }
+
     pub(crate) fn ensure_ice_conn(&self) -> Result<()> {
         if self.ice_transport.state() == RTCIceTransportState::New {
             Err(Error::ErrICEConnectionNotStarted)
@@ -157,3 +227,68 @@ impl RTCDtlsTransport {
         }
     }
 }
+
+/// fingerprint_verifier accepts the remote certificate only if it matches
+/// one of the fingerprints the remote description advertised.
+fn fingerprint_verifier(fingerprints: Vec<RTCDtlsFingerprint>) -> VerifyPeerCertificateFn {
+    Arc::new(move |certificates: &[Vec<u8>], _| {
+        let Some(certificate) = certificates.first() else {
+            return Err(crate::webrtc::dtls::Error::ErrCertificateVerifyNoCertificate);
+        };
+
+        if fingerprints
+            .iter()
+            .any(|fingerprint| fingerprint.matches(certificate))
+        {
+            Ok(())
+        } else {
+            Err(crate::webrtc::dtls::Error::ErrNoMatchingCertificateFingerprint)
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha2::{Digest, Sha256};
+
+    const CERTIFICATE: &[u8] = b"not really a DER certificate";
+
+    fn verifier() -> VerifyPeerCertificateFn {
+        let value: Vec<String> = Sha256::digest(CERTIFICATE)
+            .iter()
+            .map(|x| format!("{:02x}", x))
+            .collect();
+        fingerprint_verifier(vec![
+            RTCDtlsFingerprint {
+                algorithm: "md5".to_owned(),
+                value: "00".to_owned(),
+            },
+            RTCDtlsFingerprint {
+                algorithm: "sha-256".to_owned(),
+                value: value.join(":"),
+            },
+        ])
+    }
+
+    #[test]
+    fn test_fingerprint_verifier_accepts_matching_certificate() {
+        assert_eq!(verifier()(&[CERTIFICATE.to_vec()], &[]), Ok(()));
+    }
+
+    #[test]
+    fn test_fingerprint_verifier_rejects_mismatching_certificate() {
+        assert_eq!(
+            verifier()(&[b"another certificate".to_vec()], &[]),
+            Err(crate::webrtc::dtls::Error::ErrNoMatchingCertificateFingerprint)
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_verifier_rejects_no_certificate() {
+        assert_eq!(
+            verifier()(&[], &[]),
+            Err(crate::webrtc::dtls::Error::ErrCertificateVerifyNoCertificate)
+        );
+    }
+}
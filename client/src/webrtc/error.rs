@@ -16,6 +16,11 @@ pub(crate) enum Error {
     #[error("connection closed")]
     ErrConnectionClosed,
 
+    /// ErrMaxDataChannelID indicates that the maximum number ID that could be
+    /// specified for a data channel has been exceeded.
+    #[error("maximum number ID for datachannel specified")]
+    ErrMaxDataChannelID,
+
     /// ErrNonCertificate indicates that there is no certificate
     #[error("no certificate")]
     ErrNonCertificate,
@@ -3,7 +3,10 @@ use std::sync::Arc;
 
 use crate::webrtc::ice::candidate::candidate_base::CandidateBaseConfig;
 use crate::webrtc::ice::candidate::candidate_host::CandidateHostConfig;
+use crate::webrtc::ice::candidate::candidate_relay::CandidateRelayConfig;
+use crate::webrtc::ice::candidate::candidate_server_reflexive::CandidateServerReflexiveConfig;
 use crate::webrtc::ice::candidate::Candidate;
+use crate::webrtc::ice::tcp_type::TcpType;
 use serde::{Deserialize, Serialize};
 
 use crate::webrtc::error::{Error, Result};
@@ -23,6 +26,7 @@ pub(crate) struct RTCIceCandidate {
     pub(crate) component: u16,
     pub(crate) related_address: String,
     pub(crate) related_port: u16,
+    pub(crate) tcp_type: String,
 }
 
 /// Conversion for ice_candidates
@@ -53,6 +57,7 @@ impl From<&Arc<dyn Candidate + Send + Sync>> for RTCIceCandidate {
             typ,
             related_address,
             related_port,
+            tcp_type: c.tcp_type().to_string(),
         }
     }
 }
@@ -69,7 +74,7 @@ impl RTCIceCandidate {
                         address: self.address.clone(),
                         port: self.port,
                         component: self.component,
-                        //tcp_type: ice.NewTCPType(c.TCPType),
+                        tcp_type: TcpType::from(self.tcp_type.as_str()),
                         foundation: self.foundation.clone(),
                         priority: self.priority,
                         ..Default::default()
@@ -78,11 +83,58 @@ impl RTCIceCandidate {
                 };
                 config.new_candidate_host().await?
             }
+            RTCIceCandidateType::Srflx => {
+                let config = CandidateServerReflexiveConfig {
+                    base_config: CandidateBaseConfig {
+                        candidate_id,
+                        network: self.protocol.to_string(),
+                        address: self.address.clone(),
+                        port: self.port,
+                        component: self.component,
+                        foundation: self.foundation.clone(),
+                        priority: self.priority,
+                        ..Default::default()
+                    },
+                    rel_addr: self.related_address.clone(),
+                    rel_port: self.related_port,
+                };
+                config.new_candidate_server_reflexive().await?
+            }
+            RTCIceCandidateType::Relay => {
+                let config = CandidateRelayConfig {
+                    base_config: CandidateBaseConfig {
+                        candidate_id,
+                        network: self.protocol.to_string(),
+                        address: self.address.clone(),
+                        port: self.port,
+                        component: self.component,
+                        foundation: self.foundation.clone(),
+                        priority: self.priority,
+                        ..Default::default()
+                    },
+                    rel_addr: self.related_address.clone(),
+                    rel_port: self.related_port,
+                };
+                config.new_candidate_relay().await?
+            }
             _ => return Err(Error::ErrICECandidateTypeUnknown),
         };
 
         Ok(c)
     }
+
+    /// to_json returns an ICECandidateInit
+    /// as indicated by the spec <https://w3c.github.io/webrtc-pc/#dom-rtcicecandidate-tojson>
+    pub(crate) async fn to_json(&self) -> Result<RTCIceCandidateInit> {
+        let candidate = self.to_ice().await?;
+
+        Ok(RTCIceCandidateInit {
+            candidate: format!("candidate:{}", candidate.marshal()),
+            sdp_mid: Some(String::new()),
+            sdp_mline_index: Some(0u16),
+            username_fragment: None,
+        })
+    }
 }
 
 impl fmt::Display for RTCIceCandidate {
@@ -3,10 +3,11 @@ use crate::webrtc::ice_transport::ice_candidate::*;
 use crate::webrtc::ice_transport::ice_gatherer_state::RTCIceGathererState;
 use crate::webrtc::ice_transport::ice_parameters::RTCIceParameters;
 
+use crate::webrtc::ice::agent::agent_config::{InterfaceFilterFn, IpFilterFn};
 use crate::webrtc::ice::agent::Agent;
 use crate::webrtc::ice::candidate::{Candidate, CandidateType};
 
-use crate::webrtc::ice::mdns::MulticastDnsMode;
+use crate::webrtc::setting_engine::SettingEngine;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::atomic::{AtomicU8, Ordering};
@@ -42,12 +43,15 @@ pub(crate) struct RTCIceGatherer {
 
     // Used for gathering_complete_promise
     pub(crate) on_gathering_complete_handler: Arc<Mutex<Option<OnGatheringCompleteHdlrFn>>>,
+
+    setting_engine: Arc<SettingEngine>,
 }
 
 impl RTCIceGatherer {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(setting_engine: Arc<SettingEngine>) -> Self {
         RTCIceGatherer {
             state: Arc::new(AtomicU8::new(RTCIceGathererState::New as u8)),
+            setting_engine,
             ..Default::default()
         }
     }
@@ -60,19 +64,30 @@ impl RTCIceGatherer {
             }
         }
 
-        let mut mdns_mode = MulticastDnsMode::Unspecified;
-        if mdns_mode != crate::webrtc::ice::mdns::MulticastDnsMode::Disabled
-            && mdns_mode != crate::webrtc::ice::mdns::MulticastDnsMode::QueryAndGather
-        {
-            // If enum is in state we don't recognized default to MulticastDNSModeQueryOnly
-            mdns_mode = crate::webrtc::ice::mdns::MulticastDnsMode::QueryOnly;
+        let mut urls = vec![];
+        for server in &self.setting_engine.ice_servers {
+            urls.extend(server.urls()?);
         }
 
+        let interface_filter = self
+            .setting_engine
+            .ice_interface_filter
+            .clone()
+            .map(|filter| Box::new(move |name: &str| (filter.0)(name)) as InterfaceFilterFn);
+        let ip_filter = self
+            .setting_engine
+            .ice_ip_filter
+            .clone()
+            .map(|filter| Box::new(move |ip| (filter.0)(ip)) as IpFilterFn);
+
         let mut config = crate::webrtc::ice::agent::agent_config::AgentConfig {
+            urls,
             lite: false,
-            disconnected_timeout: None,
-            failed_timeout: None,
-            keepalive_interval: None,
+            disconnected_timeout: self.setting_engine.ice_disconnected_timeout,
+            failed_timeout: self.setting_engine.ice_failed_timeout,
+            keepalive_interval: self.setting_engine.ice_keepalive_interval,
+            consent_timeout: self.setting_engine.ice_consent_timeout,
+            network_monitor_interval: self.setting_engine.ice_network_monitor_interval,
             candidate_types: Vec::new(),
             host_acceptance_min_wait: None,
             srflx_acceptance_min_wait: None,
@@ -80,15 +95,35 @@ impl RTCIceGatherer {
             relay_acceptance_min_wait: None,
             nat_1to1_ip_candidate_type: CandidateType::Unspecified,
             net: None,
-            multicast_dns_mode: mdns_mode,
+            multicast_dns_mode: self.setting_engine.ice_multicast_dns_mode,
+            interface_filter: Arc::new(interface_filter),
+            ip_filter: Arc::new(ip_filter),
+            port_min: self.setting_engine.ice_port_min,
+            port_max: self.setting_engine.ice_port_max,
+            local_ip: self.setting_engine.ice_local_ip,
+            udp_mux: self.setting_engine.ice_udp_mux.clone(),
             //TODO: TCPMux:                 self.setting_engine.iceTCPMux,
             //TODO: ProxyDialer:            self.setting_engine.iceProxyDialer,
             ..Default::default()
         };
 
-        let requested_network_types = crate::webrtc::ice::network_type::supported_network_types();
-
-        config.network_types.extend(requested_network_types);
+        if self.setting_engine.ice_network_types.is_empty() {
+            config
+                .network_types
+                .extend(crate::webrtc::ice::network_type::supported_network_types());
+        } else {
+            config
+                .network_types
+                .extend(&self.setting_engine.ice_network_types);
+        }
+        if self.setting_engine.ice_tcp {
+            let tcp_network_types: Vec<_> = config
+                .network_types
+                .iter()
+                .filter_map(|network_type| network_type.tcp_counterpart())
+                .collect();
+            config.network_types.extend(tcp_network_types);
+        }
 
         {
             let mut agent = self.agent.lock().await;
@@ -0,0 +1,36 @@
+use crate::webrtc::error::Result;
+use crate::webrtc::ice::url::{SchemeType, Url};
+use crate::webrtc::ice::Error as IceError;
+
+/// ICEServer describes a single STUN and TURN server that can be used by
+/// the ICEAgent to establish a connection with a peer.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub(crate) struct RTCIceServer {
+    pub(crate) urls: Vec<String>,
+    pub(crate) username: String,
+    pub(crate) credential: String,
+}
+
+impl RTCIceServer {
+    /// urls parses the server's URLs, handing the username and credential
+    /// to the TURN ones, which require them.
+    pub(crate) fn urls(&self) -> Result<Vec<Url>> {
+        let mut urls = vec![];
+
+        for raw in &self.urls {
+            let mut url = Url::parse_url(raw)?;
+            if url.scheme == SchemeType::Turn || url.scheme == SchemeType::Turns {
+                // https://www.w3.org/TR/webrtc/#set-the-configuration (step #11.3.2)
+                if self.username.is_empty() || self.credential.is_empty() {
+                    return Err(IceError::ErrTurnCredentialsMissing.into());
+                }
+                url.username = self.username.clone();
+                url.password = self.credential.clone();
+            }
+
+            urls.push(url);
+        }
+
+        Ok(urls)
+    }
+}
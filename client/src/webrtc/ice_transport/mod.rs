@@ -3,7 +3,7 @@ use std::pin::Pin;
 use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::Arc;
 
-use crate::webrtc::ice::candidate::Candidate;
+use crate::webrtc::ice::candidate::{Candidate, CandidatePair};
 use crate::webrtc::ice::state::ConnectionState;
 use crate::webrtc::util::Conn;
 use tokio::sync::{mpsc, Mutex};
@@ -31,6 +31,7 @@ pub(crate) mod ice_gathering_state;
 pub(crate) mod ice_parameters;
 pub(crate) mod ice_protocol;
 pub(crate) mod ice_role;
+pub(crate) mod ice_server;
 pub(crate) mod ice_transport_state;
 
 pub(crate) type OnConnectionStateChangeHdlrFn = Box<
@@ -45,6 +46,9 @@ pub(crate) type OnSelectedCandidatePairChangeHdlrFn = Box<
         + Sync,
 >;
 
+pub(crate) type OnNetworkChangeHdlrFn =
+    Box<dyn (FnMut() -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>) + Send + Sync>;
+
 #[derive(Default)]
 struct ICETransportInternal {
     role: RTCIceRole,
@@ -61,6 +65,7 @@ pub(crate) struct RTCIceTransport {
     on_connection_state_change_handler: Arc<Mutex<Option<OnConnectionStateChangeHdlrFn>>>,
     on_selected_candidate_pair_change_handler:
         Arc<Mutex<Option<OnSelectedCandidatePairChangeHdlrFn>>>,
+    on_network_change_handler: Arc<Mutex<Option<OnNetworkChangeHdlrFn>>>,
     state: Arc<AtomicU8>, // ICETransportState
     internal: Mutex<ICETransportInternal>,
 }
@@ -128,6 +133,19 @@ impl RTCIceTransport {
                 ))
                 .await;
 
+            let on_network_change_handler = Arc::clone(&self.on_network_change_handler);
+            agent
+                .on_network_change(Box::new(move || {
+                    let on_network_change_handler_clone = Arc::clone(&on_network_change_handler);
+                    Box::pin(async move {
+                        let mut handler = on_network_change_handler_clone.lock().await;
+                        if let Some(f) = &mut *handler {
+                            f().await;
+                        }
+                    })
+                }))
+                .await;
+
             let role = if let Some(role) = role {
                 role
             } else {
@@ -206,6 +224,15 @@ impl RTCIceTransport {
         *on_connection_state_change_handler = Some(f);
     }
 
+    /// on_network_change sets a handler that is fired when a change of the
+    /// local IPs cuts off the selected pair, or offers a way back while the
+    /// transport is disconnected or failed, after which ICE should be
+    /// restarted to move to the new network.
+    pub(crate) async fn on_network_change(&self, f: OnNetworkChangeHdlrFn) {
+        let mut on_network_change_handler = self.on_network_change_handler.lock().await;
+        *on_network_change_handler = Some(f);
+    }
+
     /// adds a candidate associated with the remote ICETransport.
     pub(crate) async fn add_remote_candidate(
         &self,
@@ -225,6 +252,59 @@ impl RTCIceTransport {
         }
     }
 
+    /// restart gathers candidates again under new local credentials. The
+    /// transport's conn is kept, so DTLS carries on once checks with the
+    /// remote's new credentials select a pair.
+    pub(crate) async fn restart(&self) -> Result<()> {
+        if let Some(agent) = self.gatherer.get_agent().await {
+            agent.restart(String::new(), String::new()).await?;
+        } else {
+            return Err(Error::ErrICEAgentNotExist);
+        }
+
+        self.gatherer.gather().await
+    }
+
+    /// set_remote_credentials sets the remote credentials after an ICE restart
+    pub(crate) async fn set_remote_credentials(
+        &self,
+        remote_ufrag: String,
+        remote_pwd: String,
+    ) -> Result<()> {
+        if let Some(agent) = self.gatherer.get_agent().await {
+            Ok(agent
+                .set_remote_credentials(remote_ufrag, remote_pwd)
+                .await?)
+        } else {
+            Err(Error::ErrICEAgentNotExist)
+        }
+    }
+
+    /// Returns the candidate pair the ICE agent selected to send packets on,
+    /// or None if no pair has been selected yet.
+    pub(crate) async fn selected_candidate_pair(&self) -> Option<Arc<CandidatePair>> {
+        let agent = self.gatherer.get_agent().await?;
+        agent.get_selected_candidate_pair().await
+    }
+
+    /// Returns whether the ICE agent failed because the remote stopped
+    /// granting consent to send, rather than going silent for its timeouts.
+    pub(crate) async fn consent_expired(&self) -> bool {
+        match self.gatherer.get_agent().await {
+            Some(agent) => agent.consent_expired(),
+            None => false,
+        }
+    }
+
+    /// Returns the number of bytes sent and received by the ICE agent, or
+    /// zeroes if the agent has not been created yet.
+    pub(crate) async fn bytes_sent_received(&self) -> (usize, usize) {
+        match self.gatherer.get_agent().await {
+            Some(agent) => (agent.bytes_sent(), agent.bytes_received()),
+            None => (0, 0),
+        }
+    }
+
     /// State returns the current ice transport state.
     pub(crate) fn state(&self) -> RTCIceTransportState {
         RTCIceTransportState::from(self.state.load(Ordering::SeqCst))
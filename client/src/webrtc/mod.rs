@@ -3,9 +3,11 @@ mod crates;
 // re-export sub-crates
 pub(crate) use crates::dtls;
 pub(crate) use crates::ice;
+pub(crate) use crates::mdns;
 pub(crate) use crates::sctp;
 pub(crate) use crates::sdp;
 pub(crate) use crates::stun;
+pub(crate) use crates::turn;
 pub(crate) use crates::util;
 pub(crate) use data_channel::internal;
 
@@ -17,6 +19,7 @@ pub(crate) mod ice_transport;
 pub(crate) mod mux;
 pub(crate) mod peer_connection;
 pub(crate) mod sctp_transport;
+pub(crate) mod setting_engine;
 
 pub(crate) const UNSPECIFIED_STR: &str = "Unspecified";
 
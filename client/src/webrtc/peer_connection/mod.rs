@@ -1,4 +1,5 @@
 pub(crate) mod certificate;
+pub(crate) mod offer_answer_options;
 pub(crate) mod operation;
 mod peer_connection_internal;
 pub(crate) mod peer_connection_state;
@@ -7,6 +8,7 @@ pub(crate) mod sdp;
 pub(crate) mod signaling_state;
 
 use crate::webrtc::api::API;
+use crate::webrtc::data_channel::data_channel_init::RTCDataChannelInit;
 use crate::webrtc::data_channel::data_channel_state::RTCDataChannelState;
 use crate::webrtc::data_channel::RTCDataChannel;
 use crate::webrtc::dtls_transport::dtls_fingerprint::RTCDtlsFingerprint;
@@ -14,16 +16,17 @@ use crate::webrtc::dtls_transport::dtls_parameters::DTLSParameters;
 use crate::webrtc::dtls_transport::dtls_role::{DTLSRole, DEFAULT_DTLS_ROLE_OFFER};
 use crate::webrtc::dtls_transport::dtls_transport_state::RTCDtlsTransportState;
 use crate::webrtc::dtls_transport::RTCDtlsTransport;
-use crate::webrtc::error::{Error, Result};
+use crate::webrtc::error::{Error, OnErrorHdlrFn, Result};
 use crate::webrtc::ice_transport::ice_candidate::RTCIceCandidate;
 use crate::webrtc::ice_transport::ice_connection_state::RTCIceConnectionState;
-use crate::webrtc::ice_transport::ice_gatherer::RTCIceGatherer;
+use crate::webrtc::ice_transport::ice_gatherer::{OnLocalCandidateHdlrFn, RTCIceGatherer};
 use crate::webrtc::ice_transport::ice_gatherer_state::RTCIceGathererState;
 use crate::webrtc::ice_transport::ice_gathering_state::RTCIceGatheringState;
 use crate::webrtc::ice_transport::ice_parameters::RTCIceParameters;
 use crate::webrtc::ice_transport::ice_role::RTCIceRole;
 use crate::webrtc::ice_transport::ice_transport_state::RTCIceTransportState;
 use crate::webrtc::ice_transport::RTCIceTransport;
+use crate::webrtc::peer_connection::offer_answer_options::RTCOfferOptions;
 use crate::webrtc::peer_connection::operation::{Operation, Operations};
 use crate::webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
 use crate::webrtc::peer_connection::sdp::sdp_type::RTCSdpType;
@@ -34,6 +37,7 @@ use crate::webrtc::peer_connection::signaling_state::{
 };
 use crate::webrtc::sctp_transport::sctp_transport_state::RTCSctpTransportState;
 use crate::webrtc::sctp_transport::RTCSctpTransport;
+use crate::webrtc::setting_engine::SettingEngine;
 
 use crate::webrtc::ice::candidate::candidate_base::unmarshal_candidate;
 use crate::webrtc::ice::candidate::Candidate;
@@ -45,7 +49,7 @@ use std::future::Future;
 use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex};
 
 pub(crate) const MEDIA_SECTION_APPLICATION: &str = "application";
 
@@ -105,18 +109,60 @@ impl RTCPeerConnection {
     /// If you wish to customize the set of available codecs or the set of
     /// active interceptors, create a MediaEngine and call api.new_peer_connection
     /// instead of this function.
-    pub(crate) async fn new() -> Arc<RTCPeerConnection> {
-        let internal = PeerConnectionInternal::new()
-            .await
-            .expect("can't create peer connection");
+    pub(crate) async fn new(setting_engine: Arc<SettingEngine>) -> Result<Arc<RTCPeerConnection>> {
+        let internal = PeerConnectionInternal::new(setting_engine).await?;
 
         // <https://w3c.github.io/webrtc-pc/#constructor> (Step #2)
         // Some variables defined explicitly despite their implicit zero values to
         // allow better readability to understand what is happening.
-        Arc::new(RTCPeerConnection {
+        Ok(Arc::new(RTCPeerConnection {
             internal,
             idp_login_url: None,
-        })
+        }))
+    }
+
+    /// on_ice_connection_state_change sets an event handler which is called
+    /// when an ICE connection state is changed.
+    pub(crate) async fn on_ice_connection_state_change(&self, f: OnICEConnectionStateChangeHdlrFn) {
+        let mut on_ice_connection_state_change_handler = self
+            .internal
+            .on_ice_connection_state_change_handler
+            .lock()
+            .await;
+        *on_ice_connection_state_change_handler = Some(f);
+    }
+
+    /// on_peer_connection_state_change sets an event handler which is called
+    /// when the PeerConnectionState has changed
+    pub(crate) async fn on_peer_connection_state_change(
+        &self,
+        f: OnPeerConnectionStateChangeHdlrFn,
+    ) {
+        let mut on_peer_connection_state_change_handler = self
+            .internal
+            .on_peer_connection_state_change_handler
+            .lock()
+            .await;
+        *on_peer_connection_state_change_handler = Some(f);
+    }
+
+    /// on_ice_candidate sets an event handler which is invoked when a new ICE
+    /// candidate is found, and with None once gathering is complete.
+    pub(crate) async fn on_ice_candidate(&self, f: OnLocalCandidateHdlrFn) {
+        let mut on_local_candidate_handler = self
+            .internal
+            .ice_gatherer
+            .on_local_candidate_handler
+            .lock()
+            .await;
+        *on_local_candidate_handler = Some(f);
+    }
+
+    /// on_error sets an event handler which is invoked when the ICE, DTLS or
+    /// SCTP transports fail to start after the remote description is applied.
+    pub(crate) async fn on_error(&self, f: OnErrorHdlrFn) {
+        let mut on_error_handler = self.internal.on_error_handler.lock().await;
+        *on_error_handler = Some(f);
     }
 
     async fn do_signaling_state_change(&self, new_state: RTCSignalingState) {
@@ -157,7 +203,10 @@ impl RTCPeerConnection {
 
     /// create_offer starts the PeerConnection and generates the localDescription
     /// <https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnection-createoffer>
-    pub(crate) async fn create_offer(&self) -> Result<RTCSessionDescription> {
+    pub(crate) async fn create_offer(
+        &self,
+        options: Option<RTCOfferOptions>,
+    ) -> Result<RTCSessionDescription> {
         let use_identity = self.idp_login_url.is_some();
         if use_identity {
             return Err(Error::ErrIdentityProviderNotImplemented);
@@ -165,6 +214,13 @@ impl RTCPeerConnection {
             return Err(Error::ErrConnectionClosed);
         }
 
+        // restart ICE first so the offer carries the new credentials and candidates
+        if let Some(options) = options {
+            if options.ice_restart {
+                self.internal.ice_transport.restart().await?;
+            }
+        }
+
         // This may be necessary to recompute if, for example, createOffer was called when only an
         // audio RTCRtpTransceiver was added to connection, but while performing the in-parallel
         // steps to create an offer, a video RTCRtpTransceiver was added, requiring additional
@@ -577,6 +633,12 @@ impl RTCPeerConnection {
             return Err(Error::ErrConnectionClosed);
         }
 
+        // the transports are already running when answering a later offer
+        let is_renegotiation = {
+            let current_remote_description = self.internal.current_remote_description.lock().await;
+            current_remote_description.is_some()
+        };
+
         desc.parsed = Some(desc.unmarshal()?);
         self.set_description(&desc, StateChangeOp::SetRemote)
             .await?;
@@ -586,6 +648,15 @@ impl RTCPeerConnection {
 
             let (remote_ufrag, remote_pwd, candidates) = extract_ice_details(parsed).await?;
 
+            // an ICE restart cleared the remote credentials, set them before
+            // the new candidates are checked
+            if is_renegotiation {
+                self.internal
+                    .ice_transport
+                    .set_remote_credentials(remote_ufrag.clone(), remote_pwd.clone())
+                    .await?;
+            }
+
             for candidate in candidates {
                 self.internal
                     .ice_transport
@@ -593,6 +664,11 @@ impl RTCPeerConnection {
                     .await?;
             }
 
+            // DTLS and SCTP keep running over whichever pair ICE selects next
+            if is_renegotiation {
+                return Ok(());
+            }
+
             let (fingerprint, fingerprint_hash) = extract_fingerprint(parsed)?;
 
             // If one of the agents is lite and the other one is not, the lite agent must be the controlling agent.
@@ -618,11 +694,18 @@ impl RTCPeerConnection {
                             ice_role,
                             dtls_role,
                         );
-                        pc.start_transports(ice_role, dtls_role, ru, rp, fp, fp_hash)
-                            .await;
+                        if let Err(err) = pc
+                            .start_transports(ice_role, dtls_role, ru, rp, fp, fp_hash)
+                            .await
+                        {
+                            pc.do_error(err).await;
+                            return false;
+                        }
 
                         if we_offer {
-                            let _ = pc.maybe_start_sctp(rd).await;
+                            if let Err(err) = pc.maybe_start_sctp(rd).await {
+                                pc.do_error(err).await;
+                            }
                         }
                         false
                     })
@@ -675,13 +758,14 @@ impl RTCPeerConnection {
         &self,
         label: &str,
         protocol: &str,
+        options: RTCDataChannelInit,
     ) -> Result<Arc<RTCDataChannel>> {
         // https://w3c.github.io/webrtc-pc/#peer-to-peer-data-api (Step #2)
         if self.internal.is_closed.load(Ordering::SeqCst) {
             return Err(Error::ErrConnectionClosed);
         }
 
-        let d = Arc::new(RTCDataChannel::new(label, protocol));
+        let d = Arc::new(RTCDataChannel::new(label, protocol, options));
 
         {
             let mut data_channels = self.internal.sctp_transport.data_channels.lock().await;
@@ -741,4 +825,37 @@ impl RTCPeerConnection {
     pub(crate) fn ice_gathering_state(&self) -> RTCIceGatheringState {
         self.internal.ice_gathering_state()
     }
+
+    /// gathering_complete_promise returns a receiver that is closed once ICE
+    /// gathering completes. Create it before set_local_description, and wait
+    /// on it before reading local_description to get every candidate in it.
+    pub(crate) async fn gathering_complete_promise(&self) -> mpsc::Receiver<()> {
+        let (gathering_complete_tx, gathering_complete_rx) = mpsc::channel(1);
+
+        // dropping the sender closes the receiver
+        let done = Arc::new(Mutex::new(Some(gathering_complete_tx)));
+        let done2 = Arc::clone(&done);
+        {
+            let mut handler = self
+                .internal
+                .ice_gatherer
+                .on_gathering_complete_handler
+                .lock()
+                .await;
+            *handler = Some(Box::new(move || {
+                let done3 = Arc::clone(&done2);
+                Box::pin(async move {
+                    let mut d = done3.lock().await;
+                    d.take();
+                })
+            }));
+        }
+
+        if self.ice_gathering_state() == RTCIceGatheringState::Complete {
+            let mut d = done.lock().await;
+            d.take();
+        }
+
+        gathering_complete_rx
+    }
 }
@@ -0,0 +1,8 @@
+/// RTCOfferOptions describes the options used to control the offer creation process
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct RTCOfferOptions {
+    /// ice_restart forces the underlying ice gathering process to be restarted.
+    /// When this value is true, the generated description will have ICE
+    /// credentials that are different from the current credentials
+    pub(crate) ice_restart: bool,
+}
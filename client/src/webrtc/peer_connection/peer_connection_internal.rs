@@ -1,4 +1,5 @@
 use crate::webrtc::peer_connection::*;
+use crate::webrtc::sctp_transport::sctp_transport_capabilities::SCTPTransportCapabilities;
 use std::sync::atomic::AtomicIsize;
 use tokio::sync::Notify;
 
@@ -33,6 +34,7 @@ pub(crate) struct PeerConnectionInternal {
     pub(crate) on_ice_connection_state_change_handler:
         Arc<Mutex<Option<OnICEConnectionStateChangeHdlrFn>>>,
     pub(crate) on_data_channel_handler: Arc<Mutex<Option<OnDataChannelHdlrFn>>>,
+    pub(crate) on_error_handler: Arc<Mutex<Option<OnErrorHdlrFn>>>,
 
     pub(crate) ice_gatherer: Arc<RTCIceGatherer>,
 
@@ -43,7 +45,7 @@ pub(crate) struct PeerConnectionInternal {
 }
 
 impl PeerConnectionInternal {
-    pub(crate) async fn new() -> Result<Arc<Self>> {
+    pub(crate) async fn new(setting_engine: Arc<SettingEngine>) -> Result<Arc<Self>> {
         let mut pc = PeerConnectionInternal {
             greater_mid: AtomicIsize::new(-1),
             sdp_origin: Mutex::new(Default::default()),
@@ -59,6 +61,7 @@ impl PeerConnectionInternal {
             on_signaling_state_change_handler: Arc::new(Default::default()),
             on_ice_connection_state_change_handler: Arc::new(Default::default()),
             on_data_channel_handler: Arc::new(Default::default()),
+            on_error_handler: Arc::new(Default::default()),
             ice_gatherer: Arc::new(Default::default()),
             current_local_description: Arc::new(Default::default()),
             current_remote_description: Arc::new(Default::default()),
@@ -69,16 +72,25 @@ impl PeerConnectionInternal {
         };
 
         // Create the ice gatherer
-        pc.ice_gatherer = Arc::new(API::new_ice_gatherer()?);
+        pc.ice_gatherer = Arc::new(API::new_ice_gatherer(Arc::clone(&setting_engine))?);
 
         // Create the ice transport
-        pc.ice_transport = pc.create_ice_transport().await;
+        pc.ice_transport = Arc::new(API::new_ice_transport(Arc::clone(&pc.ice_gatherer)));
 
         // Create the DTLS transport
-        pc.dtls_transport = Arc::new(API::new_dtls_transport(Arc::clone(&pc.ice_transport))?);
+        pc.dtls_transport = Arc::new(API::new_dtls_transport(
+            Arc::clone(&pc.ice_transport),
+            Arc::clone(&setting_engine),
+        )?);
+
+        // Wire up the ice connection state handler, it needs the DTLS transport state
+        pc.wire_ice_transport().await;
 
         // Create the SCTP transport
-        pc.sctp_transport = Arc::new(API::new_sctp_transport(Arc::clone(&pc.dtls_transport))?);
+        pc.sctp_transport = Arc::new(API::new_sctp_transport(
+            Arc::clone(&pc.dtls_transport),
+            setting_engine,
+        )?);
 
         // Wire up the on datachannel handler
         let on_data_channel_handler = Arc::clone(&pc.on_data_channel_handler);
@@ -112,7 +124,10 @@ impl PeerConnectionInternal {
 
         if let Some(parsed) = &remote_desc.parsed {
             if have_application_media_section(parsed) {
-                self.start_sctp().await;
+                self.start_sctp(SCTPTransportCapabilities {
+                    max_message_size: get_max_message_size(parsed),
+                })
+                .await?;
             }
         }
 
@@ -120,15 +135,15 @@ impl PeerConnectionInternal {
     }
 
     /// Start SCTP subsystem
-    async fn start_sctp(&self) {
+    async fn start_sctp(&self, remote_caps: SCTPTransportCapabilities) -> Result<()> {
         // Start sctp
-        if let Err(err) = self.sctp_transport.start().await {
+        if let Err(err) = self.sctp_transport.start(remote_caps).await {
             log::warn!("Failed to start SCTP: {}", err);
             if let Err(err) = self.sctp_transport.stop().await {
                 log::warn!("Failed to stop SCTPTransport: {}", err);
             }
 
-            return;
+            return Err(err);
         }
 
         // DataChannels that need to be opened now that SCTP is available
@@ -141,10 +156,9 @@ impl PeerConnectionInternal {
         let mut opened_dc_count = 0;
         for d in data_channels {
             if d.ready_state() == RTCDataChannelState::Connecting {
-                if let Err(err) = d.open(Arc::clone(&self.sctp_transport)).await {
-                    log::warn!("failed to open data channel: {}", err);
-                    continue;
-                }
+                // the socket waits for every data channel it declared, so
+                // one failing to open fails the connection through on_error
+                d.open(Arc::clone(&self.sctp_transport)).await?;
                 opened_dc_count += 1;
             }
         }
@@ -152,6 +166,17 @@ impl PeerConnectionInternal {
         self.sctp_transport
             .data_channels_opened
             .fetch_add(opened_dc_count, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    /// do_error invokes the on_error handler with a transport failure
+    pub(crate) async fn do_error(&self, err: Error) {
+        log::warn!("peer connection transport error: {}", err);
+        let mut handler = self.on_error_handler.lock().await;
+        if let Some(f) = &mut *handler {
+            f(err).await;
+        }
     }
 
     pub(crate) async fn remote_description(self: &Arc<Self>) -> Option<RTCSessionDescription> {
@@ -173,7 +198,7 @@ impl PeerConnectionInternal {
         remote_pwd: String,
         fingerprint: String,
         fingerprint_hash: String,
-    ) {
+    ) -> Result<()> {
         // Start the ice transport
         if let Err(err) = self
             .ice_transport
@@ -187,7 +212,7 @@ impl PeerConnectionInternal {
             .await
         {
             log::warn!("Failed to start manager ice: {}", err);
-            return;
+            return Err(err);
         }
 
         // Start the dtls_transport transport
@@ -211,7 +236,10 @@ impl PeerConnectionInternal {
         .await;
         if let Err(err) = result {
             log::warn!("Failed to start manager dtls: {}", err);
+            return Err(err);
         }
+
+        Ok(())
     }
 
     pub(crate) async fn close(&self) {
@@ -225,12 +253,13 @@ impl PeerConnectionInternal {
         self.ice_transport.close().await;
 
         // SCTP
-        self.sctp_transport.stop().await.unwrap();
+        if let Err(err) = self.sctp_transport.stop().await {
+            log::warn!("Failed to stop SCTPTransport: {}", err);
+        }
 
         // DTLS
-        let mut dtls_conn = self.dtls_transport.conn.lock().await;
-        if let Some(conn) = &mut *dtls_conn {
-            conn.close().await.unwrap();
+        if let Err(err) = self.dtls_transport.stop().await {
+            log::warn!("Failed to stop DTLSTransport: {}", err);
         }
     }
 
@@ -271,6 +300,7 @@ impl PeerConnectionInternal {
             is_icelite: false,
             connection_role: DEFAULT_DTLS_ROLE_OFFER.to_connection_role(),
             ice_gathering_state: self.ice_gathering_state(),
+            max_message_size: self.sctp_transport.max_receive_message_size(),
         };
         populate_sdp(
             d,
@@ -343,6 +373,7 @@ impl PeerConnectionInternal {
             is_icelite: false,
             connection_role,
             ice_gathering_state: self.ice_gathering_state(),
+            max_message_size: self.sctp_transport.max_receive_message_size(),
         };
         populate_sdp(
             d,
@@ -363,9 +394,7 @@ impl PeerConnectionInternal {
         }
     }
 
-    pub(crate) async fn create_ice_transport(&self) -> Arc<RTCIceTransport> {
-        let ice_transport = Arc::new(API::new_ice_transport(Arc::clone(&self.ice_gatherer)));
-
+    pub(crate) async fn wire_ice_transport(&self) {
         let ice_connection_state = Arc::clone(&self.ice_connection_state);
         let peer_connection_state = Arc::clone(&self.peer_connection_state);
         let is_closed = Arc::clone(&self.is_closed);
@@ -375,7 +404,7 @@ impl PeerConnectionInternal {
         let on_peer_connection_state_change_handler =
             Arc::clone(&self.on_peer_connection_state_change_handler);
 
-        ice_transport
+        self.ice_transport
             .on_connection_state_change(Box::new(move |state: RTCIceTransportState| {
                 let cs = match state {
                     RTCIceTransportState::New => RTCIceConnectionState::New,
@@ -418,7 +447,5 @@ impl PeerConnectionInternal {
                 })
             }))
             .await;
-
-        ice_transport
     }
 }
@@ -11,6 +11,7 @@ pub(crate) mod session_description;
 use crate::webrtc::ice::candidate::candidate_base::unmarshal_candidate;
 use crate::webrtc::ice::candidate::Candidate;
 use crate::webrtc::peer_connection::MEDIA_SECTION_APPLICATION;
+use crate::webrtc::sctp::association::DEFAULT_MAX_MESSAGE_SIZE;
 use crate::webrtc::sdp::description::common::{Address, ConnectionInformation};
 use crate::webrtc::sdp::description::media::{MediaDescription, MediaName, RangedPort};
 use crate::webrtc::sdp::description::session::*;
@@ -64,6 +65,7 @@ pub(crate) struct AddDataMediaSectionParams {
     ice_params: RTCIceParameters,
     dtls_role: ConnectionRole,
     ice_gathering_state: RTCIceGatheringState,
+    max_message_size: u32,
 }
 
 pub(crate) async fn add_data_media_section(
@@ -103,6 +105,10 @@ pub(crate) async fn add_data_media_section(
     .with_value_attribute(ATTR_KEY_MID.to_owned(), params.mid_value)
     .with_property_attribute("sendrecv".to_owned())
     .with_property_attribute("sctp-port:5000".to_owned())
+    .with_value_attribute(
+        ATTR_KEY_MAX_MESSAGE_SIZE.to_owned(),
+        params.max_message_size.to_string(),
+    )
     .with_ice_credentials(
         params.ice_params.username_fragment,
         params.ice_params.password,
@@ -171,6 +177,7 @@ pub(crate) struct PopulateSdpParams {
     pub(crate) is_icelite: bool,
     pub(crate) connection_role: ConnectionRole,
     pub(crate) ice_gathering_state: RTCIceGatheringState,
+    pub(crate) max_message_size: u32,
 }
 
 /// populate_sdp serializes a PeerConnections state into an SDP
@@ -201,6 +208,7 @@ pub(crate) async fn populate_sdp(
                 ice_params: ice_params.clone(),
                 dtls_role: params.connection_role,
                 ice_gathering_state: params.ice_gathering_state,
+                max_message_size: params.max_message_size,
             };
             d = add_data_media_section(d, &media_dtls_fingerprints, candidates, params).await?;
             true
@@ -313,8 +321,16 @@ pub(crate) async fn extract_ice_details(
         for a in &m.attributes {
             if a.is_ice_candidate() {
                 if let Some(value) = &a.value {
-                    let c: Arc<dyn Candidate + Send + Sync> =
-                        Arc::new(unmarshal_candidate(value).await?);
+                    // one candidate this agent cannot use, such as a prflx
+                    // or an unknown type, leaves the others usable
+                    let c: Arc<dyn Candidate + Send + Sync> = match unmarshal_candidate(value).await
+                    {
+                        Ok(c) => Arc::new(c),
+                        Err(err) => {
+                            log::warn!("skipping remote candidate {}: {}", value, err);
+                            continue;
+                        }
+                    };
                     let candidate = RTCIceCandidate::from(&c);
                     candidates.push(candidate);
                 }
@@ -343,6 +359,25 @@ pub(crate) async fn extract_ice_details(
     Ok((remote_ufrags[0].clone(), remote_pwds[0].clone(), candidates))
 }
 
+/// get_max_message_size returns the remote's a=max-message-size, or 65536
+/// if the attribute is absent as <https://www.rfc-editor.org/rfc/rfc8841#section-6.1> requires
+pub(crate) fn get_max_message_size(desc: &SessionDescription) -> u32 {
+    for m in &desc.media_descriptions {
+        if m.media_name.media != MEDIA_SECTION_APPLICATION {
+            continue;
+        }
+        if let Some(max_message_size) = m
+            .attribute(ATTR_KEY_MAX_MESSAGE_SIZE)
+            .and_then(|o| o)
+            .and_then(|value| value.trim().parse::<u32>().ok())
+        {
+            return max_message_size;
+        }
+    }
+
+    DEFAULT_MAX_MESSAGE_SIZE
+}
+
 pub(crate) fn have_application_media_section(desc: &SessionDescription) -> bool {
     for m in &desc.media_descriptions {
         if m.media_name.media == MEDIA_SECTION_APPLICATION {
@@ -1,13 +1,18 @@
+pub(crate) mod sctp_transport_capabilities;
 pub(crate) mod sctp_transport_state;
 
+use sctp_transport_capabilities::SCTPTransportCapabilities;
 use sctp_transport_state::RTCSctpTransportState;
 
 use crate::webrtc::data_channel::RTCDataChannel;
+use crate::webrtc::dtls_transport::dtls_role::DTLSRole;
 use crate::webrtc::dtls_transport::*;
 use crate::webrtc::error::*;
 use crate::webrtc::sctp::association::Association;
+use crate::webrtc::setting_engine::SettingEngine;
 use crate::webrtc::util::Conn;
 
+use std::collections::HashSet;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, Ordering};
@@ -21,14 +26,18 @@ pub(crate) type OnDataChannelHdlrFn = Box<
         + Sync,
 >;
 
+pub(crate) type OnSctpTransportStateChangeHdlrFn = Box<
+    dyn (FnMut(RTCSctpTransportState) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>)
+        + Send
+        + Sync,
+>;
+
 /// SCTPTransport provides details about the SCTP transport.
 #[derive(Default)]
 pub(crate) struct RTCSctpTransport {
-    // removing this causes compile panic, last checked
-    #[allow(dead_code)]
-    max_message_size: bool,
-    #[allow(dead_code)]
-    setting_engine: bool,
+    // the largest message the remote accepts, negotiated when the transport starts
+    max_message_size: AtomicU32,
+    setting_engine: Arc<SettingEngine>,
 
     pub(crate) dtls_transport: Arc<RTCDtlsTransport>,
 
@@ -42,6 +51,7 @@ pub(crate) struct RTCSctpTransport {
     sctp_association: Mutex<Option<Arc<Association>>>,
 
     on_data_channel_handler: Arc<Mutex<Option<OnDataChannelHdlrFn>>>,
+    on_state_change_handler: Arc<Mutex<Option<OnSctpTransportStateChangeHdlrFn>>>,
 
     // DataChannels
     pub(crate) data_channels: Arc<Mutex<Vec<Arc<RTCDataChannel>>>>,
@@ -52,16 +62,20 @@ pub(crate) struct RTCSctpTransport {
 }
 
 impl RTCSctpTransport {
-    pub(crate) fn new(dtls_transport: Arc<RTCDtlsTransport>) -> Self {
+    pub(crate) fn new(
+        dtls_transport: Arc<RTCDtlsTransport>,
+        setting_engine: Arc<SettingEngine>,
+    ) -> Self {
         RTCSctpTransport {
-            setting_engine: true,
-            max_message_size: true,
+            max_message_size: AtomicU32::new(0),
+            setting_engine,
 
             dtls_transport,
             state: AtomicU8::new(RTCSctpTransportState::Connecting as u8),
             is_started: AtomicBool::new(false),
             sctp_association: Mutex::new(None),
             on_data_channel_handler: Arc::new(Mutex::new(None)),
+            on_state_change_handler: Arc::new(Mutex::new(None)),
             data_channels: Arc::new(Mutex::new(vec![])),
             data_channels_opened: Arc::new(AtomicU32::new(0)),
             data_channels_requested: Arc::new(AtomicU32::new(0)),
@@ -77,20 +91,34 @@ impl RTCSctpTransport {
     /// Start the SCTPTransport. Since both local and remote parties must mutually
     /// create an SCTPTransport, SCTP SO (Simultaneous Open) is used to establish
     /// a connection over SCTP.
-    pub(crate) async fn start(&self) -> Result<()> {
+    pub(crate) async fn start(&self, remote_caps: SCTPTransportCapabilities) -> Result<()> {
         if self.is_started.load(Ordering::SeqCst) {
             return Ok(());
         }
         self.is_started.store(true, Ordering::SeqCst);
 
+        // a remote max-message-size of 0 means it accepts messages of any size
+        let max_message_size = match remote_caps.max_message_size {
+            0 => u32::MAX,
+            max_message_size => max_message_size,
+        };
+        self.max_message_size
+            .store(max_message_size, Ordering::SeqCst);
+
+        // the receive window must hold at least one message of the size we advertise
+        let max_receive_buffer_size = self
+            .setting_engine
+            .sctp_max_receive_buffer_size
+            .max(self.setting_engine.sctp_max_message_size);
+
         let dtls_transport = self.transport();
         if let Some(net_conn) = &dtls_transport.conn().await {
             let sctp_association = Arc::new(
                 crate::webrtc::sctp::association::Association::client(
                     crate::webrtc::sctp::association::Config {
                         net_conn: Arc::clone(net_conn) as Arc<dyn Conn + Send + Sync>,
-                        max_receive_buffer_size: 0,
-                        max_message_size: 0,
+                        max_receive_buffer_size,
+                        max_message_size,
                         name: String::new(),
                     },
                 )
@@ -101,8 +129,7 @@ impl RTCSctpTransport {
                 let mut sa = self.sctp_association.lock().await;
                 *sa = Some(Arc::clone(&sctp_association));
             }
-            self.state
-                .store(RTCSctpTransportState::Connected as u8, Ordering::SeqCst);
+            self.state_change(RTCSctpTransportState::Connected).await;
 
             Ok(())
         } else {
@@ -110,6 +137,18 @@ impl RTCSctpTransport {
         }
     }
 
+    /// max_message_size returns the largest message the remote accepts, 0
+    /// until the transport has started
+    pub(crate) fn max_message_size(&self) -> u32 {
+        self.max_message_size.load(Ordering::SeqCst)
+    }
+
+    /// max_receive_message_size returns the largest message the local
+    /// endpoint accepts, as advertised in the local description
+    pub(crate) fn max_receive_message_size(&self) -> u32 {
+        self.setting_engine.sctp_max_message_size
+    }
+
     /// Stop stops the SCTPTransport
     pub(crate) async fn stop(&self) -> Result<()> {
         {
@@ -119,8 +158,7 @@ impl RTCSctpTransport {
             }
         }
 
-        self.state
-            .store(RTCSctpTransportState::Closed as u8, Ordering::SeqCst);
+        self.state_change(RTCSctpTransportState::Closed).await;
 
         self.notify_tx.notify_waiters();
 
@@ -134,11 +172,50 @@ impl RTCSctpTransport {
         *handler = Some(f);
     }
 
+    /// on_state_change sets a handler that is fired when the SCTP
+    /// transport state changes.
+    pub(crate) async fn on_state_change(&self, f: OnSctpTransportStateChangeHdlrFn) {
+        let mut handler = self.on_state_change_handler.lock().await;
+        *handler = Some(f);
+    }
+
+    async fn state_change(&self, state: RTCSctpTransportState) {
+        if self.state.swap(state as u8, Ordering::SeqCst) == state as u8 {
+            return;
+        }
+        let mut handler = self.on_state_change_handler.lock().await;
+        if let Some(f) = &mut *handler {
+            f(state).await;
+        }
+    }
+
     /// state returns the current state of the SCTPTransport
     pub(crate) fn state(&self) -> RTCSctpTransportState {
         self.state.load(Ordering::SeqCst).into()
     }
 
+    /// generate_data_channel_id returns the lowest stream id not used by an
+    /// opened data channel, even for the DTLS client and odd for the DTLS
+    /// server as <https://www.rfc-editor.org/rfc/rfc8832#section-6> requires.
+    pub(crate) async fn generate_data_channel_id(&self, dtls_role: DTLSRole) -> Result<u16> {
+        let mut ids = HashSet::new();
+        {
+            let data_channels = self.data_channels.lock().await;
+            for dc in &*data_channels {
+                if let Some(id) = dc.id().await {
+                    ids.insert(id);
+                }
+            }
+        }
+
+        // 65535 is reserved, <https://www.rfc-editor.org/rfc/rfc8831#section-6.6>
+        let first_id = if dtls_role == DTLSRole::Client { 0 } else { 1 };
+        (first_id..u16::MAX)
+            .step_by(2)
+            .find(|id| !ids.contains(id))
+            .ok_or(Error::ErrMaxDataChannelID)
+    }
+
     pub(crate) async fn association(&self) -> Option<Arc<Association>> {
         let sctp_association = self.sctp_association.lock().await;
         sctp_association.clone()
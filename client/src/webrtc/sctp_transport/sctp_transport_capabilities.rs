@@ -0,0 +1,7 @@
+/// SCTPTransportCapabilities indicates the capabilities of the SCTPTransport.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct SCTPTransportCapabilities {
+    /// max_message_size is the largest message the remote accepts, 0 if it
+    /// accepts messages of any size
+    pub(crate) max_message_size: u32,
+}
@@ -0,0 +1,120 @@
+use std::fmt;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::webrtc::dtls::cipher_suite::CipherSuiteId;
+use crate::webrtc::ice::mdns::MulticastDnsMode;
+use crate::webrtc::ice::network_type::NetworkType;
+use crate::webrtc::ice::udp_mux::UdpMux;
+use crate::webrtc::ice_transport::ice_server::RTCIceServer;
+use crate::webrtc::sctp::association::{DEFAULT_MAX_MESSAGE_SIZE, INITIAL_RECV_BUF_SIZE};
+
+/// SettingEngine allows influencing behavior in ways that are not
+/// supported by the WebRTC API. This allows us to support additional
+/// use-cases without deviating from the WebRTC API elsewhere.
+#[derive(Debug, Clone)]
+pub(crate) struct SettingEngine {
+    /// disable_certificate_fingerprint_verification skips comparing the
+    /// remote DTLS certificate with the fingerprints in the remote
+    /// description. This leaves the connection open to man-in-the-middle
+    /// attacks, and should be used only for testing.
+    pub(crate) disable_certificate_fingerprint_verification: bool,
+    /// sctp_max_message_size is the largest message the local endpoint
+    /// accepts, advertised with the a=max-message-size attribute.
+    pub(crate) sctp_max_message_size: u32,
+    /// sctp_max_receive_buffer_size is the SCTP receive window, it is raised
+    /// to sctp_max_message_size if smaller.
+    pub(crate) sctp_max_receive_buffer_size: u32,
+    /// ice_disconnected_timeout is passed to the ICE agent, None uses its default.
+    pub(crate) ice_disconnected_timeout: Option<Duration>,
+    /// ice_failed_timeout is passed to the ICE agent, None uses its default.
+    pub(crate) ice_failed_timeout: Option<Duration>,
+    /// ice_keepalive_interval is passed to the ICE agent, None uses its default.
+    pub(crate) ice_keepalive_interval: Option<Duration>,
+    /// ice_consent_timeout is passed to the ICE agent, None uses its default.
+    pub(crate) ice_consent_timeout: Option<Duration>,
+    /// ice_network_monitor_interval is passed to the ICE agent, None never
+    /// checks the local interfaces.
+    pub(crate) ice_network_monitor_interval: Option<Duration>,
+    /// ice_multicast_dns_mode controls whether .local candidates are
+    /// resolved and whether host candidates are published under a .local
+    /// name, Unspecified uses the agent's default.
+    pub(crate) ice_multicast_dns_mode: MulticastDnsMode,
+    /// ice_network_types are the networks candidates are gathered on, empty
+    /// gathers on every supported network.
+    pub(crate) ice_network_types: Vec<NetworkType>,
+    /// ice_tcp also gathers TCP candidates on the IP families of
+    /// ice_network_types.
+    pub(crate) ice_tcp: bool,
+    /// ice_interface_filter restricts the interfaces host candidates are
+    /// gathered on by name.
+    pub(crate) ice_interface_filter: Option<InterfaceFilter>,
+    /// ice_ip_filter restricts the local IPs host candidates are gathered on.
+    pub(crate) ice_ip_filter: Option<IpFilter>,
+    /// ice_port_min and ice_port_max bound the ports local UDP sockets are
+    /// bound to, 0 leaves that end unbounded.
+    pub(crate) ice_port_min: u16,
+    pub(crate) ice_port_max: u16,
+    /// ice_local_ip binds every local socket to this IP, None gathers on
+    /// every interface.
+    pub(crate) ice_local_ip: Option<IpAddr>,
+    /// ice_udp_mux is the shared socket the UDP host candidate is gathered
+    /// on, None binds a socket per interface.
+    pub(crate) ice_udp_mux: Option<Arc<UdpMux>>,
+    /// dtls_flight_interval controls how often DTLS handshake flights are
+    /// retransmitted, None uses the DTLS default.
+    pub(crate) dtls_flight_interval: Option<Duration>,
+    /// dtls_cipher_suites restricts the offered DTLS cipher suites, empty
+    /// offers the DTLS defaults.
+    pub(crate) dtls_cipher_suites: Vec<CipherSuiteId>,
+    /// ice_servers are the STUN and TURN servers server reflexive and relay
+    /// candidates are gathered from, parsed when the ICE agent is created.
+    pub(crate) ice_servers: Vec<RTCIceServer>,
+}
+
+impl Default for SettingEngine {
+    fn default() -> Self {
+        SettingEngine {
+            disable_certificate_fingerprint_verification: false,
+            sctp_max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            sctp_max_receive_buffer_size: INITIAL_RECV_BUF_SIZE,
+            ice_disconnected_timeout: None,
+            ice_failed_timeout: None,
+            ice_keepalive_interval: None,
+            ice_consent_timeout: None,
+            ice_network_monitor_interval: None,
+            ice_multicast_dns_mode: MulticastDnsMode::Unspecified,
+            ice_network_types: vec![],
+            ice_tcp: false,
+            ice_interface_filter: None,
+            ice_ip_filter: None,
+            ice_port_min: 0,
+            ice_port_max: 0,
+            ice_local_ip: None,
+            ice_udp_mux: None,
+            dtls_flight_interval: None,
+            dtls_cipher_suites: vec![],
+            ice_servers: vec![],
+        }
+    }
+}
+
+pub(crate) type InterfaceFilter = GatherFilter<dyn Fn(&str) -> bool + Send + Sync>;
+pub(crate) type IpFilter = GatherFilter<dyn Fn(IpAddr) -> bool + Send + Sync>;
+
+/// GatherFilter shares a candidate gathering filter between the settings and
+/// every ICE agent created from them.
+pub(crate) struct GatherFilter<F: ?Sized>(pub(crate) Arc<F>);
+
+impl<F: ?Sized> Clone for GatherFilter<F> {
+    fn clone(&self) -> Self {
+        GatherFilter(Arc::clone(&self.0))
+    }
+}
+
+impl<F: ?Sized> fmt::Debug for GatherFilter<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("GatherFilter")
+    }
+}
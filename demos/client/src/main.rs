@@ -1,10 +1,7 @@
 use anyhow::{Error, Result};
-use tokio::{
-    sync::mpsc::{UnboundedReceiver, UnboundedSender},
-    time::Duration,
-};
+use tokio::time::Duration;
 
-use webrtc_unreliable_client::{AddrCell, ServerAddr, Socket};
+use webrtc_unreliable_client::{AddrCell, MessageReceiver, MessageSender, ServerAddr, Socket};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -21,12 +18,19 @@ async fn main() -> Result<()> {
     let (socket, socket_io) = Socket::new();
     socket
         .connect(server_url.as_str(), Some("12345".as_bytes().to_vec()), None)
-        .await;
+        .await?;
 
     let addr_cell_1 = socket_io.addr_cell.clone();
     let addr_cell_2 = socket_io.addr_cell.clone();
     let to_client_receiver = socket_io.to_client_receiver;
     let to_server_sender = socket_io.to_server_sender;
+    let mut to_client_event_receiver = socket_io.to_client_event_receiver;
+    tokio::spawn(async move {
+        while to_client_event_receiver.changed().await.is_ok() {
+            let event = to_client_event_receiver.borrow().clone();
+            log::info!("Client connection event: {:?}", event);
+        }
+    });
     tokio::spawn(async move {
         read_loop(addr_cell_1, to_client_receiver)
             .await
@@ -43,10 +47,7 @@ async fn main() -> Result<()> {
     loop {}
 }
 
-async fn read_loop(
-    addr_cell: AddrCell,
-    mut to_client_receiver: UnboundedReceiver<Box<[u8]>>,
-) -> Result<()> {
+async fn read_loop(addr_cell: AddrCell, mut to_client_receiver: MessageReceiver) -> Result<()> {
     loop {
         let message = match to_client_receiver.recv().await {
             Some(message) => message,
@@ -67,10 +68,7 @@ async fn read_loop(
     }
 }
 
-async fn write_loop(
-    addr_cell: AddrCell,
-    to_server_sender: UnboundedSender<Box<[u8]>>,
-) -> Result<()> {
+async fn write_loop(addr_cell: AddrCell, to_server_sender: MessageSender) -> Result<()> {
     let mut count = 0;
 
     loop {
@@ -88,7 +86,7 @@ async fn write_loop(
                     };
                     let message = "PING".to_string();
                     log::info!("Client send -> {}: {}", addr, message);
-                    match to_server_sender.send(message.as_bytes().into()) {
+                    match to_server_sender.send(message.as_bytes().into()).await {
                         Ok(_) => {},
                         Err(e) => {
                             return Err(Error::new(e));